@@ -0,0 +1,67 @@
+//! Measures how long lexing and parsing a large generated program takes.
+//!
+//! Run with `cargo bench --bench lex_parse`.
+
+use std::time::{Duration, Instant};
+
+use zontanos::{
+    parser_v2::parser::Parser,
+    zon_parser::lexer::{Lexer, Tokenizer},
+};
+
+/// The amount of functions in the generated program, every function is 10 lines
+const FUNCTIONS: usize = 5_000;
+const RUNS: u32 = 20;
+
+fn generate_program(functions: usize) -> String {
+    let mut source = String::new();
+    for index in 0..functions {
+        source.push_str(&format!(
+            "fn compute_{index}(value: i32, range: i32) i32 {{
+\tlet sum: i32 = (value + range * 2 - 10 / 5)
+\tlet letter: char = '\\n'
+\tif sum >= range && value == 0 {{
+\t\treturn sum
+\t}} else {{
+\t\tprintf(^\"%d %c function {index}\\n\", sum, letter)
+\t}}
+\treturn compute_{index}(sum, range)
+}}
+"
+        ));
+    }
+    source
+}
+
+fn millis(duration: Duration) -> f64 {
+    duration.as_secs_f64() * 1000.0
+}
+
+fn main() {
+    let source = generate_program(FUNCTIONS);
+    let lines = source.lines().count();
+
+    // The fastest run is the least disturbed by everything else running on the machine
+    let mut lexing = Duration::MAX;
+    let mut parsing = Duration::MAX;
+    for _ in 0..RUNS {
+        let start = Instant::now();
+        let mut tokenizer = Tokenizer::new(&source);
+        let tokens = Tokenizer::lex(&mut tokenizer);
+        lexing = lexing.min(start.elapsed());
+
+        let start = Instant::now();
+        let mut parser = Parser::new(&tokens);
+        let ast = parser.parse().expect("the generated program should parse");
+        parsing = parsing.min(start.elapsed());
+        assert_eq!(ast.body.len(), FUNCTIONS);
+    }
+
+    println!(
+        "lex_parse: {lines} lines, {} bytes, fastest of {RUNS} runs",
+        source.len()
+    );
+    println!("  lex:   {:>8.2} ms", millis(lexing));
+    println!("  parse: {:>8.2} ms", millis(parsing));
+    println!("  total: {:>8.2} ms", millis(lexing + parsing));
+}
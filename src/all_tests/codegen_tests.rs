@@ -0,0 +1,441 @@
+//! This contains the tests of the codegen, they compile small programs and check the LLVM IR of
+//! the module or the error the codegen returns
+use zontanos::{gen_ir, parser_v2::modules::Program};
+
+use crate::panic_test;
+
+/// Compiles the source of a program without imports into the LLVM IR of its module
+fn compile(source: &str) -> Result<String, String> {
+    let program = Program::from_source("main.zon", source.to_string()).map_err(|error| error.render())?;
+    gen_ir(&program)
+}
+
+fn compile_ok(test_desc: &str, source: &str) -> String {
+    match compile(source) {
+        Ok(ir) => ir,
+        Err(error) => {
+            panic_test!(test_desc, error);
+        }
+    }
+}
+
+fn compile_err(test_desc: &str, source: &str) -> String {
+    match compile(source) {
+        Ok(ir) => {
+            panic_test!(test_desc, format!("Expected the program to not compile, got:\n{ir}"));
+        }
+        Err(error) => error,
+    }
+}
+
+#[test]
+fn codegen_pointer_params() {
+    let source = "struct Point { x: i32 }
+    fn f(a: i32^, b: Point^, c: string, d: i8^) i32 {
+        return 0
+    }";
+    let ir = compile_ok("Pointer params", source);
+    assert!(ir.contains("@f(i32* %0, %Point* %1, i8* %2, i8* %3)"), "{ir}");
+}
+
+#[test]
+fn codegen_unsupported_param() {
+    let error = compile_err("Unsupported params", "fn f(a: array<i32>) void {\n}");
+    assert!(error.contains("The paramater a has the type Array"), "{error}");
+}
+
+#[test]
+fn codegen_variadic_promotion() {
+    let source = "extern fn printf(fmt: i8^, ...) i32
+    fn main() i32 {
+        let small: i8 = -1
+        let letter: char = 'a'
+        printf(^\"%d %c %f\", small, letter, 1.5)
+        return 0
+    }";
+    let ir = compile_ok("Variadic promotion", source);
+    assert!(ir.contains("declare i32 @printf(i8*, ...)"), "{ir}");
+    // A i8 is sign extended and a char is zero extended to a int, a float becomes a double
+    assert!(ir.contains("sext i8 %loaded to i32"), "{ir}");
+    assert!(ir.contains("zext i8 %loaded1 to i32"), "{ir}");
+    assert!(ir.contains("i32 %int_sext, i32 %int_zext, double 1.500000e+00)"), "{ir}");
+}
+
+#[test]
+fn codegen_extern_param_sign() {
+    let source = "extern fn putchar(c: i32) i32
+    fn main() i32 {
+        let big: u8 = 200
+        putchar(big)
+        return 0
+    }";
+    let ir = compile_ok("Extern param sign", source);
+    // A u8 of 200 stays 200 when it is passed to a i32 paramater
+    assert!(ir.contains("zext i8 %loaded to i32"), "{ir}");
+    assert!(ir.contains("call i32 @putchar(i32 %int_zext)"), "{ir}");
+}
+
+#[test]
+fn codegen_struct_layout() {
+    let source = "struct Line { start: Point, end: Point, id: u8 }
+    struct Point { x: i32, y: i8 }
+    fn main() i32 {
+        let line: Line = Line { id: 1, end: Point { y: 2, x: 3 }, start: Point { x: 4, y: 5 } }
+        line.end.y = 7
+        return line.end.x
+    }";
+    let ir = compile_ok("Struct layout", source);
+    // The fields keep the order of the declaration, a struct can use a struct declared after it
+    assert!(ir.contains("%Point = type { i32, i8 }"), "{ir}");
+    assert!(ir.contains("%Line = type { %Point, %Point, i8 }"), "{ir}");
+    // The fields of a literal are put in the order of the declaration, not the order they are written in
+    assert!(ir.contains("store %Line { %Point { i32 4, i8 5 }, %Point { i32 3, i8 2 }, i8 1 }, %Line* %line"), "{ir}");
+    assert!(ir.contains("%end = getelementptr inbounds %Line, %Line* %line, i32 0, i32 1"), "{ir}");
+    assert!(ir.contains("%y = getelementptr inbounds %Point, %Point* %end, i32 0, i32 1"), "{ir}");
+    assert!(ir.contains("store i8 7, i8* %y"), "{ir}");
+}
+
+#[test]
+fn codegen_struct_errors() {
+    let errors = [
+        (
+            "struct Node { value: i32, next: Node }
+            fn main() i32 {
+                return 0
+            }",
+            "The struct Node can't contain itself, found in field next",
+        ),
+        (
+            "struct Point { x: i32, y: i8 }
+            fn main() i32 {
+                let p: Point = Point { x: 1 }
+                return 0
+            }",
+            "The field y is missing in the literal of struct Point",
+        ),
+    ];
+    for (source, expected) in errors {
+        let error = compile_err("Struct errors", source);
+        assert!(error.contains(expected), "{error}");
+    }
+}
+
+/// Writes the files of a program to a new folder in the temp folder and compiles the program
+/// from its first file
+fn compile_files(name: &str, files: &[(&str, &str)]) -> Result<String, String> {
+    let folder = std::env::temp_dir().join(format!("zontanos_codegen_{name}_{}", std::process::id()));
+    for (path, source) in files {
+        std::fs::create_dir_all(&folder).unwrap();
+        std::fs::write(folder.join(path), source).unwrap();
+    }
+    let compiled = Program::load(folder.join(files[0].0)).map_err(|error| error.render()).and_then(|program| gen_ir(&program));
+    std::fs::remove_dir_all(folder).unwrap();
+    compiled
+}
+
+/// The line after the first line that starts with `label`, the first instruction of that block
+fn block_start<'a>(ir: &'a str, label: &str) -> Option<&'a str> {
+    ir.lines().skip_while(|line| !line.starts_with(label)).nth(1)
+}
+
+#[test]
+fn codegen_enum_match() {
+    let source = "enum Shape { Circle(i32), Rect(i32, i32), Empty }
+    fn area(shape: Shape) i32 {
+        let size: i32 = match shape {
+            Shape::Circle(r) => r * r * 3,
+            Shape::Rect(w, h) => w * h,
+            Shape::Empty => 0,
+        }
+        return size
+    }";
+    let ir = compile_ok("Enum match", source);
+    // A i32 tag and the biggest payload, Rect(i32, i32), made of its most aligned field
+    assert!(ir.contains("%Shape = type { i32, [2 x i32] }"), "{ir}");
+    // Every variant is matched, so no tag ends up in the default block
+    assert!(ir.contains("switch i32 %tag1, label %match_value_unreachable."), "{ir}");
+    assert!(ir.contains("i32 0, label %match_value_arm0."), "{ir}");
+    assert!(ir.contains("i32 2, label %match_value_arm2."), "{ir}");
+    assert_eq!(block_start(&ir, "match_value_unreachable."), Some("  unreachable"), "{ir}");
+}
+
+#[test]
+fn codegen_enum_wildcard() {
+    let source = "enum Shape { Circle(i32), Rect(i32, i32), Empty }
+    fn radius(shape: Shape) i32 {
+        let size: i32 = match shape {
+            Shape::Circle(r) => r,
+            _ => 0,
+        }
+        return size
+    }";
+    let ir = compile_ok("Enum wildcard", source);
+    // The `_` arm is the default of the switch
+    assert!(ir.contains("switch i32 %tag1, label %match_value_arm1."), "{ir}");
+    assert!(!ir.contains("_unreachable."), "{ir}");
+}
+
+#[test]
+fn codegen_enum_match_errors() {
+    let arms = [
+        (
+            "Shape::Circle(r) => r,
+            Shape::Rect(w, h) => w * h,",
+            "The match on Shape doesn't cover Shape::Empty, add a arm for them or a `_` arm",
+        ),
+        (
+            "Shape::Circle(r) => r,
+            Shape::Circle(r) => r,
+            _ => 0,",
+            "The arm on line 4 is never reached, Shape::Circle is already matched",
+        ),
+        (
+            "_ => 0,
+            Shape::Empty => 0,",
+            "The arm on line 4 is never reached, the `_` arm before it matches every value",
+        ),
+    ];
+    for (arms, expected) in arms {
+        let source = format!(
+            "enum Shape {{ Circle(i32), Rect(i32, i32), Empty }}
+            fn area(shape: Shape) i32 {{
+                let size: i32 = match shape {{
+                    {arms}
+                }}
+                return size
+            }}"
+        );
+        let error = compile_err("Enum match errors", &source);
+        assert!(error.contains(expected), "{error}");
+    }
+}
+
+#[test]
+fn codegen_int_match() {
+    let source = "fn half(byte: u8) i32 {
+        let half: i32 = match byte {
+            0..=127 => 0,
+            128..=255 => 1,
+        }
+        return half
+    }";
+    let ir = compile_ok("Int match", source);
+    // The ranges cover every u8, every value is a case and the default block is unreachable
+    assert!(ir.contains("switch i8 %0, label %match_value_unreachable."), "{ir}");
+    assert!(ir.contains("i8 127, label %match_value_arm0."), "{ir}");
+    assert!(ir.contains("i8 -128, label %match_value_arm1."), "{ir}");
+    assert_eq!(block_start(&ir, "match_value_unreachable."), Some("  unreachable"), "{ir}");
+}
+
+#[test]
+fn codegen_int_match_ranges() {
+    let source = "fn size(n: i32) i32 {
+        let size: i32 = match n {
+            0..=9 => 0,
+            10..=100000 => 1,
+            _ => 2,
+        }
+        return size
+    }";
+    let ir = compile_ok("Int match ranges", source);
+    // A range with too many values for cases is checked before the switch
+    assert!(ir.contains("%after_start = icmp sge i32 %0, 10"), "{ir}");
+    assert!(ir.contains("%before_end = icmp sle i32 %0, 100000"), "{ir}");
+    assert!(ir.contains("br i1 %in_range, label %match_value_arm1."), "{ir}");
+    assert!(ir.contains("i32 9, label %match_value_arm0."), "{ir}");
+    assert!(!ir.contains("i32 10, label"), "{ir}");
+    assert!(ir.contains("switch i32 %0, label %match_value_arm2."), "{ir}");
+}
+
+#[test]
+fn codegen_int_match_errors() {
+    let arms = [
+        ("0..=100 => 0,", "The match doesn't cover every value of the integer, 101 isn't matched"),
+        ("256 => 0,\n_ => 1,", "The pattern 256 on line 2 doesn't fit into the value that is matched on, it goes from 0 to 255"),
+        ("1 | 1..=3 => 0,\n_ => 1,", "The patterns of the arm on line 2 overlap, 1..=3 is matched twice"),
+        ("0..=10 => 0,\n5 => 1,\n_ => 2,", "The pattern 5 on line 3 is never reached, the arms before it match all of its values"),
+    ];
+    for (arms, expected) in arms {
+        let source = format!(
+            "fn kind(byte: u8) i32 {{
+            let kind: i32 = match byte {{
+            {arms}
+            }}
+            return kind
+            }}"
+        );
+        let error = compile_err("Int match errors", &source);
+        assert!(error.contains(expected), "{error}");
+    }
+}
+
+#[test]
+fn codegen_globals() {
+    let source = "struct Point { x: i32, y: i8 }
+    let counter: i32 = 5
+    const LIMIT: u8 = 200
+    let origin: Point = Point { y: 2, x: 1 }
+    fn main() i32 {
+        counter = counter + 1
+        return counter
+    }";
+    let ir = compile_ok("Globals", source);
+    // Globals are internal so they can't clash with a symbol of the C library, a const is constant
+    assert!(ir.contains("@counter = internal global i32 5"), "{ir}");
+    assert!(ir.contains("@LIMIT = internal constant i8 -56"), "{ir}");
+    assert!(ir.contains("@origin = internal global %Point { i32 1, i8 2 }"), "{ir}");
+    assert!(ir.lines().any(|line| line.starts_with("  store i32") && line.ends_with(", i32* @counter")), "{ir}");
+}
+
+#[test]
+fn codegen_global_errors() {
+    let globals = [
+        ("let first: i32 = 1\nlet second: i32 = first", "The global second must be given a constant value"),
+        ("let a: i32 = 1\nlet a: i32 = 2", "The global a is declared more than once, found the second one on line 1"),
+    ];
+    for (globals, expected) in globals {
+        let source = format!("{globals}\nfn main() i32 {{\n    return 0\n}}");
+        let error = compile_err("Global errors", &source);
+        assert!(error.contains(expected), "{error}");
+    }
+}
+
+#[test]
+fn codegen_unsigned_compare() {
+    let source = "fn f(b: u8, c: char) i32 {
+        let n: i32 = 200
+        if b > 100 {
+            return 1
+        }
+        if c < n {
+            return 2
+        }
+        return 0
+    }";
+    let ir = compile_ok("Unsigned compare", source);
+    // A literal takes the type of the other side, a u8 is compared unsigned
+    assert!(ir.contains("%comp = icmp ugt i8 %0, 100"), "{ir}");
+    // A char is zero extended to the i32 it is compared with, the comparison is signed
+    assert!(ir.contains("%int_zext = zext i8 %1 to i32"), "{ir}");
+    assert!(ir.contains("icmp slt i32 %int_zext, %if_load"), "{ir}");
+}
+
+#[test]
+fn codegen_return_errors() {
+    let errors = [
+        (
+            "fn f(c: i32) i32 {
+                return if c { 1 } else { 'a' }
+            }",
+            "Expected type value i32 but got value a",
+        ),
+        (
+            "fn f(c: i32) i32 {
+                let small: i8 = 1
+                return if c > 0 { c } else { small }
+            }",
+            "the if block and the else block must have a value of the same type",
+        ),
+    ];
+    for (source, expected) in errors {
+        let error = compile_err("Return errors", source);
+        assert!(error.contains(expected), "{error}");
+    }
+}
+
+#[test]
+fn codegen_if_value_return() {
+    let source = "fn pick(c: i32) i32 {
+        let x: i32 = if c > 0 {
+            return 1
+            2
+        } else {
+            3
+        }
+        return x
+    }";
+    let ir = compile_ok("If value return", source);
+    // The if block returns, it doesn't jump to the merge block or give the phi a value
+    let then_block: Vec<&str> = ir
+        .lines()
+        .skip_while(|line| !line.starts_with("if_value_then."))
+        .skip(1)
+        .take_while(|line| !line.is_empty())
+        .collect();
+    assert_eq!(then_block, ["  ret i32 1"], "{ir}");
+    let phi = ir.lines().find(|line| line.contains("phi i32")).unwrap_or_default();
+    assert!(phi.contains("[ 3, %if_value_else.") && !phi.contains("if_value_then"), "{ir}");
+}
+
+#[test]
+fn codegen_return_float_call() {
+    let source = "let half_value: f32 = 0.5
+    fn half() f32 {
+        return half_value
+    }
+    fn main() f32 {
+        return half()
+    }";
+    let ir = compile_ok("Return float call", source);
+    assert!(ir.contains("%return = call float @half()"), "{ir}");
+    assert!(ir.contains("ret float %return"), "{ir}");
+}
+
+#[test]
+fn codegen_unsigned_widening() {
+    let source = "struct Point { x: i32, y: i8 }
+    fn byte() u8 {
+        return 200
+    }
+    fn main() i32 {
+        let b: u8 = 200
+        let p: Point = Point { x: b, y: 1 }
+        let n: i32 = byte()
+        return n
+    }";
+    let ir = compile_ok("Unsigned widening", source);
+    // A u8 of 200 stays 200 in a i32 field or variable
+    assert!(ir.contains("zext i8 %if_load to i32"), "{ir}");
+    assert!(ir.contains("zext i8 %call to i32"), "{ir}");
+    assert!(!ir.contains("sext"), "{ir}");
+}
+
+#[test]
+fn codegen_module_types() {
+    let files = [
+        (
+            "main.zon",
+            "import \"shapes.zon\"
+            struct Point { x: i8 }
+            fn size(shape: shapes::Shape) i32 {
+                let own: Point = Point { x: 1 }
+                let point: shapes::Point = shapes::Point { x: 1, y: 2 }
+                let size: i32 = match shape {
+                    shapes::Shape::Circle(center) => shapes::width(center),
+                    shapes::Shape::Empty => 0,
+                }
+                return size
+            }",
+        ),
+        (
+            "shapes.zon",
+            "pub struct Point { x: i32, y: i32 }
+            pub enum Shape { Circle(Point), Empty }
+            pub fn width(p: Point) i32 {
+                return p.x
+            }",
+        ),
+    ];
+    let ir = match compile_files("module_types", &files) {
+        Ok(ir) => ir,
+        Err(error) => {
+            panic_test!("Module types", error);
+        }
+    };
+    // Both modules have a Point, the type of a imported module has the prefix of the module
+    assert!(ir.contains("%Point = type { i8 }"), "{ir}");
+    assert!(ir.contains("%\"shapes::Point\" = type { i32, i32 }"), "{ir}");
+    assert!(ir.contains("%\"shapes::Shape\" = type { i32, [2 x i32] }"), "{ir}");
+    // The payload is read as the Point of shapes in main
+    assert!(ir.contains("to { %\"shapes::Point\" }*"), "{ir}");
+}
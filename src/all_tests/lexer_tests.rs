@@ -1,6 +1,10 @@
 use crate::{
     panic_test,
-    zon_parser::lexer::{Keywords, Lexer, Operator, Tokenizer, Tokens},
+    zon_parser::{
+        lexer::{Keywords, Lexer, Operator, TokenErrorMessages, Tokenizer, Tokens},
+        span::Span,
+        trivia::TriviaKind,
+    },
 };
 
 #[test]
@@ -289,3 +293,472 @@ pub fn test_syntax() {
     assert_eq!(let_token.token_type, Tokens::Kw(Keywords::Let));
     assert_eq!(let_token.value, "let");
 }
+
+#[test]
+pub fn test_token_spans() {
+    let str = "let a\n  \"é\" b";
+    let mut tokenizer = Tokenizer::new(str);
+    let lexer = Tokenizer::lex(&mut tokenizer);
+
+    let Some(let_token) = lexer.first() else {
+        panic_test!("Lexer test spans", "Error expected the first token of of lexer tokens to be Some(Kw) but got None");
+    };
+    assert_eq!(let_token.span, Span::new(0, 0, 3, 1, 1));
+
+    let Some(ident_token) = lexer.get(1) else {
+        panic_test!("Lexer test spans", "Error expected the second token of of lexer tokens to be Some(Identifier) but got None");
+    };
+    assert_eq!(ident_token.span, Span::new(0, 4, 5, 1, 5));
+
+    let Some(string_token) = lexer.get(2) else {
+        panic_test!("Lexer test spans", "Error expected the third token of of lexer tokens to be Some(String) but got None");
+    };
+    assert_eq!(string_token.span, Span::new(0, 8, 12, 2, 3));
+    assert_eq!(string_token.span.source_text(str), Some("\"é\""));
+
+    // The column is counted in chars, so the two byte 'é' only moves it by one
+    let Some(last_token) = lexer.get(3) else {
+        panic_test!("Lexer test spans", "Error expected the fourth token of of lexer tokens to be Some(Identifier) but got None");
+    };
+    assert_eq!(last_token.span, Span::new(0, 13, 14, 2, 7));
+}
+
+#[test]
+pub fn test_token_spans_file_id() {
+    let str = "fn";
+    let mut tokenizer = Tokenizer::new(str).with_file_id(3);
+    let lexer = Tokenizer::lex(&mut tokenizer);
+
+    let Some(fn_token) = lexer.first() else {
+        panic_test!("Lexer test spans", "Error expected the first token of of lexer tokens to be Some(Kw) but got None");
+    };
+    assert_eq!(fn_token.span.file_id, 3);
+}
+
+#[test]
+pub fn test_lex_diagnostics() {
+    let str = "let a: i32 = 10 $\nlet b: char = 'é'\n@ \"no end";
+    let mut tokenizer = Tokenizer::new(str);
+    let (tokens, diagnostics) = Tokenizer::lex_with_diagnostics(&mut tokenizer);
+
+    assert!(tokens
+        .iter()
+        .all(|token| !matches!(token.token_type, Tokens::InvalidToken(_))));
+    assert_eq!(diagnostics.len(), 4);
+
+    let Some(dollar) = diagnostics.first() else {
+        panic_test!("Lexer test diagnostics", "Error expected the first diagnostic to be Some(TokenInvalid) but got None");
+    };
+    assert_eq!(dollar.kind, TokenErrorMessages::TokenInvalid("$".into()));
+    assert_eq!((dollar.span.line, dollar.span.column), (1, 17));
+
+    let Some(char_literal) = diagnostics.get(1) else {
+        panic_test!("Lexer test diagnostics", "Error expected the second diagnostic to be Some(InvalidChar) but got None");
+    };
+    assert_eq!(char_literal.kind, TokenErrorMessages::InvalidChar);
+    assert_eq!((char_literal.span.line, char_literal.span.column), (2, 15));
+
+    let Some(at) = diagnostics.get(2) else {
+        panic_test!("Lexer test diagnostics", "Error expected the third diagnostic to be Some(TokenInvalid) but got None");
+    };
+    assert_eq!(at.kind, TokenErrorMessages::TokenInvalid("@".into()));
+    assert_eq!(
+        at.to_string(),
+        "[Lex Error] Found a invalid token '@' on line 3, column 1"
+    );
+
+    let Some(string) = diagnostics.get(3) else {
+        panic_test!("Lexer test diagnostics", "Error expected the fourth diagnostic to be Some(StringNoEnd) but got None");
+    };
+    assert_eq!(string.kind, TokenErrorMessages::StringNoEnd);
+    assert_eq!((string.span.line, string.span.column), (3, 3));
+}
+
+#[test]
+pub fn test_lex_drops_invalid_tokens() {
+    let str = "a $ b\t;";
+    let mut tokenizer = Tokenizer::new(str);
+    let lexer = Tokenizer::lex(&mut tokenizer);
+
+    assert_eq!(lexer.len(), 2);
+    assert_eq!(lexer[0].value, "a");
+    assert_eq!(lexer[1].value, "b");
+}
+
+#[test]
+pub fn test_number_literals() {
+    let str = "0xFF_u8 0b1010 0o17 1e10 1.5E-3f32 -2i8 -1.5 10u8x 0x1f32 1..10";
+    let mut tokenizer = Tokenizer::new(str);
+    let lexer = Tokenizer::lex(&mut tokenizer);
+
+    let expected = [
+        (Tokens::Number, "0xFFu8"),
+        (Tokens::Number, "0b1010"),
+        (Tokens::Number, "0o17"),
+        (Tokens::FloatNumber, "1e10"),
+        (Tokens::FloatNumber, "1.5E-3f32"),
+        (Tokens::NegativeNumber, "-2i8"),
+        (Tokens::FloatNumber, "-1.5"),
+        (Tokens::Number, "10"),
+        (Tokens::Identifier, "u8x"),
+        (Tokens::Number, "0x1f32"),
+        (Tokens::Number, "1"),
+    ];
+    for (index, (token_type, value)) in expected.into_iter().enumerate() {
+        let Some(token) = lexer.get(index) else {
+            panic_test!("Lexer test number literals", format!("Expected a token at {index} but got None"));
+        };
+        assert_eq!(token.token_type, token_type);
+        assert_eq!(token.value, value);
+    }
+}
+
+#[test]
+pub fn test_escape_sequences() {
+    let str = r#""a\n\t\r\"\'\\\0\x41\u{e9}" '\n' '\'' '\x7F' '\\'"#;
+    let mut tokenizer = Tokenizer::new(str);
+    let (tokens, diagnostics) = Tokenizer::lex_with_diagnostics(&mut tokenizer);
+    assert!(diagnostics.is_empty());
+
+    let expected = [
+        (Tokens::String, "a\n\t\r\"'\\\0Aé"),
+        (Tokens::Char, "\n"),
+        (Tokens::Char, "'"),
+        (Tokens::Char, "\x7F"),
+        (Tokens::Char, "\\"),
+    ];
+    assert_eq!(tokens.len(), expected.len());
+    for (token, (token_type, value)) in tokens.iter().zip(expected) {
+        assert_eq!(token.token_type, token_type);
+        assert_eq!(token.value, value);
+    }
+}
+
+#[test]
+pub fn test_invalid_escape_sequences() {
+    let str = r#""bad \q escape" '\x80' "\u{110000}" '\u{e9}' "\x4" a"#;
+    let mut tokenizer = Tokenizer::new(str);
+    let (tokens, diagnostics) = Tokenizer::lex_with_diagnostics(&mut tokenizer);
+
+    let kinds: Vec<TokenErrorMessages> = diagnostics
+        .into_iter()
+        .map(|diagnostic| diagnostic.kind)
+        .collect();
+    assert_eq!(
+        kinds,
+        vec![
+            TokenErrorMessages::InvalidEscape("\\q".into()),
+            TokenErrorMessages::InvalidEscape("\\x80".into()),
+            TokenErrorMessages::InvalidEscape("\\u{110000}".into()),
+            TokenErrorMessages::InvalidChar,
+            TokenErrorMessages::InvalidEscape("\\x4".into()),
+        ]
+    );
+
+    // The rest of the source still gets lexed after a invalid escape
+    let Some(identifier) = tokens.first() else {
+        panic_test!("Lexer test invalid escapes", "Error expected the first token of of lexer tokens to be Some(Identifier) but got None");
+    };
+    assert_eq!(identifier.token_type, Tokens::Identifier);
+    assert_eq!(tokens.len(), 1);
+}
+
+#[test]
+pub fn test_char_literals() {
+    let str = "'0' ' ' '%' 'z' '\\'' '' 'é'";
+    let mut tokenizer = Tokenizer::new(str);
+    let (tokens, diagnostics) = Tokenizer::lex_with_diagnostics(&mut tokenizer);
+
+    let values: Vec<&str> = tokens.iter().map(|token| token.value.as_ref()).collect();
+    assert_eq!(values, ["0", " ", "%", "z", "'"]);
+    assert!(tokens.iter().all(|token| token.token_type == Tokens::Char));
+
+    // A empty char and a char that isn't a single byte are invalid
+    let kinds: Vec<TokenErrorMessages> = diagnostics.into_iter().map(|diagnostic| diagnostic.kind).collect();
+    assert_eq!(kinds, vec![TokenErrorMessages::InvalidChar, TokenErrorMessages::InvalidChar]);
+}
+
+#[test]
+pub fn test_block_comments() {
+    let str = "a /* one /* two */ still one */ b\n/* multi\nline */ c / d";
+    let mut tokenizer = Tokenizer::new(str);
+    let lexer = Tokenizer::lex(&mut tokenizer);
+
+    let expected = [
+        (Tokens::Identifier, "a"),
+        (Tokens::Comment, " one /* two */ still one "),
+        (Tokens::Identifier, "b"),
+        (Tokens::Comment, " multi\nline "),
+        (Tokens::Identifier, "c"),
+        (Tokens::Op(Operator::Slash), "/"),
+        (Tokens::Identifier, "d"),
+    ];
+    assert_eq!(lexer.len(), expected.len());
+    for (token, (token_type, value)) in lexer.iter().zip(expected) {
+        assert_eq!(token.token_type, token_type);
+        assert_eq!(token.value, value);
+    }
+    // The lines inside the comment still count
+    assert_eq!(lexer[4].line, 2);
+    assert_eq!(lexer[4].span.line, 3);
+}
+
+#[test]
+pub fn test_unterminated_block_comment() {
+    let str = "a\n  /* one /* two */ never closed\nb";
+    let mut tokenizer = Tokenizer::new(str);
+    let (tokens, diagnostics) = Tokenizer::lex_with_diagnostics(&mut tokenizer);
+
+    assert_eq!(tokens.len(), 1);
+    let Some(comment) = diagnostics.first() else {
+        panic_test!("Lexer test unterminated block comment", "Error expected the first diagnostic to be Some(BlockCommentNoEnd) but got None");
+    };
+    assert_eq!(comment.kind, TokenErrorMessages::BlockCommentNoEnd);
+    assert_eq!(comment.span.source_text(str), Some("/*"));
+    assert_eq!((comment.span.line, comment.span.column), (2, 3));
+}
+
+#[test]
+pub fn test_unicode_identifiers() {
+    let str = "let _unused größe _ 変数1 x_2 $";
+    let mut tokenizer = Tokenizer::new(str);
+    let (tokens, diagnostics) = Tokenizer::lex_with_diagnostics(&mut tokenizer);
+
+    let identifiers: Vec<&str> = tokens
+        .iter()
+        .skip(1)
+        .map(|token| {
+            assert_eq!(token.token_type, Tokens::Identifier);
+            token.value.as_ref()
+        })
+        .collect();
+    assert_eq!(identifiers, vec!["_unused", "größe", "_", "変数1", "x_2"]);
+
+    // Columns count chars and not bytes, so the diagnostic still lines up
+    let Some(dollar) = diagnostics.first() else {
+        panic_test!("Lexer test unicode identifiers", "Error expected the first diagnostic to be Some(TokenInvalid) but got None");
+    };
+    assert_eq!(dollar.span.column, 29);
+    assert_eq!(dollar.span.source_text(str), Some("$"));
+}
+
+#[test]
+pub fn test_lex_with_trivia() {
+    let str = "let a = \"b\\n\" // note\r\n\t/* block */ c;  ";
+    let mut tokenizer = Tokenizer::new(str);
+    let lossless = Tokenizer::lex_with_trivia(&mut tokenizer);
+
+    assert_eq!(lossless.to_source(), str);
+    let texts: Vec<&str> = lossless
+        .tokens
+        .iter()
+        .map(|token| token.text)
+        .collect();
+    assert_eq!(texts, vec!["let", "a", "=", "\"b\\n\"", "c"]);
+    assert_eq!(lossless.tokens[3].token.value, "b\n");
+
+    let Some(c) = lossless.tokens.last() else {
+        panic_test!("Lexer test trivia", "Error expected the last token of of lexer tokens to be Some(Identifier) but got None");
+    };
+    let kinds: Vec<TriviaKind> = c.leading_trivia.iter().map(|trivia| trivia.kind).collect();
+    assert_eq!(
+        kinds,
+        vec![
+            TriviaKind::Whitespace,
+            TriviaKind::LineComment,
+            TriviaKind::Newline,
+            TriviaKind::Whitespace,
+            TriviaKind::BlockComment,
+            TriviaKind::Whitespace,
+        ]
+    );
+    assert_eq!(c.leading_trivia[1].text, "// note");
+    assert_eq!(c.leading_trivia[2].text, "\r\n");
+
+    let kinds: Vec<TriviaKind> = lossless
+        .trailing_trivia
+        .iter()
+        .map(|trivia| trivia.kind)
+        .collect();
+    assert_eq!(kinds, vec![TriviaKind::Semicolon, TriviaKind::Whitespace]);
+}
+
+#[test]
+pub fn test_lex_with_trivia_round_trip() {
+    let sources = [
+        include_str!("../../test_code/main.zon"),
+        include_str!("../../zonc_cli/main.zon"),
+        "größe $ '\\q' /* never closed\n",
+        "\"no end\n\n",
+        "",
+    ];
+    for source in sources {
+        let mut tokenizer = Tokenizer::new(source);
+        let lossless = Tokenizer::lex_with_trivia(&mut tokenizer);
+        assert_eq!(lossless.to_source(), source);
+    }
+}
+
+#[test]
+pub fn test_tokens_borrow_source() {
+    use std::borrow::Cow;
+
+    let str = "let größe = \"plain\" \"esc\\n\" 1_000 42";
+    let mut tokenizer = Tokenizer::new(str);
+    let lexer = Tokenizer::lex(&mut tokenizer);
+
+    let borrowed: Vec<bool> = lexer
+        .iter()
+        .map(|token| matches!(token.value, Cow::Borrowed(_)))
+        .collect();
+    // Only the string with a escape and the number with underscores had to be decoded
+    assert_eq!(borrowed, vec![true, true, true, true, false, false, true]);
+    assert_eq!(lexer[3].value, "plain");
+    assert_eq!(lexer[5].value, "1000");
+}
+
+#[test]
+pub fn test_math_operators() {
+    let str = "a % b %= c / d /= e << f >> g ^ h ~i | j & k";
+    let mut tokenizer = Tokenizer::new(str);
+    let lexer = Tokenizer::lex(&mut tokenizer);
+
+    let operators: Vec<&Tokens> = lexer
+        .iter()
+        .map(|token| &token.token_type)
+        .filter(|token_type| **token_type != Tokens::Identifier)
+        .collect();
+    let expected = [
+        Tokens::Op(Operator::Percent),
+        Tokens::Op(Operator::PercentIs),
+        Tokens::Op(Operator::Slash),
+        Tokens::Op(Operator::SlashIs),
+        Tokens::Op(Operator::ShiftLeft),
+        Tokens::Op(Operator::ShiftRight),
+        // The lexer can't tell a xor from a pointer, the parser decides
+        Tokens::Pointer,
+        Tokens::Op(Operator::Tilde),
+        Tokens::Op(Operator::Or),
+        Tokens::Op(Operator::And),
+    ];
+    assert_eq!(operators.len(), expected.len());
+    for (operator, expected) in operators.into_iter().zip(expected.iter()) {
+        assert_eq!(operator, expected);
+    }
+    assert_eq!(lexer.len(), 21);
+}
+
+#[test]
+pub fn test_loop_keywords() {
+    let str = "while i < 10 { break continue } whiles";
+    let mut tokenizer = Tokenizer::new(str);
+    let lexer = Tokenizer::lex(&mut tokenizer);
+
+    assert_eq!(lexer[0].token_type, Tokens::Kw(Keywords::While));
+    assert_eq!(lexer[5].token_type, Tokens::Kw(Keywords::Break));
+    assert_eq!(lexer[6].token_type, Tokens::Kw(Keywords::Continue));
+    assert_eq!(lexer[8].token_type, Tokens::Identifier);
+}
+
+#[test]
+pub fn test_const_keyword() {
+    let str = "const SIZE: i32 = 16 constant";
+    let mut tokenizer = Tokenizer::new(str);
+    let lexer = Tokenizer::lex(&mut tokenizer);
+
+    assert_eq!(lexer[0].token_type, Tokens::Kw(Keywords::Const));
+    assert_eq!(lexer[0].value, "const");
+    assert_eq!(lexer[1].token_type, Tokens::Identifier);
+    assert_eq!(lexer[6].token_type, Tokens::Identifier);
+}
+
+#[test]
+pub fn test_extern_tokens() {
+    let str = "extern fn printf(fmt: i8^, ...) i32; a...b";
+    let mut tokenizer = Tokenizer::new(str);
+    let lexer = Tokenizer::lex(&mut tokenizer);
+
+    let token_types: Vec<&Tokens> = lexer.iter().map(|token| &token.token_type).collect();
+    let expected = [
+        Tokens::Kw(Keywords::Extern),
+        Tokens::Kw(Keywords::Fn),
+        Tokens::Identifier,
+        Tokens::OpenBrace,
+        Tokens::Identifier,
+        Tokens::Colon,
+        Tokens::Kw(Keywords::I8),
+        Tokens::Pointer,
+        Tokens::Comma,
+        Tokens::Ellipsis,
+        Tokens::CloseBrace,
+        Tokens::Kw(Keywords::I32),
+        Tokens::Identifier,
+        Tokens::Ellipsis,
+        Tokens::Identifier,
+    ];
+    assert_eq!(token_types, expected.iter().collect::<Vec<_>>());
+    assert_eq!(lexer[0].value, "extern");
+    assert_eq!(lexer[9].value, "...");
+}
+
+#[test]
+pub fn test_import_tokens() {
+    let str = "import \"math.zon\" pub fn f() i32 { math::sqrt(4) }";
+    let mut tokenizer = Tokenizer::new(str);
+    let lexer = Tokenizer::lex(&mut tokenizer);
+
+    assert_eq!(lexer[0].token_type, Tokens::Kw(Keywords::Import));
+    assert_eq!(lexer[0].value, "import");
+    assert_eq!(lexer[1].token_type, Tokens::String);
+    assert_eq!(lexer[1].value, "math.zon");
+    assert_eq!(lexer[2].token_type, Tokens::Kw(Keywords::Pub));
+    assert_eq!(lexer[9].token_type, Tokens::Identifier);
+    assert_eq!(lexer[10].token_type, Tokens::DoubleColon);
+}
+
+#[test]
+pub fn test_range_tokens() {
+    let str = "for i in 0..10 0..=n a.b 1.5..2";
+    let mut tokenizer = Tokenizer::new(str);
+    let lexer = Tokenizer::lex(&mut tokenizer);
+
+    let token_types: Vec<&Tokens> = lexer.iter().map(|token| &token.token_type).collect();
+    let expected = [
+        Tokens::Kw(Keywords::For),
+        Tokens::Identifier,
+        Tokens::Kw(Keywords::In),
+        Tokens::Number,
+        Tokens::Op(Operator::Range),
+        Tokens::Number,
+        Tokens::Number,
+        Tokens::Op(Operator::RangeEq),
+        Tokens::Identifier,
+        Tokens::Identifier,
+        Tokens::Dot,
+        Tokens::Identifier,
+        Tokens::FloatNumber,
+        Tokens::Op(Operator::Range),
+        Tokens::Number,
+    ];
+    assert_eq!(token_types.len(), expected.len());
+    for (token_type, expected) in token_types.into_iter().zip(expected.iter()) {
+        assert_eq!(token_type, expected);
+    }
+    assert_eq!(lexer[4].value, "..");
+    assert_eq!(lexer[7].value, "..=");
+}
+
+#[test]
+pub fn test_match_tokens() {
+    let str = "match s { Shape::Circle(r) => r, _ => 0 } a: b";
+    let mut tokenizer = Tokenizer::new(str);
+    let lexer = Tokenizer::lex(&mut tokenizer);
+
+    assert_eq!(lexer[0].token_type, Tokens::Kw(Keywords::Match));
+    assert_eq!(lexer[4].token_type, Tokens::DoubleColon);
+    assert_eq!(lexer[4].value, "::");
+    assert_eq!(lexer[9].token_type, Tokens::FatArrow);
+    assert_eq!(lexer[9].value, "=>");
+    assert_eq!(lexer[13].token_type, Tokens::FatArrow);
+    assert_eq!(lexer[17].token_type, Tokens::Colon);
+}
@@ -5,7 +5,11 @@
 //! # Where to add my tests? :  
 //!
 //! lexer: if you write a adition to the lexer you should include a test in the [`lexer_tests.rs`] file
+//!
+//! codegen: compile a small program and check its LLVM IR in the [`codegen_tests.rs`] file
 
+#[cfg(test)]
+mod codegen_tests;
 #[cfg(test)]
 pub mod lexer_tests;
 #[cfg(test)]
@@ -9,12 +9,17 @@ use crate::{
     },
     panic_test,
     parser_v2::{
-        ast::{NodeTypes, Type, TypeValues, Types},
-        parser::lep::Statements,
+        ast::{Math, Node, NodeTypes, Type, TypeValues, Types, Value},
+        parser::{
+            errors::ParseErrorKind,
+            lep::Statements,
+            Parser,
+        },
     },
     zon_parser::{
         lexer::{Lexer, Operator, Tokenizer},
-        parser::parser::Parser,
+        parser::parser::Parser as ZonParser,
+        span::Span,
     },
 };
 
@@ -24,8 +29,8 @@ pub fn test_variables() {
     let mut lex = Tokenizer::new(&string_vars);
     let lex = Tokenizer::lex(&mut lex);
 
-    let mut parse = Parser::new(lex);
-    let parse_tokens = Parser::parse(&mut parse);
+    let mut parse = ZonParser::new(lex);
+    let parse_tokens = ZonParser::parse(&mut parse);
 
     assert!(parse_tokens.is_ok());
 
@@ -81,8 +86,8 @@ pub fn test_block() {
     let mut lex = Tokenizer::new(&string_vars);
     let lex = Tokenizer::lex(&mut lex);
 
-    let mut parse = Parser::new(lex);
-    let parse_tokens = Parser::parse(&mut parse);
+    let mut parse = ZonParser::new(lex);
+    let parse_tokens = ZonParser::parse(&mut parse);
 
     assert!(parse_tokens.is_ok());
 
@@ -102,7 +107,7 @@ fn parsing_function() {
     let function = "fn print_name() i32 { print(\"name\") }";
     let mut tokenize = Tokenizer::new(function);
     let tokenize = Tokenizer::lex(&mut tokenize);
-    let mut parse = Parser::new(tokenize);
+    let mut parse = ZonParser::new(tokenize);
     let Ok(parse) = parse.parse() else {
         panic_test!("parsing function", "expected the ast to be ok but it wasn't");
     };
@@ -130,7 +135,7 @@ fn parsing_function_calls() {
 
     let mut tokenize = Tokenizer::new(function_call);
     let tokenize = Tokenizer::lex(&mut tokenize);
-    let mut parse = Parser::new(tokenize);
+    let mut parse = ZonParser::new(tokenize);
     let Ok(parse) = parse.parse() else {
         panic_test!("parsing function calls", "expected the ast to be ok but it wasn't");
     };
@@ -164,7 +169,7 @@ fn parsing_statements() {
     }";
     let mut tokenize = Tokenizer::new(statement);
     let tokenize = Tokenizer::lex(&mut tokenize);
-    let mut parse = Parser::new(tokenize);
+    let mut parse = ZonParser::new(tokenize);
     let Ok(parse) = parse.parse() else {
         panic_test!("parsing statements", "expected the ast to be ok but it wasn't");
     };
@@ -192,26 +197,24 @@ fn parsing_statements() {
 
 #[test]
 fn parse_single_type() {
-    use crate::parser_v2::parser::Parser;
 
     let statement = "array";
     let mut tokenize = Tokenizer::new(statement);
     let tokenize = Tokenizer::lex(&mut tokenize);
 
-    let mut parser = Parser::new(tokenize);
+    let mut parser = Parser::new(&tokenize);
     let tokens = parser.parse_type_expr().unwrap();
     assert_eq!(tokens.r#type, crate::parser_v2::ast::Types::Array);
 }
 
 #[test]
 fn parsing_generics() {
-    use crate::parser_v2::parser::Parser;
 
     let statement = "array<i32, string, i32<string, i32<string>>, i32>";
     let mut tokenize = Tokenizer::new(statement);
     let tokenize = Tokenizer::lex(&mut tokenize);
 
-    let mut parser = Parser::new(tokenize);
+    let mut parser = Parser::new(&tokenize);
     let tokens = parser.parse_type_expr().unwrap();
     assert_eq!(tokens.r#type, crate::parser_v2::ast::Types::Array);
 
@@ -237,28 +240,54 @@ fn parsing_generics() {
     );
 }
 
-#[should_panic(expected = "[Parse Error] Expected a end to generics '>' on line 0")]
+#[test]
+fn parsing_nested_generics() {
+
+    // `>>>` gets lexed as a `>>` and a `>`, `>>>>` as two `>>`
+    let cases = ["Foo<Bar<Baz<i32>>>", "Foo<Bar<Baz<Qux<i32>>>>", "Foo<Bar<Baz<i32>>, i8>"];
+    for statement in cases {
+        let mut tokenize = Tokenizer::new(statement);
+        let tokenize = Tokenizer::lex(&mut tokenize);
+        let mut parser = Parser::new(&tokenize);
+        let parsed = match parser.parse_type_expr() {
+            Ok(parsed) => parsed,
+            Err(error) => {
+                panic_test!("Parsing nested generics", format!("{statement}: {error}"));
+            }
+        };
+        assert_eq!(parsed.r#type, Types::Named("Foo".into()));
+        assert_eq!(parsed.generics[0].r#type, Types::Named("Bar".into()));
+        assert_eq!(parsed.generics[0].generics[0].r#type, Types::Named("Baz".into()));
+        assert_eq!(parsed.span.end, statement.len(), "{statement}");
+    }
+
+    let mut tokenize = Tokenizer::new("Foo<Bar<Baz<i32>>, i8>");
+    let tokenize = Tokenizer::lex(&mut tokenize);
+    let parsed = Parser::new(&tokenize).parse_type_expr().unwrap();
+    assert_eq!(parsed.generics.len(), 2);
+    assert_eq!(parsed.generics[1].r#type, Types::I8);
+}
+
+#[should_panic(expected = "[Parse Error E0004] Expected a end to generics '>' on line 1")]
 #[test]
 fn parsing_generics_no_end() {
-    use crate::parser_v2::parser::Parser;
 
     let generics = "array<i32, string, i32<string, i32<string>>, i32"; // end of the statement doesn't have a ending '>'
     let mut tokenize = Tokenizer::new(generics);
     let tokenize = Tokenizer::lex(&mut tokenize);
 
-    let mut parser = Parser::new(tokenize);
+    let mut parser = Parser::new(&tokenize);
     let tokens = parser.parse_type_expr();
     panic!("{}", tokens.err().unwrap())
 }
 
 #[test]
 fn parsing_ident() {
-    use crate::parser_v2::parser::Parser;
 
     let ident = "some";
     let mut tokens = Lexer::new(ident);
     let ident = Tokenizer::lex(&mut tokens);
-    let mut parser = Parser::new(ident);
+    let mut parser = Parser::new(&ident);
     let parse = parser.parse_next_ident_expr().unwrap();
     assert_eq!(parse.name, "some")
 }
@@ -266,23 +295,21 @@ fn parsing_ident() {
 #[should_panic]
 #[test]
 fn parsing_keyword_not_ident() {
-    use crate::parser_v2::parser::Parser;
 
     let kw = "let";
     let mut tokens = Lexer::new(kw);
     let ident = Tokenizer::lex(&mut tokens);
-    let mut parser = Parser::new(ident);
+    let mut parser = Parser::new(&ident);
     let parse = parser.parse_next_ident_expr().unwrap();
     assert_eq!(parse.name, "let")
 }
 
 #[test]
 fn parsing_values_i8() {
-    use crate::parser_v2::parser::Parser;
     let num = "20";
     let mut tokens = Lexer::new(num);
     let ident = Tokenizer::lex(&mut tokens);
-    let mut parser = Parser::new(ident);
+    let mut parser = Parser::new(&ident);
     let parse = parser
         .parse_value_expr(&Type {
             r#type: Types::I8,
@@ -290,6 +317,7 @@ fn parsing_values_i8() {
             is_pointer: false,
             size: 0,
             generics: Vec::new(),
+            span: Span::default(),
         })
         .unwrap();
     //assert_eq!(parse.value, TypeValues::I8(20))
@@ -297,11 +325,10 @@ fn parsing_values_i8() {
 
 #[test]
 fn parsing_values_i32() {
-    use crate::parser_v2::parser::Parser;
     let num = "20";
     let mut tokens = Lexer::new(num);
     let ident = Tokenizer::lex(&mut tokens);
-    let mut parser = Parser::new(ident);
+    let mut parser = Parser::new(&ident);
     let parse = parser
         .parse_value_expr(&Type {
             r#type: Types::I32,
@@ -309,6 +336,7 @@ fn parsing_values_i32() {
             is_pointer: false,
             size: 0,
             generics: Vec::new(),
+            span: Span::default(),
         })
         .unwrap();
     println!("{:#?}", parse);
@@ -317,11 +345,10 @@ fn parsing_values_i32() {
 
 #[test]
 fn parsing_values_f32() {
-    use crate::parser_v2::parser::Parser;
     let float = "20.";
     let mut tokens = Lexer::new(float);
     let ident = Tokenizer::lex(&mut tokens);
-    let mut parser = Parser::new(ident);
+    let mut parser = Parser::new(&ident);
     let parse = parser
         .parse_value_expr(&Type {
             r#type: Types::F32,
@@ -329,6 +356,7 @@ fn parsing_values_f32() {
             is_array: false,
             size: 0,
             generics: Vec::new(),
+            span: Span::default(),
         })
         .unwrap();
     //assert_eq!(parse.value, TypeValues::F32(20.))
@@ -336,11 +364,10 @@ fn parsing_values_f32() {
 
 #[test]
 fn parsing_values_string() {
-    use crate::parser_v2::parser::Parser;
     let let_expr = "\"hello world!\"";
     let mut tokens = Lexer::new(let_expr);
     let ident = Tokenizer::lex(&mut tokens);
-    let mut parser = Parser::new(ident);
+    let mut parser = Parser::new(&ident);
     let parse = parser
         .parse_value_expr(&Type {
             r#type: Types::String,
@@ -348,6 +375,7 @@ fn parsing_values_string() {
             is_pointer: false,
             size: 0,
             generics: Vec::new(),
+            span: Span::default(),
         })
         .unwrap();
     //assert_eq!(parse.value, TypeValues::String("hello world!".into()))
@@ -355,11 +383,10 @@ fn parsing_values_string() {
 
 #[test]
 fn parsing_let_expr() {
-    use crate::parser_v2::parser::Parser;
     let let_expr = "let test: string[12] = \"testing this\"";
     let mut tokens = Lexer::new(let_expr);
     let var = Tokenizer::lex(&mut tokens);
-    let mut parser = Parser::new(var);
+    let mut parser = Parser::new(&var);
     let parse = parser.parse_let_expr().unwrap();
     let NodeTypes::Variable(var) = parse.node_type else {
         panic!("Parsing let expr expected the type of node to be a variable")
@@ -371,13 +398,12 @@ fn parsing_let_expr() {
 
 #[test]
 fn parsing_let_exprs() {
-    use crate::parser_v2::parser::Parser;
     let ident_str = "let test:string = \"testing this\" 
         let other: string[12] = \"Hello world!\"
         let some: i32[3] = [1, 2, 3]";
     let mut tokens = Lexer::new(ident_str);
     let var = Tokenizer::lex(&mut tokens); //     println!("tokens {:#?}", var);
-    let mut parser = Parser::new(var);
+    let mut parser = Parser::new(&var);
     let parse = parser.parse().unwrap();
 
     let var1 = parse.body.get(0).unwrap();
@@ -444,19 +470,18 @@ fn parsing_let_exprs() {
 
 #[test]
 fn parsing_arrays() {
-    use crate::parser_v2::parser::Parser;
     let type_array = "char[3]";
     let values = "['a', 'b', 'c']";
 
     let mut type_array = Lexer::new(type_array);
     let type_array = Tokenizer::lex(&mut type_array);
-    let mut parser = Parser::new(type_array);
+    let mut parser = Parser::new(&type_array);
     let type_array = parser.parse_type_expr().unwrap();
 
     let mut tokens = Lexer::new(values);
     let var = Tokenizer::lex(&mut tokens);
 
-    let mut parser = Parser::new(var);
+    let mut parser = Parser::new(&var);
     let parse = parser.parse_array(&type_array).unwrap();
     assert_eq!(parse.get(0), Some(&TypeValues::Char('a')));
     assert_eq!(parse.get(1), Some(&TypeValues::Char('b')));
@@ -465,12 +490,11 @@ fn parsing_arrays() {
 
 #[test]
 fn parsing_paramaters() {
-    use crate::parser_v2::parser::Parser;
     let params = "(hello: string, other: string, some: array<i32>)";
 
     let mut params = Lexer::new(params);
     let params = Tokenizer::lex(&mut params);
-    let mut parser = Parser::new(params);
+    let mut parser = Parser::new(&params);
     let params = parser.parse_params().expect("Couldn't unwrap on params?");
 
     let first_param = &params
@@ -499,14 +523,13 @@ fn parsing_paramaters() {
 
 #[test]
 fn parsing_functions() {
-    use crate::parser_v2::parser::Parser;
     let params = "fn name(hello: string, other: string, some: array<i32>) string {
         let some: string = \"hello world!\";
     }";
 
     let mut params = Lexer::new(params);
     let params = Tokenizer::lex(&mut params);
-    let mut parser = Parser::new(params);
+    let mut parser = Parser::new(&params);
     let function = parser.parse().expect("Coudln't parse function");
 
     let function = function
@@ -529,12 +552,11 @@ fn parsing_functions() {
 
 #[test]
 fn parse_arguments() {
-    use crate::parser_v2::parser::Parser;
     let args = "(1, 10, \"testing\")";
 
     let mut params = Tokenizer::new(args);
     let tokens = Tokenizer::lex(&mut params);
-    let mut parser = Parser::new(tokens);
+    let mut parser = Parser::new(&tokens);
     let parse = parser.parse_args_expr().unwrap();
 
     let first_arg = parse.get(0).unwrap();
@@ -549,7 +571,6 @@ fn parse_arguments() {
 
 #[test]
 fn parse_block_expr() {
-    use crate::parser_v2::parser::Parser;
     let args = "{
         let some: char = 'a'
         func(some)
@@ -558,7 +579,7 @@ fn parse_block_expr() {
 
     let mut params = Tokenizer::new(args);
     let tokens = Tokenizer::lex(&mut params);
-    let mut parser = Parser::new(tokens);
+    let mut parser = Parser::new(&tokens);
     let (body, _) = parser
         .parse_block_expr(&Type {
             r#type: Types::I32,
@@ -566,6 +587,7 @@ fn parse_block_expr() {
             is_pointer: false,
             size: 0,
             generics: Vec::new(),
+            span: Span::default(),
         })
         .unwrap();
 
@@ -597,12 +619,11 @@ fn parse_block_expr() {
 
 #[test]
 fn parse_func_call() {
-    use crate::parser_v2::parser::Parser;
     let args = "test(1, 10, \"testing\")";
 
     let mut params = Tokenizer::new(args);
     let tokens = Tokenizer::lex(&mut params);
-    let mut parser = Parser::new(tokens);
+    let mut parser = Parser::new(&tokens);
     let parse = parser.parse_fn_call_expr().unwrap();
 
     let first_arg = parse.0.calls_to.name;
@@ -624,13 +645,12 @@ fn parse_func_call() {
 
 #[test]
 fn parse_statements() {
-    use crate::parser_v2::parser::Parser;
     // the closecurrlybrace '{' serves for the end of a logical statements
     let statements = " 10 >= 20 || 20 == 10 && 20 < wow() && true {";
 
     let mut statements = Tokenizer::new(statements);
     let statements_tokens = Tokenizer::lex(&mut statements);
-    let mut parser = Parser::new(statements_tokens);
+    let mut parser = Parser::new(&statements_tokens);
     let parse_statements = parser.lep_parse_statements().unwrap();
 
     let Some(Statements::MoreEq(value, value1)) = parse_statements.get(0) else {
@@ -663,7 +683,6 @@ fn parse_statements() {
 
 #[test]
 fn parse_logical_expr() {
-    use crate::parser_v2::parser::Parser;
     // the closecurrlybrace '{' serves for the end of a logical statements
     let lep = "10 >= 20 || 20 == 10 && 20 < wow() || 20 < 10 {
         print(\"Hello world\")
@@ -673,7 +692,7 @@ fn parse_logical_expr() {
 
     let mut lep = Tokenizer::new(lep);
     let lep_tokens = Tokenizer::lex(&mut lep);
-    let mut parser = Parser::new(lep_tokens);
+    let mut parser = Parser::new(&lep_tokens);
     let parse_lep = parser.lep_parse(&Type::none_type()).unwrap();
 
     let cases = parse_lep.case;
@@ -702,3 +721,1365 @@ fn parse_logical_expr() {
     assert_eq!(value.value, TypeValues::I32(20));
     assert!(parse_lep.else_do.is_some());
 }
+
+#[test]
+fn parse_node_spans() {
+    let source = "let some: i32 = 10\nlet other: char = 'a'";
+
+    let mut tokens = Tokenizer::new(source);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let ast = parser.parse().unwrap();
+
+    let first = ast.body.get(0).unwrap();
+    assert_eq!(first.span.source_text(source), Some("let some: i32 = 10"));
+    let NodeTypes::Variable(variable) = &first.node_type else {
+        panic!("Expected the first node to be a variable");
+    };
+    assert_eq!(variable.var_type.span.source_text(source), Some("i32"));
+
+    let second = ast.body.get(1).unwrap();
+    assert_eq!(second.span.line, 2);
+    assert_eq!(second.span.column, 1);
+    let NodeTypes::Value(value) = &second.right.as_ref().unwrap().node_type else {
+        panic!("Expected the right node of the second variable to be a value");
+    };
+    assert_eq!(value.span.source_text(source), Some("'a'"));
+}
+
+#[test]
+fn parse_number_literals() {
+    let source = "let a: u8 = 0xFF let b: i8 = -0b101 let c: i32 = 0o17 let d: f32 = 1.5e2 let e: i32 = 3i32";
+
+    let mut tokens = Tokenizer::new(source);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let ast = parser.parse().unwrap();
+
+    let expected = [
+        TypeValues::U8(255),
+        TypeValues::I8(-5),
+        TypeValues::I32(15),
+        TypeValues::F32(150.0),
+        TypeValues::I32(3),
+    ];
+    for (node, expected) in ast.body.iter().zip(expected) {
+        let NodeTypes::Value(value) = &node.right.as_ref().unwrap().node_type else {
+            panic!("Expected the right node of the variable to be a value");
+        };
+        assert_eq!(value.value, expected);
+    }
+}
+
+#[test]
+fn parse_number_literal_errors() {
+    let cases = [
+        (
+            "let a: i8 = 200",
+            "The literal 200 is out of range for type i8, expected a value in between -128 and 127",
+        ),
+        (
+            "let a: u8 = 0x1FF",
+            "The literal 0x1FF is out of range for type u8, expected a value in between 0 and 255",
+        ),
+        (
+            "let a: i32 = 10u8",
+            "Expected type value i32 but got value 10u8 of type U8",
+        ),
+    ];
+
+    for (source, error) in cases {
+        let mut tokens = Tokenizer::new(source);
+        let tokens = Tokenizer::lex(&mut tokens);
+        let mut parser = Parser::new(&tokens);
+        let Err(parse_error) = parser.parse() else {
+            panic_test!("Parse number literal errors", format!("Expected {source} to not parse"));
+        };
+        assert_eq!(parse_error.len(), 1, "{parse_error:#?}");
+        assert_eq!(parse_error[0].message, error);
+        assert_eq!(parse_error[0].kind, ParseErrorKind::InvalidLiteral);
+        assert_eq!(parse_error[0].code(), "E0007");
+    }
+}
+
+#[test]
+fn parse_math_operators() {
+    let source = "let a: i32 = (b % 3 << 1 ^ ~c | d & 0xF - 1)";
+
+    let mut tokens = Tokenizer::new(source);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let ast = parser.parse().unwrap();
+
+    let NodeTypes::Value(value) = &ast.body[0].right.as_ref().unwrap().node_type else {
+        panic!("Expected the right node of the variable to be a value");
+    };
+    let TypeValues::Math(math) = &value.value else {
+        panic_test!("Parse math operators", format!("Expected a math value but got {:?}", value.value));
+    };
+    // `0xF -1` is lexed as a negative number, after a value it is a minus
+    assert_eq!(math_tree(math), "((((b % 3) << 1) ^ ~c) | (d & (15 - 1)))");
+
+    assert_eq!(Operator::Times.precedence(), Some(6));
+    assert!(Operator::Plus.precedence() > Operator::ShiftLeft.precedence());
+    assert!(Operator::And.precedence() > Operator::Xor.precedence());
+    assert!(Operator::Xor.precedence() > Operator::Or.precedence());
+    assert_eq!(Operator::Tilde.precedence(), None);
+}
+
+#[test]
+fn parse_compound_assignments_and_nested_generics() {
+    let source = "fn main() void { let a: array<array<i32>> = b a /= 2 a %= 3 }";
+
+    let mut tokens = Tokenizer::new(source);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let ast = parser.parse().unwrap();
+
+    let NodeTypes::Function(function) = &ast.body[0].node_type else {
+        panic!("Expected the first node to be a function");
+    };
+    let NodeTypes::Variable(variable) = &function.body[0].node_type else {
+        panic!("Expected the first node of the body to be a variable");
+    };
+    assert_eq!(variable.var_type.r#type, Types::Array);
+    assert_eq!(variable.var_type.generics[0].r#type, Types::Array);
+    assert_eq!(variable.var_type.generics[0].generics[0].r#type, Types::I32);
+
+    let expected = [Operator::SlashIs, Operator::PercentIs];
+    for (node, expected) in function.body[1..].iter().zip(expected) {
+        let NodeTypes::Operator(op) = &node.left.as_ref().unwrap().node_type else {
+            panic!("Expected the left node of the assignment to be a operator");
+        };
+        assert_eq!(op, &expected);
+    }
+}
+
+/// Writes a math tree with braces around every binary expression, `(a + (b * c))`
+fn math_tree(math: &Math) -> String {
+    match math {
+        Math::Value(value) => value_tree(value),
+        Math::Unary(op, operand) => format!("{}{}", operator_str(op), math_tree(operand)),
+        Math::Binary(lhs, op, rhs) => {
+            format!("({} {} {})", math_tree(lhs), operator_str(op), math_tree(rhs))
+        }
+    }
+}
+
+fn value_tree(value: &Value) -> String {
+    match &value.value {
+        TypeValues::Identifier(ident) => ident.clone(),
+        TypeValues::I32(num) | TypeValues::I32Neg(num) => num.to_string(),
+        TypeValues::Math(math) => math_tree(math),
+        TypeValues::FunctionCall(call, args) => {
+            let args: Vec<String> = args.iter().map(value_tree).collect();
+            format!("{}({})", call.calls_to.name, args.join(", "))
+        }
+        value => format!("{value:?}"),
+    }
+}
+
+fn operator_str(op: &Operator) -> &'static str {
+    match op {
+        Operator::Plus => "+",
+        Operator::Min => "-",
+        Operator::Times => "*",
+        Operator::Slash => "/",
+        Operator::Percent => "%",
+        Operator::ShiftLeft => "<<",
+        Operator::ShiftRight => ">>",
+        Operator::And => "&",
+        Operator::Or => "|",
+        Operator::Xor => "^",
+        Operator::Tilde => "~",
+        op => panic!("No math operator {op:?}"),
+    }
+}
+
+#[test]
+fn parse_math_expressions() {
+    let cases = [
+        ("n * factorial(n - 1)", "(n * factorial((n - 1)))"),
+        ("1 + 2 * 3 - 4", "((1 + (2 * 3)) - 4)"),
+        ("10 - 2 - 3", "((10 - 2) - 3)"),
+        ("(1 + 2) * 3", "((1 + 2) * 3)"),
+        ("-a * -(b + 1)", "(-a * -(b + 1))"),
+        ("-1 * b", "(-1 * b)"),
+        ("a -1 * 2", "(a - (1 * 2))"),
+        ("~a & 0xF << 2", "(~a & (15 << 2))"),
+        ("f(a + 1, b) / 2", "(f((a + 1), b) / 2)"),
+    ];
+
+    for (expression, expected) in cases {
+        let source = format!("let a: i32 = {expression}");
+        let mut tokens = Tokenizer::new(&source);
+        let tokens = Tokenizer::lex(&mut tokens);
+        let mut parser = Parser::new(&tokens);
+        let ast = match parser.parse() {
+            Ok(ast) => ast,
+            Err(error) => {
+                panic_test!("Parse math expressions", format!("{expression}: {error:?}"));
+            }
+        };
+
+        let NodeTypes::Value(value) = &ast.body[0].right.as_ref().unwrap().node_type else {
+            panic!("Expected the right node of the variable to be a value");
+        };
+        assert_eq!(value_tree(value), expected);
+    }
+}
+
+#[test]
+fn parse_math_expressions_in_statements() {
+    let source = "fn factorial(n: i32) i32 {
+        n -= 1 * 2
+        printf(^\"%d\", n + 1)
+        return n * factorial(n - 1)
+    }";
+    let mut tokens = Tokenizer::new(source);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let ast = parser.parse().unwrap();
+
+    let NodeTypes::Function(function) = &ast.body[0].node_type else {
+        panic!("Expected the first node to be a function");
+    };
+    assert_eq!(function.body.len(), 3);
+
+    let expected = ["(1 * 2)", "(n + 1)", "(n * factorial((n - 1)))"];
+    let values = [
+        &function.body[0].right.as_ref().unwrap().node_type,
+        &function.body[1].left.as_ref().unwrap().node_type,
+        &function.body[2].right.as_ref().unwrap().node_type,
+    ];
+    for (value, expected) in values.into_iter().zip(expected) {
+        let value = match value {
+            NodeTypes::Value(value) => value,
+            NodeTypes::Arguments(args) => &args[1],
+            node => panic!("Expected a value or arguments but got {node:?}"),
+        };
+        assert_eq!(value_tree(value), expected);
+    }
+}
+
+#[test]
+fn parse_math_stops_at_line_break() {
+    // The `-1` and `^` start the next arm and the next line, they don't continue the value before them
+    let source = "fn f(n: i32, a: i32) i32 {
+        match n {
+            0 => a
+            -1 => a ^ 1
+            _ => 0
+        }
+    }";
+    let mut tokens = Tokenizer::new(source);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let ast = match parser.parse() {
+        Ok(ast) => ast,
+        Err(error) => {
+            panic_test!("Parse math stops at line break", format!("{error:?}"));
+        }
+    };
+
+    let NodeTypes::Function(function) = &ast.body[0].node_type else {
+        panic!("Expected the first node to be a function");
+    };
+    let [Node { node_type: NodeTypes::Match(match_expr), .. }] = &function.body[..] else {
+        panic!("Expected a match as the body, got {:?}", function.body)
+    };
+    assert_eq!(match_expr.arms.len(), 3);
+    let values: Vec<String> = match_expr.arms[..2]
+        .iter()
+        .map(|arm| match &arm.body[..] {
+            [Node { node_type: NodeTypes::Value(value), .. }] => value_tree(value),
+            body => panic!("Expected the arm to be a value, got {body:?}"),
+        })
+        .collect();
+    assert_eq!(values, ["a", "(a ^ 1)"]);
+}
+
+#[test]
+fn parse_grouped_and_negated_statements() {
+    // the closecurrlybrace '{' serves for the end of a logical statements
+    let statements = "(a > b || c < d) && !done || !(e == 1 && ((f))) || (g + 1) * 2 != h {";
+
+    let mut statements = Tokenizer::new(statements);
+    let statements_tokens = Tokenizer::lex(&mut statements);
+    let mut parser = Parser::new(&statements_tokens);
+    let parse_statements = parser.lep_parse_statements().unwrap();
+    assert_eq!(parse_statements.len(), 7);
+
+    let Some(Statements::Group(group)) = parse_statements.get(0) else {
+        panic!("Expected the first statement to be a Group")
+    };
+    assert!(matches!(group[..], [Statements::More(..), Statements::Or, Statements::Less(..)]));
+    assert_eq!(Some(&Statements::And), parse_statements.get(1));
+
+    let Some(Statements::Not(negated)) = parse_statements.get(2) else {
+        panic!("Expected the third statement to be a Not")
+    };
+    let Statements::Atomic(done) = negated.as_ref() else {
+        panic!("Expected the negated statement to be a Atomic")
+    };
+    assert_eq!(done.value, TypeValues::Identifier("done".into()));
+    assert_eq!(Some(&Statements::Or), parse_statements.get(3));
+
+    let Some(Statements::Not(negated)) = parse_statements.get(4) else {
+        panic!("Expected the fifth statement to be a Not")
+    };
+    let Statements::Group(group) = negated.as_ref() else {
+        panic!("Expected the negated statement to be a Group")
+    };
+    let [Statements::EqEq(..), Statements::And, Statements::Group(inner)] = &group[..] else {
+        panic!("Expected the negated group to contain a EqEq and a Group, got {group:?}")
+    };
+    let [Statements::Group(inner)] = &inner[..] else {
+        panic!("Expected a group in a group")
+    };
+    assert!(matches!(inner[..], [Statements::Atomic(_)]));
+
+    // A ')' followed by a operator is a math value, not a group
+    let Some(Statements::NotEq(value, other)) = parse_statements.get(6) else {
+        panic!("Expected the last statement to be a NotEq")
+    };
+    assert!(matches!(value.value, TypeValues::Math(_)));
+    assert_eq!(other.value, TypeValues::Identifier("h".into()));
+}
+
+#[test]
+fn parse_statements_errors() {
+    let cases = ["(a > b {", "a > b ) {", "!a > b {"];
+
+    for statements in cases {
+        let mut tokens = Tokenizer::new(statements);
+        let tokens = Tokenizer::lex(&mut tokens);
+        let mut parser = Parser::new(&tokens);
+        if let Ok(parsed) = parser.lep_parse_statements() {
+            panic_test!("Parse statements errors", format!("Expected {statements} to not parse, got {parsed:?}"));
+        }
+    }
+}
+
+#[test]
+fn parse_statement_operands() {
+    let statements = "factorial(n) > (limit * 2) && c == 'q' || ratio >= 1.5 && n * 2 < -1 {";
+
+    let mut statements = Tokenizer::new(statements);
+    let statements_tokens = Tokenizer::lex(&mut statements);
+    let mut parser = Parser::new(&statements_tokens);
+    let parse_statements = parser.lep_parse_statements().unwrap();
+
+    let Some(Statements::More(call, math)) = parse_statements.get(0) else {
+        panic!("Expected the first statement to be a More")
+    };
+    assert!(matches!(call.value, TypeValues::FunctionCall(..)));
+    assert_eq!(value_tree(math), "(limit * 2)");
+
+    let Some(Statements::EqEq(char_ident, char)) = parse_statements.get(2) else {
+        panic!("Expected the third statement to be a EqEq")
+    };
+    assert_eq!(char_ident.value, TypeValues::Identifier("c".into()));
+    assert_eq!(char.value, TypeValues::Char('q'));
+
+    let Some(Statements::MoreEq(_, float)) = parse_statements.get(4) else {
+        panic!("Expected the fifth statement to be a MoreEq")
+    };
+    assert_eq!(float.value, TypeValues::F32(1.5));
+
+    let Some(Statements::Less(math, negative)) = parse_statements.get(6) else {
+        panic!("Expected the last statement to be a Less")
+    };
+    assert_eq!(value_tree(math), "(n * 2)");
+    assert_eq!(value_tree(negative), "-1");
+}
+
+#[test]
+fn parse_else_if_chain() {
+    let lep = "a < 0 { print(\"negative\") } else if a == 0 { print(\"zero\") } else if a < 10 {
+        print(\"small\")
+    } else {
+        print(\"big\")
+    }";
+
+    let mut lep = Tokenizer::new(lep);
+    let lep_tokens = Tokenizer::lex(&mut lep);
+    let mut parser = Parser::new(&lep_tokens);
+    let parse_lep = parser.lep_parse(&Type::none_type()).unwrap();
+
+    let Some(NodeTypes::LogicalStatement(else_if)) = &parse_lep.else_do else {
+        panic!("Expected the else of the first if to be a else if")
+    };
+    assert!(matches!(else_if.case[..], [Statements::EqEq(..)]));
+    let Some(NodeTypes::LogicalStatement(else_if)) = &else_if.else_do else {
+        panic!("Expected the else of the second if to be a else if")
+    };
+    assert!(matches!(else_if.case[..], [Statements::Less(..)]));
+    let Some(NodeTypes::Block(else_block)) = &else_if.else_do else {
+        panic!("Expected the chain to end in a else block")
+    };
+    assert_eq!(else_block.len(), 1);
+}
+
+#[test]
+fn parse_if_expressions() {
+    let source = "let sign: i32 = if x < 0 { -1 } else if x == 0 { 0 } else { 1 }
+        let b: i32 = if sign > 0 { let c: i32 = 2 c * sign } else { abs(sign) }";
+
+    let mut tokens = Tokenizer::new(source);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+
+    let sign = parser.parse_let_expr().unwrap();
+    let Some(NodeTypes::LogicalStatement(if_value)) = sign.right.map(|value| value.node_type) else {
+        panic!("Expected the value of sign to be a if expression")
+    };
+    let NodeTypes::Block(if_block) = &if_value.if_do else {
+        panic!("Expected a if block")
+    };
+    let [value] = &if_block[..] else {
+        panic!("Expected the if block to only contain a value, got {if_block:?}")
+    };
+    let NodeTypes::Value(value) = &value.node_type else {
+        panic!("Expected the if block to end in a value")
+    };
+    assert_eq!(value.value, TypeValues::I32(-1));
+    assert!(matches!(if_value.else_do, Some(NodeTypes::LogicalStatement(_))));
+
+    let b = parser.parse_let_expr().unwrap();
+    let Some(NodeTypes::LogicalStatement(if_value)) = b.right.map(|value| value.node_type) else {
+        panic!("Expected the value of b to be a if expression")
+    };
+    let NodeTypes::Block(if_block) = &if_value.if_do else {
+        panic!("Expected a if block")
+    };
+    let [let_c, math] = &if_block[..] else {
+        panic!("Expected a variable and a value in the if block, got {if_block:?}")
+    };
+    assert!(matches!(let_c.node_type, NodeTypes::Variable(_)));
+    let NodeTypes::Value(math) = &math.node_type else {
+        panic!("Expected the if block to end in a value")
+    };
+    assert_eq!(value_tree(math), "(c * sign)");
+    let Some(NodeTypes::Block(else_block)) = &if_value.else_do else {
+        panic!("Expected a else block")
+    };
+    assert!(matches!(else_block[..], [Node { node_type: NodeTypes::FunctionCall(_), .. }]));
+}
+
+#[test]
+fn parse_block_value_not_last() {
+    let source = "let a: i32 = if b { 1 let c: i32 = 2 } else { 2 }";
+
+    let mut tokens = Tokenizer::new(source);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    if let Ok(parsed) = parser.parse_let_expr() {
+        panic_test!("Parse block value", format!("Expected a value in the middle of a block to not parse, got {parsed:?}"));
+    }
+}
+
+#[test]
+fn parse_nested_ifs_without_else() {
+    let block = "{
+        if a > 1 {
+            if b > 2 {
+                print(b)
+            }
+            a += 1
+        }
+        print(a)
+    }";
+
+    let mut tokens = Tokenizer::new(block);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let (body, _) = parser.parse_block_expr(&Type::none_type()).unwrap();
+
+    let [if_node, call] = &body[..] else {
+        panic!("Expected a if and a function call in the block, got {body:?}")
+    };
+    assert!(matches!(call.node_type, NodeTypes::FunctionCall(_)));
+    let NodeTypes::LogicalStatement(outer) = &if_node.node_type else {
+        panic!("Expected the first node to be a if")
+    };
+    assert!(outer.else_do.is_none());
+    let NodeTypes::Block(outer_block) = &outer.if_do else {
+        panic!("Expected a if block")
+    };
+    let [Node { node_type: NodeTypes::LogicalStatement(inner), .. }, Node { node_type: NodeTypes::Assignment(_), .. }] = &outer_block[..] else {
+        panic!("Expected a nested if followed by a assignment, got {outer_block:?}")
+    };
+    assert!(inner.else_do.is_none());
+}
+
+#[test]
+fn parse_while_loops() {
+    let block = "{
+        let i: i32 = 0
+        while i < 10 && !done {
+            i += 1
+            if i == 5 {
+                continue
+            }
+            while 1 {
+                break
+            }
+        }
+    }";
+
+    let mut tokens = Tokenizer::new(block);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let (body, _) = parser.parse_block_expr(&Type::none_type()).unwrap();
+
+    let Some(NodeTypes::While(while_loop)) = body.get(1).map(|node| &node.node_type) else {
+        panic!("Expected the second node to be a while loop, got {body:?}")
+    };
+    assert!(matches!(
+        while_loop.case[..],
+        [Statements::Less(..), Statements::And, Statements::Not(_)]
+    ));
+    let [assignment, if_node, inner] = &while_loop.body[..] else {
+        panic!("Expected three nodes in the body of the loop, got {:?}", while_loop.body)
+    };
+    assert!(matches!(assignment.node_type, NodeTypes::Assignment(_)));
+    let NodeTypes::LogicalStatement(if_node) = &if_node.node_type else {
+        panic!("Expected a if in the loop")
+    };
+    let NodeTypes::Block(if_block) = &if_node.if_do else {
+        panic!("Expected a if block")
+    };
+    assert!(matches!(if_block[..], [Node { node_type: NodeTypes::Continue, .. }]));
+    let NodeTypes::While(inner) = &inner.node_type else {
+        panic!("Expected a nested while loop")
+    };
+    assert!(matches!(inner.body[..], [Node { node_type: NodeTypes::Break, .. }]));
+}
+
+#[test]
+fn parse_while_errors() {
+    let cases = ["{ while { } }", "{ while i < 10 }", "{ while i < 10 { i += 1 }"];
+
+    for block in cases {
+        let mut tokens = Tokenizer::new(block);
+        let tokens = Tokenizer::lex(&mut tokens);
+        let mut parser = Parser::new(&tokens);
+        if let Ok(parsed) = parser.parse_block_expr(&Type::none_type()) {
+            panic_test!("Parse while errors", format!("Expected {block} to not parse, got {parsed:?}"));
+        }
+    }
+}
+
+#[test]
+fn parse_for_loops() {
+    use crate::parser_v2::parser::loops::ForIterator;
+    let block = "{
+        for i in 0..n * 2 {
+            for letter in name {
+                if letter == 'a' {
+                    break
+                }
+            }
+        }
+        for j in -5..=5 {
+            continue
+        }
+    }";
+
+    let mut tokens = Tokenizer::new(block);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let (body, _) = parser.parse_block_expr(&Type::none_type()).unwrap();
+
+    let [Node { node_type: NodeTypes::For(outer), .. }, Node { node_type: NodeTypes::For(inclusive), .. }] = &body[..] else {
+        panic!("Expected two for loops in the block, got {body:?}")
+    };
+    assert_eq!(outer.binding.name, "i");
+    let ForIterator::Range { start, end, inclusive: false } = &outer.iterator else {
+        panic!("Expected a exclusive range, got {:?}", outer.iterator)
+    };
+    assert_eq!(start.value, TypeValues::I32(0));
+    assert_eq!(value_tree(end), "(n * 2)");
+
+    let [Node { node_type: NodeTypes::For(inner), .. }] = &outer.body[..] else {
+        panic!("Expected a for loop in the for loop, got {:?}", outer.body)
+    };
+    assert_eq!(inner.binding.name, "letter");
+    let ForIterator::Array(array) = &inner.iterator else {
+        panic!("Expected a loop over a array, got {:?}", inner.iterator)
+    };
+    assert_eq!(array.value, TypeValues::Identifier("name".into()));
+
+    let ForIterator::Range { start, inclusive: true, .. } = &inclusive.iterator else {
+        panic!("Expected a inclusive range, got {:?}", inclusive.iterator)
+    };
+    assert_eq!(start.value, TypeValues::I32(-5));
+    assert!(matches!(inclusive.body[..], [Node { node_type: NodeTypes::Continue, .. }]));
+}
+
+#[test]
+fn parse_for_errors() {
+    let cases = ["{ for i 0..10 { } }", "{ for in 0..10 { } }", "{ for i in 0.. { } }", "{ for i in 0..10 }"];
+
+    for block in cases {
+        let mut tokens = Tokenizer::new(block);
+        let tokens = Tokenizer::lex(&mut tokens);
+        let mut parser = Parser::new(&tokens);
+        if let Ok(parsed) = parser.parse_block_expr(&Type::none_type()) {
+            panic_test!("Parse for errors", format!("Expected {block} to not parse, got {parsed:?}"));
+        }
+    }
+}
+
+#[test]
+fn parse_structs() {
+    use crate::parser_v2::ast::Ident;
+    let source = "struct Point { x: i32, y: i32 }
+    struct Line {
+        // the fields don't need commas
+        start: Point
+        end: Point
+    }
+    fn length(line: Line) i32 {
+        let p: Point = Point { x: line.start.x, y: 2 }
+        p.x += line.end.x - 1
+        p.x
+    }";
+
+    let mut tokens = Tokenizer::new(source);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let ast = parser.parse().unwrap();
+
+    let [Node { node_type: NodeTypes::Struct(point), .. }, Node { node_type: NodeTypes::Struct(line), .. }, Node { node_type: NodeTypes::Function(function), .. }] = &ast.body[..] else {
+        panic!("Expected two structs and a function, got {:?}", ast.body)
+    };
+    assert_eq!(point.ident.name, "Point");
+    let fields: Vec<&str> = point.fields.iter().map(|field| field.ident.name.as_str()).collect();
+    assert_eq!(fields, ["x", "y"]);
+    assert_eq!(line.fields[1].r#type.r#type, Types::Named("Point".into()));
+    assert_eq!(function.paramaters[0].r#type.r#type, Types::Named("Line".into()));
+
+    let [variable, assignment, tail] = &function.body[..] else {
+        panic!("Expected three nodes in the body, got {:?}", function.body)
+    };
+    let Some(NodeTypes::Value(literal)) = variable.right.as_ref().map(|value| &value.node_type) else {
+        panic!("Expected a struct literal, got {variable:?}")
+    };
+    let TypeValues::Struct(name, fields) = &literal.value else {
+        panic!("Expected a struct literal, got {literal:?}")
+    };
+    assert_eq!(name.name, "Point");
+    assert_eq!(fields[0].0.name, "x");
+    assert_eq!(
+        fields[0].1.value,
+        TypeValues::FieldAccess(Ident { name: "line".into() }, vec![Ident { name: "start".into() }, Ident { name: "x".into() }])
+    );
+
+    let NodeTypes::Assignment(assignment) = &assignment.node_type else {
+        panic!("Expected a assignment to a field, got {assignment:?}")
+    };
+    assert_eq!(assignment.assigns_to.name, "p");
+    assert_eq!(assignment.fields, vec![Ident { name: "x".into() }]);
+
+    let NodeTypes::Value(tail) = &tail.node_type else {
+        panic!("Expected the field to be the value of the body, got {tail:?}")
+    };
+    assert_eq!(tail.value, TypeValues::FieldAccess(Ident { name: "p".into() }, vec![Ident { name: "x".into() }]));
+}
+
+#[test]
+fn parse_struct_errors() {
+    // The span of the error is the range of the source it points at
+    let cases = [
+        ("struct Point { x: i32, x: i32 }", ParseErrorKind::DuplicateName, 23..24),
+        ("struct Point { x i32 }", ParseErrorKind::ExpectedToken, 17..20),
+        ("struct Point x: i32", ParseErrorKind::ExpectedBody, 13..14),
+        ("struct Point { x: i32, y: i32", ParseErrorKind::UnclosedDelimiter, 29..29),
+        ("fn f() void { let p: Point = Point { x: 1, x: 2 } }", ParseErrorKind::DuplicateName, 43..44),
+        ("fn f() void { let p: Point = Point { x: 1 y: 2 } }", ParseErrorKind::UnclosedDelimiter, 42..43),
+        ("fn f() void { p. = 1 }", ParseErrorKind::ExpectedIdent, 17..18),
+    ];
+
+    for (source, kind, span) in cases {
+        let mut tokens = Tokenizer::new(source);
+        let tokens = Tokenizer::lex(&mut tokens);
+        let mut parser = Parser::new(&tokens);
+        let Err(errors) = parser.parse() else {
+            panic_test!("Parse struct errors", format!("Expected {source} to not parse"));
+        };
+        assert_eq!((errors[0].kind, errors[0].span.start..errors[0].span.end), (kind, span), "{source}: {errors:#?}");
+    }
+}
+
+#[test]
+fn parse_enums() {
+    use crate::parser_v2::ast::Ident;
+    use crate::parser_v2::parser::matches::Pattern;
+    let source = "enum Shape {
+        Circle(i32),
+        Rect(i32, i32)
+        // variants without a payload don't need parens
+        Empty
+    }
+    fn area(shape: Shape) i32 {
+        let default: Shape = Shape::Rect(1, 2)
+        match shape {
+            Shape::Circle(r) => r * r * 3,
+            Shape::Rect(w, _) => { w * 2 }
+            _ => 0
+        }
+    }";
+
+    let mut tokens = Tokenizer::new(source);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let ast = parser.parse().unwrap();
+
+    let [Node { node_type: NodeTypes::Enum(shape), .. }, Node { node_type: NodeTypes::Function(function), .. }] = &ast.body[..] else {
+        panic!("Expected a enum and a function, got {:?}", ast.body)
+    };
+    assert_eq!(shape.ident.name, "Shape");
+    let variants: Vec<(&str, usize)> = shape.variants.iter().map(|variant| (variant.ident.name.as_str(), variant.fields.len())).collect();
+    assert_eq!(variants, [("Circle", 1), ("Rect", 2), ("Empty", 0)]);
+    assert_eq!(function.paramaters[0].r#type.r#type, Types::Named("Shape".into()));
+
+    let [variable, tail] = &function.body[..] else {
+        panic!("Expected two nodes in the body, got {:?}", function.body)
+    };
+    let Some(NodeTypes::Value(value)) = variable.right.as_ref().map(|value| &value.node_type) else {
+        panic!("Expected a enum value, got {variable:?}")
+    };
+    let TypeValues::EnumVariant(enum_ident, variant, payload) = &value.value else {
+        panic!("Expected a enum value, got {value:?}")
+    };
+    assert_eq!((enum_ident.name.as_str(), variant.name.as_str(), payload.len()), ("Shape", "Rect", 2));
+
+    let NodeTypes::Match(match_expr) = &tail.node_type else {
+        panic!("Expected the match to be the value of the body, got {tail:?}")
+    };
+    assert_eq!(match_expr.value.value, TypeValues::Identifier("shape".into()));
+    assert_eq!(match_expr.arms.len(), 3);
+    let Pattern::Variant { variant, bindings, .. } = &match_expr.arms[1].pattern else {
+        panic!("Expected a variant pattern, got {:?}", match_expr.arms[1].pattern)
+    };
+    assert_eq!(variant.name, "Rect");
+    assert_eq!(bindings, &vec![Ident { name: "w".into() }, Ident { name: "_".into() }]);
+    assert_eq!(match_expr.arms[2].pattern, Pattern::Wildcard);
+}
+
+#[test]
+fn parse_enum_errors() {
+    // The span of the error is the range of the source it points at
+    let cases = [
+        ("enum Shape { Circle, Circle }", ParseErrorKind::DuplicateName, 21..27),
+        ("enum Shape Circle", ParseErrorKind::ExpectedBody, 11..17),
+        ("enum Shape { Circle(i32 }", ParseErrorKind::UnclosedDelimiter, 24..25),
+        ("fn f() void { let s: Shape = Shape:: }", ParseErrorKind::ExpectedIdent, 37..38),
+        ("fn f(s: Shape) void { match s { Shape::Circle(r) r } }", ParseErrorKind::ExpectedToken, 49..50),
+        ("fn f(s: Shape) void { match s Shape::Circle(r) => r }", ParseErrorKind::ExpectedBody, 30..35),
+        ("fn f(s: Shape) void { match s { + => 2 } }", ParseErrorKind::InvalidPattern, 32..33),
+        ("fn f(s: Shape) void { match s { Shape::Circle(r) => r ", ParseErrorKind::UnclosedDelimiter, 53..53),
+    ];
+
+    for (source, kind, span) in cases {
+        let mut tokens = Tokenizer::new(source);
+        let tokens = Tokenizer::lex(&mut tokens);
+        let mut parser = Parser::new(&tokens);
+        let Err(errors) = parser.parse() else {
+            panic_test!("Parse enum errors", format!("Expected {source} to not parse"));
+        };
+        assert_eq!((errors[0].kind, errors[0].span.start..errors[0].span.end), (kind, span), "{source}: {errors:#?}");
+    }
+}
+
+#[test]
+fn parse_match_patterns() {
+    use crate::parser_v2::parser::matches::Pattern;
+    let source = "fn kind(c: char) i32 {
+        match c {
+            'a'..='z' | 'A'..='Z' => 1,
+            '\\t' => 3,
+            _ => 0
+        }
+    }
+    fn sign(n: i32) void {
+        match n {
+            -1 => { printf(^\"negative\") }
+            0 | 1 => { printf(^\"small\") }
+            2..10 => { printf(^\"digit\") }
+            _ => {}
+        }
+    }";
+
+    let mut tokens = Tokenizer::new(source);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let ast = parser.parse().unwrap();
+
+    let [Node { node_type: NodeTypes::Function(kind), .. }, Node { node_type: NodeTypes::Function(sign), .. }] = &ast.body[..] else {
+        panic!("Expected two functions, got {:?}", ast.body)
+    };
+    let [Node { node_type: NodeTypes::Match(kind_match), .. }] = &kind.body[..] else {
+        panic!("Expected a match as the body, got {:?}", kind.body)
+    };
+    let Pattern::Or(patterns) = &kind_match.arms[0].pattern else {
+        panic!("Expected a or pattern, got {:?}", kind_match.arms[0].pattern)
+    };
+    let Pattern::Range { start, end, inclusive: true } = &patterns[0] else {
+        panic!("Expected a inclusive range, got {:?}", patterns[0])
+    };
+    assert_eq!((&start.value, &end.value), (&TypeValues::Char('a'), &TypeValues::Char('z')));
+    let Pattern::Literal(tab) = &kind_match.arms[1].pattern else {
+        panic!("Expected a literal pattern, got {:?}", kind_match.arms[1].pattern)
+    };
+    assert_eq!(tab.value, TypeValues::Char('\t'));
+
+    let [Node { node_type: NodeTypes::Match(sign_match), .. }] = &sign.body[..] else {
+        panic!("Expected a match as the body, got {:?}", sign.body)
+    };
+    let Pattern::Literal(negative) = &sign_match.arms[0].pattern else {
+        panic!("Expected a literal pattern, got {:?}", sign_match.arms[0].pattern)
+    };
+    assert_eq!(negative.value, TypeValues::I32(-1));
+    assert!(matches!(&sign_match.arms[1].pattern, Pattern::Or(patterns) if patterns.len() == 2));
+    assert!(matches!(sign_match.arms[2].pattern, Pattern::Range { inclusive: false, .. }));
+    assert_eq!(sign_match.arms[3].pattern, Pattern::Wildcard);
+}
+
+#[test]
+fn parse_match_pattern_errors() {
+    // The span of the error is the range of the source it points at
+    let cases = [
+        ("fn f(c: char) void { match c { 'a'.. => {} } }", 37..39),
+        ("fn f(c: char) void { match c { 'a'..=z => {} } }", 37..38),
+        ("fn f(c: char) void { match c { 'a' | _ => {} } }", 37..38),
+        ("fn f(c: char) void { match c { 'a' | => {} } }", 37..39),
+        ("fn f(c: char) void { match c { 1.5 => {} } }", 31..34),
+    ];
+
+    for (source, span) in cases {
+        let mut tokens = Tokenizer::new(source);
+        let tokens = Tokenizer::lex(&mut tokens);
+        let mut parser = Parser::new(&tokens);
+        let Err(errors) = parser.parse() else {
+            panic_test!("Parse match pattern errors", format!("Expected {source} to not parse"));
+        };
+        let expected = (ParseErrorKind::InvalidPattern, span);
+        assert_eq!((errors[0].kind, errors[0].span.start..errors[0].span.end), expected, "{source}: {errors:#?}");
+    }
+}
+
+#[test]
+fn parse_recovers_from_errors() {
+    let source = "42 + 1
+    fn first() void {
+        let a: i32 = 
+        let b: i32 = 2
+        b = = 3
+        let e: i32 = 4
+        if b > 1 { let c: = 1 }
+        printf(^\"%d\", b)
+    }
+    fn second(a i32) void {
+        let d: i32 = 1
+    }
+    struct Point { x: i32 y i32 }
+    fn third() i32 {
+        1
+    }";
+
+    let mut tokens = Tokenizer::new(source);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let Err(errors) = parser.parse() else {
+        panic_test!("Parse recovers from errors", "Expected the source to not parse");
+    };
+
+    assert_eq!(errors.len(), 6, "{errors:#?}");
+    let found: Vec<(usize, &str)> = errors.iter().map(|error| (error.span.line, error.code())).collect();
+    assert_eq!(
+        found,
+        [(1, "E0005"), (4, "E0005"), (5, "E0005"), (7, "E0001"), (10, "E0003"), (13, "E0003")]
+    );
+}
+
+#[test]
+fn parse_error_codes() {
+    let cases = [
+        ("let a: = 1", ParseErrorKind::ExpectedType, "E0001"),
+        ("fn f(a i32) void { }", ParseErrorKind::ExpectedToken, "E0003"),
+        ("fn f() void { let a: i32 = (1 + 2 }", ParseErrorKind::UnclosedDelimiter, "E0004"),
+        ("let a: u8 = 256", ParseErrorKind::InvalidLiteral, "E0007"),
+        ("struct Point { x: i32, x: i32 }", ParseErrorKind::DuplicateName, "E0011"),
+        ("fn f() void { match a { + => 1 } }", ParseErrorKind::InvalidPattern, "E0012"),
+    ];
+
+    for (source, kind, code) in cases {
+        let mut tokens = Tokenizer::new(source);
+        let tokens = Tokenizer::lex(&mut tokens);
+        let mut parser = Parser::new(&tokens);
+        let Err(errors) = parser.parse() else {
+            panic_test!("Parse error codes", format!("Expected {source} to not parse"));
+        };
+        assert_eq!(errors[0].kind, kind, "{source}: {errors:#?}");
+        assert_eq!(errors[0].code(), code);
+    }
+}
+
+#[test]
+fn parse_error_render() {
+    let source = "fn main() void {\n\tlet a: = 1\n}";
+    let mut tokens = Tokenizer::new(source);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let Err(errors) = parser.parse() else {
+        panic_test!("Parse error render", "Expected the source to not parse");
+    };
+
+    let error = &errors[0];
+    assert_eq!((error.span.line, error.span.column), (2, 9));
+    assert_eq!(error.expected.as_deref(), Some("a type"));
+    assert_eq!(error.found.as_deref(), Some("'='"));
+    assert_eq!(
+        error.render(source),
+        "error[E0001]: Expected a type after ':'\n \
+         --> line 2, column 9\n  \
+         |\n\
+         2 | \tlet a: = 1\n  \
+         | \t       ^ expected a type, found '='\n  \
+         = help: the types are i8, u8, i32, f32, char, string, void, array or the name of a struct or enum\n"
+    );
+    assert_eq!(error.to_string(), "[Parse Error E0001] Expected a type after ':' on line 2, column 9");
+}
+
+#[test]
+fn parse_never_panics_on_invalid_tokens() {
+    // The span of the error is the range of the source it points at, a error at the end of the
+    // source points at the last token or right after it
+    let cases = [
+        ("}", ParseErrorKind::UnexpectedToken, 0..1),
+        ("fn", ParseErrorKind::ExpectedIdent, 0..2),
+        ("fn f() void", ParseErrorKind::ExpectedBody, 11..11),
+        ("fn f() void x", ParseErrorKind::ExpectedBody, 12..13),
+        ("fn f() let { }", ParseErrorKind::ExpectedType, 7..10),
+        ("let a: fn = 1", ParseErrorKind::ExpectedType, 7..9),
+        ("fn f() void { while a < 1 x }", ParseErrorKind::UnexpectedToken, 26..27),
+        ("fn f() void { [ }", ParseErrorKind::ExpectedBody, 16..17),
+        ("struct", ParseErrorKind::ExpectedIdent, 0..6),
+        ("enum E { A(", ParseErrorKind::ExpectedType, 10..11),
+        ("fn f() void { match x { 'a'..", ParseErrorKind::InvalidPattern, 29..29),
+    ];
+
+    for (source, kind, span) in cases {
+        let mut tokens = Tokenizer::new(source);
+        let tokens = Tokenizer::lex(&mut tokens);
+        let mut parser = Parser::new(&tokens);
+        let Err(errors) = parser.parse() else {
+            panic_test!("Parse never panics", format!("Expected {source} to not parse"));
+        };
+        assert_eq!((errors[0].kind, errors[0].span.start..errors[0].span.end), (kind, span), "{source}: {errors:#?}");
+    }
+}
+
+#[test]
+fn parse_globals() {
+    let source = "let counter: i32 = 0
+    fn count() i32 {
+        counter += 1
+        counter
+    }
+    let table: i32[3] = [1, 2, 3]";
+
+    let mut tokens = Tokenizer::new(source);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let ast = parser.parse().unwrap();
+
+    let [Node { node_type: NodeTypes::Variable(counter), right: Some(counter_value), .. }, Node { node_type: NodeTypes::Function(function), .. }, Node { node_type: NodeTypes::Variable(table), right: Some(table_value), .. }] = &ast.body[..] else {
+        panic!("Expected a global, a function and a global, got {:?}", ast.body)
+    };
+    assert_eq!(counter.ident.name, "counter");
+    let NodeTypes::Value(counter_value) = &counter_value.node_type else {
+        panic!("Expected the global to have a value, got {counter_value:?}")
+    };
+    assert_eq!(counter_value.value, TypeValues::I32(0));
+
+    let NodeTypes::Assignment(assignment) = &function.body[0].node_type else {
+        panic!("Expected a assignment to the global, got {:?}", function.body)
+    };
+    assert_eq!(assignment.assigns_to.name, "counter");
+
+    assert!(table.var_type.is_array);
+    assert_eq!(table.var_type.size, 3);
+    let NodeTypes::Value(table_value) = &table_value.node_type else {
+        panic!("Expected the global to have a value, got {table_value:?}")
+    };
+    assert_eq!(table_value.value, TypeValues::Array(vec![TypeValues::I32(1), TypeValues::I32(2), TypeValues::I32(3)]));
+}
+
+#[test]
+fn parse_consts() {
+    let source = "const BASE: i32 = 16
+    fn square(x: i32) i32 {
+        return x * x
+    }
+    fn fac(n: i32) i32 {
+        if n <= 1 {
+            return 1
+        } else {
+            return n * fac(n - 1)
+        }
+    }
+    fn sum_to(n: i32) i32 {
+        let total: i32 = 0
+        for i in 1..=n {
+            total += i
+        }
+        total
+    }
+    const SIZE: i32 = BASE * 4 + square(3) - fac(4) + sum_to(10)
+    fn main() void {
+        const HALF: u8 = SIZE / 2
+        let buffer: i32[SIZE] = [1, 2]
+        let bytes: u8[HALF] = [1]
+    }";
+
+    let mut tokens = Tokenizer::new(source);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let ast = parser.parse().unwrap();
+
+    let Node { node_type: NodeTypes::Const(size), right: Some(size_value), .. } = &ast.body[4] else {
+        panic!("Expected the fifth node to be a const, got {:?}", ast.body[4])
+    };
+    assert_eq!(size.ident.name, "SIZE");
+    let NodeTypes::Value(size_value) = &size_value.node_type else {
+        panic!("Expected the const to hold its value, got {size_value:?}")
+    };
+    // 16 * 4 + 9 - 24 + 55
+    assert_eq!(size_value.value, TypeValues::I32(104));
+
+    let NodeTypes::Function(main) = &ast.body[5].node_type else {
+        panic!("Expected the main function, got {:?}", ast.body[5])
+    };
+    let [half, buffer, bytes] = &main.body[..] else {
+        panic!("Expected three nodes in main, got {:?}", main.body)
+    };
+    let Some(NodeTypes::Value(half_value)) = half.right.as_ref().map(|right| &right.node_type) else {
+        panic!("Expected the const to hold its value, got {half:?}")
+    };
+    assert_eq!(half_value.value, TypeValues::U8(52));
+    let (NodeTypes::Variable(buffer), NodeTypes::Variable(bytes)) = (&buffer.node_type, &bytes.node_type) else {
+        panic!("Expected two variables, got {buffer:?} and {bytes:?}")
+    };
+    assert_eq!(buffer.var_type.size, 104);
+    assert_eq!(bytes.var_type.size, 52);
+}
+
+#[test]
+fn parse_const_errors() {
+    let cases = [
+        ("const A: i32 = getchar()", ParseErrorKind::NotConstant),
+        ("let x: i32 = 1 const A: i32 = x + 1", ParseErrorKind::NotConstant),
+        ("const A: i8 = 100 * 2", ParseErrorKind::NotConstant),
+        ("const A: i32 = 1 / 0", ParseErrorKind::NotConstant),
+        ("fn f() i32 { printf(^\"f\") 1 } const A: i32 = f()", ParseErrorKind::NotConstant),
+        ("fn f() i32 { while 1 == 1 { } 1 } const A: i32 = f()", ParseErrorKind::NotConstant),
+        ("const A: i32 = g() fn g() i32 { 1 }", ParseErrorKind::NotConstant),
+        ("fn f() i32 { let x: i8 = 100 x += 100 1 } const A: i32 = f()", ParseErrorKind::NotConstant),
+        ("fn f() i32 { let x: u8 = 0 x = 300 1 } const A: i32 = f()", ParseErrorKind::NotConstant),
+        ("fn f(x: i8) i32 { x *= 2 1 } const A: i32 = f(100)", ParseErrorKind::NotConstant),
+        ("const A: i32 = 1 fn f() void { A += 1 }", ParseErrorKind::AssignToConst),
+        ("const A: i32 = 1 fn f() void { let A: i32 = 2 }", ParseErrorKind::DuplicateName),
+        ("const A: i32 = 1 fn f(A: i32) void { }", ParseErrorKind::DuplicateName),
+        ("const A: i32[2] = [1, 2]", ParseErrorKind::ExpectedType),
+        ("fn f() void { const A: i32 = 1 } fn g() void { let a: i32[A] = [1] }", ParseErrorKind::InvalidArrayType),
+    ];
+
+    for (source, kind) in cases {
+        let mut tokens = Tokenizer::new(source);
+        let tokens = Tokenizer::lex(&mut tokens);
+        let mut parser = Parser::new(&tokens);
+        let Err(errors) = parser.parse() else {
+            panic_test!("Parse const errors", format!("Expected {source} to not parse"));
+        };
+        assert_eq!(errors[0].kind, kind, "{source}: {errors:#?}");
+    }
+}
+
+#[test]
+fn parse_externs() {
+    let source = "extern fn printf(fmt: i8^, ...) i32;
+    extern fn puts(s: i8^) i32
+    extern fn exit(code: i32) void
+    fn main() void {
+        puts(^\"hi\")
+    }";
+
+    let mut tokens = Tokenizer::new(source);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let ast = parser.parse().unwrap();
+    assert_eq!(ast.body.len(), 4);
+
+    let NodeTypes::ExternFunction(printf) = &ast.body[0].node_type else {
+        panic!("Expected the first node to be a extern fn, got {:?}", ast.body[0])
+    };
+    assert_eq!(printf.ident.name, "printf");
+    assert!(printf.is_variadic);
+    assert_eq!(printf.paramaters.len(), 1);
+    assert_eq!(printf.paramaters[0].ident.name, "fmt");
+    assert!(printf.paramaters[0].r#type.is_pointer);
+    assert_eq!(printf.returns.r#type, Types::I32);
+
+    let NodeTypes::ExternFunction(puts) = &ast.body[1].node_type else {
+        panic!("Expected the second node to be a extern fn, got {:?}", ast.body[1])
+    };
+    assert_eq!(puts.ident.name, "puts");
+    assert!(!puts.is_variadic);
+
+    let NodeTypes::ExternFunction(exit) = &ast.body[2].node_type else {
+        panic!("Expected the third node to be a extern fn, got {:?}", ast.body[2])
+    };
+    assert_eq!(exit.returns.r#type, Types::None);
+}
+
+#[test]
+fn parse_extern_errors() {
+    let cases = [
+        ("extern printf(fmt: i8^, ...) i32", ParseErrorKind::ExpectedToken),
+        ("extern fn printf(..., fmt: i8^) i32", ParseErrorKind::ExpectedToken),
+        ("fn printf(fmt: i8^, ...) i32 { 1 }", ParseErrorKind::UnexpectedToken),
+        ("extern fn puts(s: i8^) i32 { 1 }", ParseErrorKind::UnexpectedToken),
+    ];
+
+    for (source, kind) in cases {
+        let mut tokens = Tokenizer::new(source);
+        let tokens = Tokenizer::lex(&mut tokens);
+        let mut parser = Parser::new(&tokens);
+        let Err(errors) = parser.parse() else {
+            panic_test!("Parse extern errors", format!("Expected {source} to not parse"));
+        };
+        assert_eq!(errors[0].kind, kind, "{source}: {errors:#?}");
+    }
+
+    // The parser recovers at the next extern, not at the fn of the extern that failed
+    let source = "extern fn puts(s i8^) i32
+    extern fn getchar() i32
+    fn main() void { }";
+    let mut tokens = Tokenizer::new(source);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let Err(errors) = parser.parse() else {
+        panic_test!("Parse extern errors", "Expected puts to not parse");
+    };
+    assert_eq!(errors.len(), 1, "{errors:#?}");
+    assert_eq!(errors[0].span.line, 1);
+}
+
+#[test]
+fn parse_imports() {
+    let source = "import \"lib/math.zon\"
+    pub struct Point { x: i32 }
+    pub fn twice(x: i32) i32 {
+        math::square(x) + math::square(x)
+    }
+    fn main() void {
+        let shape: Shape = Shape::Empty
+        math::print(twice(2))
+        let point: math::Point = math::Point { x: 1 }
+    }";
+
+    let mut tokens = Tokenizer::new(source);
+    let tokens = Tokenizer::lex(&mut tokens);
+    let mut parser = Parser::new(&tokens);
+    let ast = parser.parse().unwrap();
+
+    let NodeTypes::Import(import) = &ast.body[0].node_type else {
+        panic!("Expected the first node to be a import, got {:?}", ast.body[0])
+    };
+    assert_eq!(import.path, "lib/math.zon");
+    assert_eq!(import.module.name, "math");
+
+    let NodeTypes::Struct(point) = &ast.body[1].node_type else {
+        panic!("Expected the second node to be a struct, got {:?}", ast.body[1])
+    };
+    assert!(point.is_pub);
+    let NodeTypes::Function(twice) = &ast.body[2].node_type else {
+        panic!("Expected the third node to be a function, got {:?}", ast.body[2])
+    };
+    assert!(twice.is_pub);
+    let NodeTypes::Function(main) = &ast.body[3].node_type else {
+        panic!("Expected the fourth node to be a function, got {:?}", ast.body[3])
+    };
+    assert!(!main.is_pub);
+
+    // Shape isn't a imported module, so Shape::Empty stays a enum variant
+    let Some(NodeTypes::Value(shape)) = main.body[0].right.as_ref().map(|right| &right.node_type) else {
+        panic!("Expected the variable to have a value, got {:?}", main.body[0])
+    };
+    assert!(matches!(shape.value, TypeValues::EnumVariant(..)));
+    let NodeTypes::FunctionCall(print) = &main.body[1].node_type else {
+        panic!("Expected a call to math::print, got {:?}", main.body[1])
+    };
+    assert_eq!(print.calls_to.name, "print");
+    assert_eq!(print.module.as_ref().map(|module| module.name.as_str()), Some("math"));
+    // A type of a imported module keeps the name of the module
+    let Some(NodeTypes::Value(point)) = main.body[2].right.as_ref().map(|right| &right.node_type) else {
+        panic!("Expected the variable to have a value, got {:?}", main.body[2])
+    };
+    assert!(matches!(&point.value, TypeValues::Struct(name, _) if name.name == "math::Point"), "{point:?}");
+
+    let paths: Vec<(&str, &str)> = ast
+        .module_paths
+        .iter()
+        .map(|path| (path.module.name.as_str(), path.item.name.as_str()))
+        .collect();
+    assert_eq!(paths, [("math", "square"), ("math", "square"), ("math", "print")]);
+    let types: Vec<(Option<&str>, &str)> = ast
+        .type_uses
+        .iter()
+        .map(|type_use| (type_use.module.as_ref().map(|module| module.name.as_str()), type_use.ident.name.as_str()))
+        .collect();
+    assert_eq!(types, [(None, "Shape"), (None, "Shape"), (Some("math"), "Point"), (Some("math"), "Point")]);
+}
+
+#[test]
+fn parse_import_errors() {
+    let cases = [
+        ("import math", ParseErrorKind::ExpectedToken),
+        ("import \"1st.zon\"", ParseErrorKind::InvalidLiteral),
+        ("import \"math.zon\" import \"lib/math.zon\"", ParseErrorKind::DuplicateName),
+        ("pub let a: i32 = 1", ParseErrorKind::UnexpectedToken),
+        ("pub const A: i32 = 1", ParseErrorKind::UnexpectedToken),
+        ("fn f() void { import \"math.zon\" }", ParseErrorKind::UnexpectedToken),
+        ("import \"math.zon\" const A: i32 = math::square(2)", ParseErrorKind::NotConstant),
+        ("import \"math.zon\" fn f() i32 { math::PI }", ParseErrorKind::ExpectedToken),
+    ];
+
+    for (source, kind) in cases {
+        let mut tokens = Tokenizer::new(source);
+        let tokens = Tokenizer::lex(&mut tokens);
+        let mut parser = Parser::new(&tokens);
+        let Err(errors) = parser.parse() else {
+            panic_test!("Parse import errors", format!("Expected {source} to not parse"));
+        };
+        assert_eq!(errors[0].kind, kind, "{source}: {errors:#?}");
+    }
+}
+
+/// Writes the files of a program to a new folder in the temp folder, returns the folder
+fn write_program(name: &str, files: &[(&str, &str)]) -> std::path::PathBuf {
+    let folder = std::env::temp_dir().join(format!("zontanos_{name}_{}", std::process::id()));
+    for (path, source) in files {
+        let path = folder.join(path);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(path, source).unwrap();
+    }
+    folder
+}
+
+#[test]
+fn load_modules() {
+    use crate::parser_v2::modules::Program;
+    let folder = write_program(
+        "load_modules",
+        &[
+            ("main.zon", "import \"lib/math.zon\"\nimport \"lib/io.zon\"\nfn main() void { io::print(math::square(2)) }"),
+            ("lib/math.zon", "import \"io.zon\"\npub fn square(x: i32) i32 { x * x }"),
+            ("lib/io.zon", "pub fn print(x: i32) void { }"),
+        ],
+    );
+
+    let program = Program::load(folder.join("main.zon")).unwrap();
+    let names: Vec<&str> = program.modules.iter().map(|module| module.name.as_str()).collect();
+    // A module comes after the modules it imports, io is only loaded once
+    assert_eq!(names, ["io", "math", "main"]);
+    assert_eq!(program.files.len(), 3);
+    let main = &program.modules[2];
+    assert_eq!(main.prefix, "");
+    assert_eq!(main.imports["math"], 1);
+    assert_eq!(main.imports["io"], 0);
+    assert_eq!(program.modules[0].prefix, "io::");
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn load_module_errors() {
+    use crate::parser_v2::modules::{ModuleError, Program};
+    let folder = write_program(
+        "load_module_errors",
+        &[
+            ("main.zon", "import \"a.zon\"\nimport \"missing.zon\"\nfn main() void { a::hidden() a::nope() }"),
+            ("a.zon", "import \"b.zon\"\nfn hidden() void { }"),
+            ("b.zon", "import \"a.zon\"\nfn f() void { }"),
+        ],
+    );
+
+    let Err(ModuleError::Parse { files, errors }) = Program::load(folder.join("main.zon")) else {
+        panic_test!("Load module errors", "Expected the program to not load");
+    };
+    let kinds: Vec<(ParseErrorKind, usize)> = errors.iter().map(|error| (error.kind, error.span.file_id)).collect();
+    assert_eq!(
+        kinds,
+        [
+            (ParseErrorKind::ImportCycle, 2),
+            (ParseErrorKind::UnresolvedImport, 0),
+            (ParseErrorKind::PrivateItem, 0),
+            (ParseErrorKind::UnresolvedImport, 0),
+        ]
+    );
+    assert!(files[2].path.ends_with("b.zon"));
+    let cycle = errors[0].render_in_file(&files[2].source, &files[2].path);
+    assert!(cycle.contains("a.zon -> ") && cycle.contains("b.zon -> "), "{cycle}");
+    std::fs::remove_dir_all(folder).unwrap();
+}
+
+#[test]
+fn load_module_type_errors() {
+    use crate::parser_v2::modules::{ModuleError, Program};
+    let folder = write_program(
+        "load_module_type_errors",
+        &[
+            (
+                "main.zon",
+                "import \"shapes.zon\"
+                fn main(p: shapes::Point) void {
+                    let shape: shapes::Shape = shapes::Shape::Empty
+                    let hidden: shapes::Hidden = shapes::Hidden::A
+                    let inner: Inner = Inner { x: 1 }
+                    let point: Point = shapes::Point { x: 1 }
+                    let line: shapes::Line = shapes::Line { x: 1 }
+                    nope::f()
+                }",
+            ),
+            ("shapes.zon", "import \"inner.zon\"\npub struct Point { x: i32 }\npub enum Shape { Empty }\nenum Hidden { A }\nstruct Line { start: Point }"),
+            ("inner.zon", "pub struct Inner { x: i32 }"),
+        ],
+    );
+
+    let Err(ModuleError::Parse { errors, .. }) = Program::load(folder.join("main.zon")) else {
+        panic_test!("Load module type errors", "Expected the program to not load");
+    };
+    let kinds: Vec<(ParseErrorKind, usize)> = errors.iter().map(|error| (error.kind, error.span.line)).collect();
+    // Every name only gets a error on its first use
+    assert_eq!(
+        kinds,
+        [
+            (ParseErrorKind::PrivateItem, 4),
+            (ParseErrorKind::UnresolvedImport, 5),
+            (ParseErrorKind::UnresolvedImport, 6),
+            (ParseErrorKind::PrivateItem, 7),
+            (ParseErrorKind::UnresolvedImport, 8),
+        ]
+    );
+    assert!(errors[0].to_string().contains("The enum Hidden of the module shapes isn't pub"), "{}", errors[0]);
+    assert!(errors[1].to_string().contains("which isn't imported"), "{}", errors[1]);
+    // A type of a imported module needs the name of the module in front of it
+    assert!(errors[2].to_string().contains("The struct Point is declared in the module shapes"), "{}", errors[2]);
+    assert!(errors[3].to_string().contains("The struct Line of the module shapes isn't pub"), "{}", errors[3]);
+    assert!(errors[4].to_string().contains("There is no struct, enum or module called nope"), "{}", errors[4]);
+    std::fs::remove_dir_all(folder).unwrap();
+}
@@ -0,0 +1,242 @@
+use inkwell::{
+    targets::TargetData,
+    types::{AnyTypeEnum, BasicType, BasicTypeEnum, StructType},
+    values::{IntValue, PointerValue, StructValue},
+    AddressSpace,
+};
+
+use super::{math_codegen::IntSign, CodeGen, CompileResult};
+use crate::parser_v2::{
+    ast::{Ast, Enum, Ident, NodeTypes, Value, Variant},
+    parser::matches::{Match, Pattern},
+};
+
+impl<'ctx> CodeGen<'ctx> {
+    /// Sets the body of every enum, a enum is a i32 tag followed by a array that is big enough for
+    /// the payload of every variant. The array is made of the most aligned field of the payloads,
+    /// so a payload with a `f64` starts at offset 8: `{ i32, [N x double] }`.
+    ///
+    /// The payload of a enum can contain another enum, the size of a payload is only known once the
+    /// enums in it have a body, so the enums of a module get their body in the order their payloads
+    /// are known.
+    pub(super) fn gen_enum_bodies(&self, ast: &'ctx Ast) -> CompileResult<()> {
+        let target_data = TargetData::create(&self.module.get_data_layout().as_str().to_string_lossy());
+        let mut pending: Vec<&'ctx Enum> = ast
+            .body
+            .iter()
+            .filter_map(|node| match &node.node_type {
+                NodeTypes::Enum(declaration) => Some(declaration),
+                _ => None,
+            })
+            .collect();
+        while !pending.is_empty() {
+            let before = pending.len();
+            let mut unsized_enums = Vec::new();
+            for declaration in pending {
+                if !self.gen_enum_body(declaration, &target_data)? {
+                    unsized_enums.push(declaration);
+                }
+            }
+            if unsized_enums.len() == before {
+                let names: Vec<&str> = unsized_enums.iter().map(|declaration| declaration.ident.name.as_str()).collect();
+                return Err(format!("The enums {} contain themselves, a enum can't be part of its own payload", names.join(", ")).into());
+            }
+            pending = unsized_enums;
+        }
+        Ok(())
+    }
+
+    /// Sets the body of a enum, returns false if the size of a payload isn't known yet
+    fn gen_enum_body(&self, declaration: &'ctx Enum, target_data: &TargetData) -> CompileResult<bool> {
+        let mut payload_size = 0;
+        let mut item_type = self.context.i8_type().as_basic_type_enum();
+        let mut payload_types = Vec::new();
+        for variant in &declaration.variants {
+            let payload_type = self.gen_payload_type(variant)?;
+            if !payload_type.is_sized() {
+                return Ok(false);
+            }
+            payload_types.push(payload_type);
+            payload_size = payload_size.max(target_data.get_abi_size(&payload_type));
+            for field_type in payload_type.get_field_types() {
+                let is_more_aligned = target_data.get_abi_alignment(&field_type) > target_data.get_abi_alignment(&item_type);
+                if is_more_aligned && target_data.get_abi_size(&field_type) > 0 {
+                    item_type = field_type;
+                }
+            }
+        }
+
+        let tag_type = self.context.i32_type().as_basic_type_enum();
+        let items = payload_size.div_ceil(target_data.get_abi_size(&item_type));
+        let payload_type = item_type.array_type(items as u32).as_basic_type_enum();
+        self.gen_named_type(&declaration.ident.name)?.set_body(&[tag_type, payload_type], false);
+        self.payloads.borrow_mut().insert(self.mangle(&declaration.ident.name), payload_types);
+        Ok(true)
+    }
+
+    /// The payload of a variant as a struct, the payload of a enum value gets read through a
+    /// pointer of this type. The types of the fields resolve in the module of the enum
+    fn gen_payload_type(&self, variant: &'ctx Variant) -> CompileResult<StructType<'ctx>> {
+        let mut field_types: Vec<BasicTypeEnum> = Vec::new();
+        for field in &variant.fields {
+            field_types.push(self.gen_basic_type(field)?);
+        }
+        Ok(self.context.struct_type(&field_types, false))
+    }
+
+    /// Returns a pointer to the payload of a enum value as the payload type of `variant`, the type
+    /// is looked up by the LLVM type of the enum since the enum can be declared in another module
+    fn gen_payload_pointer(&self, enum_pointer: PointerValue<'ctx>, variant: &'ctx Variant) -> CompileResult<PointerValue<'ctx>> {
+        let name = self.get_enum_name(enum_pointer)?;
+        let tag = self.get_enum_of(enum_pointer)?.get_variant_index_with_name(&variant.ident.name);
+        let payload_type = tag.and_then(|tag| self.payloads.borrow().get(&name)?.get(tag).copied());
+        let Some(payload_type) = payload_type else {
+            return Err(format!("The payload of {} has no type", variant.ident.name).into());
+        };
+        let Ok(payload) = self.builder.build_struct_gep(enum_pointer, 1, "payload") else {
+            return Err(format!("Couldn't get the payload of {}", variant.ident.name).into());
+        };
+        let payload_type = payload_type.ptr_type(AddressSpace::default());
+        Ok(self.builder.build_pointer_cast(payload, payload_type, &variant.ident.name))
+    }
+
+    /// Generates a enum value, `Shape::Circle(5)`, the value gets built on the stack since the
+    /// payload is written through a pointer
+    pub(super) fn gen_enum_value(&self, enum_ident: &Ident, variant: &Ident, payload: &'ctx [Value]) -> CompileResult<StructValue<'ctx>> {
+        let Some(declaration) = self.enums.get(&self.type_name(&enum_ident.name)).copied() else {
+            return Err(format!("There is no enum called {}", enum_ident.name).into());
+        };
+        let Some(tag) = declaration.get_variant_index_with_name(&variant.name) else {
+            return Err(format!("The enum {} has no variant called {}", enum_ident.name, variant.name).into());
+        };
+        let declared = &declaration.variants[tag];
+        if payload.len() != declared.fields.len() {
+            return Err(format!(
+                "The variant {}::{} has {} fields but got {} values",
+                enum_ident.name, variant.name, declared.fields.len(), payload.len()
+            ).into());
+        }
+
+        let enum_type = self.gen_named_type(&enum_ident.name)?;
+        let enum_pointer = self.gen_entry_alloca(enum_type.into(), "enum_value")?;
+        let Ok(tag_pointer) = self.builder.build_struct_gep(enum_pointer, 0, "tag") else {
+            return Err(format!("Couldn't set the tag of {}::{}", enum_ident.name, variant.name).into());
+        };
+        self.builder.build_store(tag_pointer, self.context.i32_type().const_int(tag as u64, false));
+
+        if !payload.is_empty() {
+            let payload_pointer = self.gen_payload_pointer(enum_pointer, declared)?;
+            for (index, (value, field_type)) in payload.iter().zip(&declared.fields).enumerate() {
+                let value = self.gen_field_init(value, field_type)?;
+                let Ok(field_pointer) = self.builder.build_struct_gep(payload_pointer, index as u32, "payload_field") else {
+                    return Err(format!("Couldn't set the payload of {}::{}", enum_ident.name, variant.name).into());
+                };
+                self.builder.build_store(field_pointer, value);
+            }
+        }
+        Ok(self.builder.build_load(enum_pointer, "enum_load").into_struct_value())
+    }
+
+    /// Declares the bindings of a arm as new variables holding a copy of the payload
+    pub(super) fn gen_bindings(&self, enum_pointer: PointerValue<'ctx>, variant: &'ctx Variant, bindings: &[Ident]) -> CompileResult<()> {
+        if bindings.is_empty() {
+            return Ok(());
+        }
+        let payload_pointer = self.gen_payload_pointer(enum_pointer, variant)?;
+        for (index, (binding, field_type)) in bindings.iter().zip(&variant.fields).enumerate() {
+            if binding.name == "_" {
+                continue;
+            }
+            let Ok(field_pointer) = self.builder.build_struct_gep(payload_pointer, index as u32, &binding.name) else {
+                return Err(format!("Couldn't get the payload of {}", variant.ident.name).into());
+            };
+            let field = self.builder.build_load(field_pointer, &binding.name);
+            let alloc = self.gen_entry_alloca(field.get_type(), &binding.name)?;
+            self.builder.build_store(alloc, field);
+            self.declare_variable(&binding.name, alloc.into(), IntSign::of(field_type));
+        }
+        Ok(())
+    }
+
+    /// Checks that the arms are valid for the enum and cover every variant, returns the tags every
+    /// arm matches or None for the `_` arm
+    pub(super) fn gen_enum_cases(&self, match_expr: &'ctx Match, declaration: &'ctx Enum) -> CompileResult<Vec<Option<Vec<IntValue<'ctx>>>>> {
+        let name = &declaration.ident.name;
+        let mut covered = vec![false; declaration.variants.len()];
+        let mut arm_cases = Vec::new();
+        for arm in &match_expr.arms {
+            if arm_cases.contains(&None) {
+                return Err(format!("The arm on line {} is never reached, the `_` arm before it matches every value", arm.line).into());
+            }
+            let patterns = match &arm.pattern {
+                Pattern::Wildcard => {
+                    arm_cases.push(None);
+                    continue;
+                }
+                Pattern::Or(patterns) => patterns.iter().collect(),
+                pattern => vec![pattern],
+            };
+
+            let mut cases = Vec::new();
+            for pattern in patterns {
+                let Pattern::Variant { enum_ident, variant, bindings } = pattern else {
+                    return Err(format!("Expected a variant of {name} in the pattern on line {}", arm.line).into());
+                };
+                let is_same_enum = self.enums.get(&self.type_name(&enum_ident.name)).is_some_and(|found| std::ptr::eq(*found, declaration));
+                if !is_same_enum {
+                    return Err(format!("Expected a variant of {name} but got {}::{} on line {}", enum_ident.name, variant.name, arm.line).into());
+                }
+                let Some(tag) = declaration.get_variant_index_with_name(&variant.name) else {
+                    return Err(format!("The enum {name} has no variant called {} on line {}", variant.name, arm.line).into());
+                };
+                let fields = declaration.variants[tag].fields.len();
+                if !bindings.is_empty() && matches!(arm.pattern, Pattern::Or(_)) {
+                    return Err(format!("Can't bind the payload of {name}::{} on line {}, the arm has more than one pattern", variant.name, arm.line).into());
+                }
+                if !bindings.is_empty() && bindings.len() != fields {
+                    return Err(format!(
+                        "The variant {name}::{} has {fields} fields but the pattern on line {} has {}",
+                        variant.name, arm.line, bindings.len()
+                    ).into());
+                }
+                if covered[tag] {
+                    return Err(format!("The arm on line {} is never reached, {name}::{} is already matched", arm.line, variant.name).into());
+                }
+                covered[tag] = true;
+                cases.push(self.context.i32_type().const_int(tag as u64, false));
+            }
+            arm_cases.push(Some(cases));
+        }
+
+        if !arm_cases.contains(&None) {
+            let missing: Vec<String> = declaration
+                .variants
+                .iter()
+                .zip(&covered)
+                .filter(|(_, covered)| !**covered)
+                .map(|(variant, _)| format!("{name}::{}", variant.ident.name))
+                .collect();
+            if !missing.is_empty() {
+                return Err(format!("The match on {name} doesn't cover {}, add a arm for them or a `_` arm", missing.join(", ")).into());
+            }
+        }
+        Ok(arm_cases)
+    }
+
+    /// Looks up the declaration of the enum a pointer points to by the name of its LLVM type
+    pub(super) fn get_enum_of(&self, enum_pointer: PointerValue<'ctx>) -> CompileResult<&'ctx Enum> {
+        let name = self.get_enum_name(enum_pointer)?;
+        match self.enums.get(&name) {
+            Some(declaration) => Ok(declaration),
+            None => Err(format!("Can only match on a enum, {name} isn't one").into()),
+        }
+    }
+
+    /// The name of the LLVM type a enum pointer points to
+    fn get_enum_name(&self, enum_pointer: PointerValue<'ctx>) -> CompileResult<String> {
+        let AnyTypeEnum::StructType(struct_type) = enum_pointer.get_type().get_element_type() else {
+            return Err("Can only match on a enum".into());
+        };
+        Ok(struct_type.get_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default())
+    }
+}
@@ -0,0 +1,89 @@
+use inkwell::{
+    module::Linkage,
+    types::BasicTypeEnum,
+    values::BasicValueEnum,
+};
+
+use super::{math_codegen::IntSign, CodeGen, CompileResult};
+use crate::parser_v2::ast::{Ident, Node, NodeTypes, Type, TypeValues, Variable};
+
+impl<'ctx> CodeGen<'ctx> {
+    /// Declares a top-level `let` as a global, every function can read and write it. The global
+    /// is internal to the program, so its name can't clash with a symbol of the C library. A
+    /// top-level `const` is a global that is marked constant. The global belongs to the module it
+    /// is declared in, other modules can't use it.
+    pub(super) fn gen_global(&mut self, variable: &'ctx Variable, node: &'ctx Node, constant: bool) -> CompileResult<()> {
+        let name = &variable.ident.name;
+        let global_name = self.mangle(name);
+        if self.globals.contains_key(&global_name) {
+            return Err(format!("The global {name} is declared more than once, found the second one on line {}", node.line).into());
+        }
+        let Some(NodeTypes::Value(value)) = node.right.as_ref().map(|right| &right.node_type) else {
+            return Err(format!("The global {name} on line {} must be given a constant value", node.line).into());
+        };
+
+        let global_type = self.gen_basic_type(&variable.var_type)?;
+        let initializer = self.gen_const_value(&value.value, &variable.var_type, &variable.ident)?;
+        if initializer.get_type() != global_type {
+            return Err(format!("The value of the global {name} on line {} doesn't have the type of the global", node.line).into());
+        }
+
+        let global = self.module.add_global(global_type, None, &global_name);
+        global.set_linkage(Linkage::Internal);
+        global.set_initializer(&initializer);
+        global.set_constant(constant);
+        self.globals.insert(global_name, (global.as_pointer_value(), IntSign::of(&variable.var_type)));
+        Ok(())
+    }
+
+    /// Generates the value of a global, only values that are known before the program runs can be
+    /// the initializer of a global
+    fn gen_const_value(&self, value: &'ctx TypeValues, const_type: &'ctx Type, ident: &Ident) -> CompileResult<BasicValueEnum<'ctx>> {
+        let int = match value {
+            TypeValues::I8(num) => Some(*num as u64),
+            TypeValues::U8(num) => Some(*num as u64),
+            TypeValues::Char(char) => Some(*char as u64),
+            TypeValues::I32(num) | TypeValues::I32Neg(num) => Some(*num as u64),
+            _ => None,
+        };
+        if let Some(int) = int {
+            let BasicTypeEnum::IntType(int_type) = self.gen_basic_type(const_type)? else {
+                return Err(format!("The global {} is given a integer but isn't a integer", ident.name).into());
+            };
+            return Ok(int_type.const_int(int, false).into());
+        }
+
+        match value {
+            TypeValues::F32(float) => Ok(self.context.f32_type().const_float(*float as f64).into()),
+            TypeValues::Array(items) if const_type.is_array => Ok(self.gen_array_values(items, const_type).into()),
+            TypeValues::String(str) if const_type.is_array => {
+                let mut bytes = self.str_into_array(str);
+                if bytes.len() > const_type.size as usize {
+                    return Err(format!("The string \"{str}\" doesn't fit in the global {} of size {}", ident.name, const_type.size).into());
+                }
+                bytes.resize(const_type.size as usize, self.context.i8_type().const_zero());
+                Ok(self.context.i8_type().const_array(&bytes).into())
+            }
+            TypeValues::Struct(name, fields) => {
+                let Some(declaration) = self.structs.get(&self.type_name(&name.name)).copied() else {
+                    return Err(format!("There is no struct called {}", name.name).into());
+                };
+                if let Some((unknown, _)) = fields.iter().find(|(field, _)| declaration.get_field_index_with_name(&field.name).is_none()) {
+                    return Err(format!("The struct {} has no field called {}", name.name, unknown.name).into());
+                }
+                let mut field_values = Vec::new();
+                for field in &declaration.fields {
+                    let Some((_, value)) = fields.iter().find(|(assigned, _)| *assigned == field.ident) else {
+                        return Err(format!("The field {} is missing in the literal of struct {}", field.ident.name, name.name).into());
+                    };
+                    field_values.push(self.gen_const_value(&value.value, &field.r#type, ident)?);
+                }
+                Ok(self.gen_named_type(&name.name)?.const_named_struct(&field_values).into())
+            }
+            _ => Err(format!(
+                "The global {} must be given a constant value, like a number, a string, a array or a struct literal",
+                ident.name
+            ).into()),
+        }
+    }
+}
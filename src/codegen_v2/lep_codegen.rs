@@ -1,178 +1,340 @@
-use std::collections::LinkedList;
-
 use inkwell::{
-    values::{BasicValueEnum, IntValue},
-    IntPredicate,
+    basic_block::BasicBlock,
+    values::{AnyValue, AnyValueEnum, BasicValueEnum, IntValue},
+    FloatPredicate, IntPredicate,
 };
 
-use super::{CodeGen, CompileResult};
+use super::{
+    math_codegen::{IntSign, MathStatementCodegeneration},
+    CodeGen, CompileResult,
+};
 use crate::parser_v2::{
-    ast::{TypeValues, Value, NodeTypes},
+    ast::{Node, NodeTypes, TypeValues, Value},
     parser::lep::{LogicalStatement, Statements},
 };
 
 impl<'ctx> CodeGen<'ctx> {
-    pub fn gen_logcal_statement(&self, logical_statement: &'ctx Box<LogicalStatement>, in_block: Option<&str>) -> CompileResult<()> {
-        let case = self.gen_case(&logical_statement.case, in_block)?;
+    /// Generates a if statement, the branches that don't return continue in the merge block after
+    /// the if.
+    pub fn gen_logcal_statement(&self, logical_statement: &'ctx LogicalStatement) -> CompileResult<()> {
         let (func, _) = self.scope.unwrap();
-        let Some(entry) = func.get_first_basic_block() else {
-            return Err("expected to be in entry".into());
+        let NodeTypes::Block(if_block) = &logical_statement.if_do else {
+            return Err("expected a if block".into());
         };
 
+        let id = self.next_label_id();
+        let if_do = self.context.append_basic_block(func, &format!("if_then.{id}"));
+        let else_do = logical_statement.else_do.as_ref().map(|_| self.context.append_basic_block(func, &format!("if_else.{id}")));
+        let merge = self.context.append_basic_block(func, &format!("if_merge.{id}"));
+        self.gen_case(&logical_statement.case, if_do, else_do.unwrap_or(merge))?;
+
+        self.builder.position_at_end(if_do);
+        self.in_scope(|| self.gen_nodes(if_block))?;
+        self.gen_fall_through(merge);
+
+        if let Some(else_do) = else_do {
+            self.builder.position_at_end(else_do);
+            match &logical_statement.else_do {
+                Some(NodeTypes::Block(else_block)) => self.in_scope(|| self.gen_nodes(else_block))?,
+                Some(NodeTypes::LogicalStatement(else_if)) => self.gen_logcal_statement(else_if)?,
+                _ => return Err("expected a else block or a else if".into()),
+            }
+            self.gen_fall_through(merge);
+        }
+        self.builder.position_at_end(merge);
+        Ok(())
+    }
+
+    /// Jumps to `merge` when the block the builder ended in doesn't return. Nothing jumps to the
+    /// merge block of a if that returns in every branch, so that block stays unreachable.
+    pub(super) fn gen_fall_through(&self, merge: BasicBlock<'ctx>) {
+        let Some(end) = self.builder.get_insert_block() else {
+            return;
+        };
+        if end.get_terminator().is_some() {
+            return;
+        }
+        if end.get_first_use().is_none() {
+            self.builder.build_unreachable();
+        } else {
+            self.builder.build_unconditional_branch(merge);
+        }
+    }
+
+    /// Generates a if expression, both branches jump to a merge block where a phi node picks the
+    /// value of the branch that was taken. A branch that returns before its value doesn't jump to
+    /// the merge block, so the phi only gets the branches that reach it.
+    pub fn gen_if_value(&self, logical_statement: &'ctx LogicalStatement) -> CompileResult<BasicValueEnum<'ctx>> {
+        let (func, _) = self.scope.unwrap();
         let NodeTypes::Block(if_block) = &logical_statement.if_do else {
             return Err("expected a if block".into());
         };
-        let Some(NodeTypes::Block(else_block)) = &logical_statement.else_do else {
-            return Err("as of right now a if block must have a else block, this will get changed. :(".into())
+
+        let id = self.next_label_id();
+        let then_do = self.context.append_basic_block(func, &format!("if_value_then.{id}"));
+        let else_do = self.context.append_basic_block(func, &format!("if_value_else.{id}"));
+        let merge = self.context.append_basic_block(func, &format!("if_value_merge.{id}"));
+        self.gen_case(&logical_statement.case, then_do, else_do)?;
+
+        self.builder.position_at_end(then_do);
+        let then_value = self.gen_block_value(if_block)?;
+        // Nested if expressions move the builder, the phi needs the block the branch ends in
+        let Some(then_end) = self.builder.get_insert_block() else {
+            return Err("expected the if block to end in a block".into());
+        };
+        let mut incoming = Vec::new();
+        if then_end.get_terminator().is_none() {
+            incoming.push((then_value, then_end));
+            self.builder.build_unconditional_branch(merge);
+        }
+
+        self.builder.position_at_end(else_do);
+        let else_value = match &logical_statement.else_do {
+            Some(NodeTypes::Block(else_block)) => self.gen_block_value(else_block)?,
+            Some(NodeTypes::LogicalStatement(else_if)) => self.gen_if_value(else_if)?,
+            _ => return Err("a if that is used as a value must have a else block".into()),
         };
+        let Some(else_end) = self.builder.get_insert_block() else {
+            return Err("expected the else block to end in a block".into());
+        };
+        if else_end.get_terminator().is_none() {
+            incoming.push((else_value, else_end));
+            self.builder.build_unconditional_branch(merge);
+        }
 
-        let if_do = self.gen_block(func, &if_block , Some("if_then_do"))?;
-        self.builder.position_at_end(entry);
-        let else_do = self.gen_block(func, &else_block, Some("else_do"))?;
-        self.builder.position_at_end(entry);
-        self.builder.build_conditional_branch(case, if_do, else_do);
-        Ok(())
+        if then_value.get_type() != else_value.get_type() {
+            return Err("the if block and the else block must have a value of the same type".into());
+        }
+        if incoming.is_empty() {
+            return Err("a if that is used as a value must have a branch that gives a value".into());
+        }
+        self.builder.position_at_end(merge);
+        let phi = self.builder.build_phi(then_value.get_type(), "if_value");
+        for (value, block) in &incoming {
+            phi.add_incoming(&[(value, *block)]);
+        }
+        Ok(phi.as_basic_value())
     }
 
-    fn gen_case(&self, statements: &'ctx Vec<Statements>, in_block: Option<&str>) -> CompileResult<IntValue> {
-        let mut stack: LinkedList<IntValue> = LinkedList::new();
-        let mut statements = statements.iter();
+    /// Generates the statements of a block and returns the value the block ends in
+    pub(super) fn gen_block_value(&self, nodes: &'ctx [Node]) -> CompileResult<BasicValueEnum<'ctx>> {
+        let Some((last, statements)) = nodes.split_last() else {
+            return Err("expected the block of a if or match expression to end in a value".into());
+        };
+        self.in_scope(|| {
+            self.gen_nodes(statements)?;
+            self.gen_tail_value(last)
+        })
+    }
 
-        while let Some(statement) = statements.next() {
-            if let Statements::Or = statement {
-                let Some(lhs) = stack.pop_front() else {
-                    return Err("can't generate a or case if there are no values to compare to".into());
-                };
-                let Some(rhs) = statements.next() else {
-                    return Err("can't generate a or case if there are no values to compare to".into());
+    /// Generates the last node of the block of a if expression
+    fn gen_tail_value(&self, last: &'ctx Node) -> CompileResult<BasicValueEnum<'ctx>> {
+        match &last.node_type {
+            NodeTypes::Value(value) => self.gen_scalar_value(value),
+            NodeTypes::LogicalStatement(statement) => self.gen_if_value(statement),
+            NodeTypes::Match(match_expr) => self.gen_match_value(match_expr),
+            NodeTypes::FunctionCall(call) => {
+                let Some(arguments) = call.get_args(last) else {
+                    return Err("expected the arguments of a function to be in the left branch".into());
                 };
-                let rhs = self.statement_case(rhs, in_block)?;
-                let comp = self.builder.build_or(lhs, rhs, "and");
-                stack.push_front(comp);
-                continue;
+                let call_value = self.gen_func_call(call, arguments, Some("if_value_call"))?;
+                match call_value.as_any_value_enum() {
+                    AnyValueEnum::IntValue(int_value) => Ok(int_value.into()),
+                    AnyValueEnum::FloatValue(float_value) => Ok(float_value.into()),
+                    _ => Err(format!("{} doesn't return a integer or float", call.calls_to.name).into()),
+                }
             }
+            _ => Err(format!("expected the block of a if expression to end in a value on line {}", last.line).into()),
+        }
+    }
 
-            if let Statements::And = statement {
-                let Some(lhs) = stack.pop_front() else {
-                        return Err("can't generate a or case if there are no values to compare to".into());
-                };
-                let Some(rhs) = statements.next() else {
-                        return Err("can't generate a or case if there are no values to compare to".into());
+    /// Branches to `if_true` or `if_false` depending on the cases, cases only get evaluated when
+    /// they can still change the outcome: in `a || b` b only gets evaluated if a is false.
+    ///
+    /// '&&' binds tighter then '||', so the cases get split on '||' first.
+    pub(super) fn gen_case(
+        &self,
+        statements: &'ctx [Statements],
+        if_true: BasicBlock<'ctx>,
+        if_false: BasicBlock<'ctx>,
+    ) -> CompileResult<()> {
+        let (func, _) = self.scope.unwrap();
+        let mut or_cases = statements.split(|statement| *statement == Statements::Or).peekable();
+
+        while let Some(and_cases) = or_cases.next() {
+            // The last case of the '||' chain decides, the others go to the next case when false
+            let (or_next, is_last) = match or_cases.peek() {
+                Some(_) => (self.context.append_basic_block(func, "or_next"), false),
+                None => (if_false, true),
+            };
+
+            let mut and_cases = and_cases.split(|statement| *statement == Statements::And).peekable();
+            while let Some(case) = and_cases.next() {
+                let [case] = case else {
+                    return Err("expected a single case in between '&&' and '||'".into());
                 };
-                let rhs = self.statement_case(rhs, in_block)?;
-                let comp = self.builder.build_and(lhs, rhs, "and");
-                stack.push_front(comp);
-                continue;
+                match and_cases.peek() {
+                    Some(_) => {
+                        let and_next = self.context.append_basic_block(func, "and_next");
+                        self.gen_statement_branch(case, and_next, or_next)?;
+                        self.builder.position_at_end(and_next);
+                    }
+                    None => self.gen_statement_branch(case, if_true, or_next)?,
+                }
             }
 
-            let value = self.statement_case(statement, in_block)?;
-            stack.push_front(value);
+            if !is_last {
+                self.builder.position_at_end(or_next);
+            }
         }
-        let Some(statement) = stack.pop_front() else {
-            return Err("got no cases".into());
-        };
-
-        Ok(statement)
+        Ok(())
     }
 
-    fn statement_case(&self, statement: &'ctx Statements, in_block: Option<&str>) -> CompileResult<IntValue> {
+    fn gen_statement_branch(
+        &self,
+        statement: &'ctx Statements,
+        if_true: BasicBlock<'ctx>,
+        if_false: BasicBlock<'ctx>,
+    ) -> CompileResult<()> {
         match statement {
-            Statements::More(lhs, rhs) => {
-                let value = self.gen_i32_case(lhs, rhs, IntPredicate::SGT, in_block)?;
-                Ok(value)
+            Statements::Group(statements) => self.gen_case(statements, if_true, if_false),
+            // Negating a case is the same as swapping where it branches to
+            Statements::Not(statement) => self.gen_statement_branch(statement, if_false, if_true),
+            statement => {
+                let case = self.statement_case(statement)?;
+                self.builder.build_conditional_branch(case, if_true, if_false);
+                Ok(())
             }
-            Statements::Less(lhs, rhs) => {
-                let value = self.gen_i32_case(lhs, rhs, IntPredicate::SLT, in_block)?;
-                Ok(value)
+        }
+    }
+
+    fn statement_case(&self, statement: &'ctx Statements) -> CompileResult<IntValue> {
+        match statement {
+            Statements::More(lhs, rhs) => self.gen_compare(lhs, rhs, (IntPredicate::SGT, IntPredicate::UGT), FloatPredicate::OGT),
+            Statements::Less(lhs, rhs) => self.gen_compare(lhs, rhs, (IntPredicate::SLT, IntPredicate::ULT), FloatPredicate::OLT),
+            Statements::LessEq(lhs, rhs) => self.gen_compare(lhs, rhs, (IntPredicate::SLE, IntPredicate::ULE), FloatPredicate::OLE),
+            Statements::MoreEq(lhs, rhs) => self.gen_compare(lhs, rhs, (IntPredicate::SGE, IntPredicate::UGE), FloatPredicate::OGE),
+            Statements::EqEq(lhs, rhs) => self.gen_compare(lhs, rhs, (IntPredicate::EQ, IntPredicate::EQ), FloatPredicate::OEQ),
+            Statements::NotEq(lhs, rhs) => self.gen_compare(lhs, rhs, (IntPredicate::NE, IntPredicate::NE), FloatPredicate::ONE),
+            Statements::Atomic(value) => self.gen_atomic_case(value),
+            statement => Err(format!("{statement:?} can't be turned into a single value").into()),
+        }
+    }
+
+    /// A single value is true when it isn't zero, `if done` or `if count(list)`
+    fn gen_atomic_case(&self, value: &'ctx Value) -> CompileResult<IntValue> {
+        let bool_type = self.context.bool_type();
+        match &value.value {
+            TypeValues::True => Ok(bool_type.const_int(1, false)),
+            TypeValues::False => Ok(bool_type.const_int(0, false)),
+            _ => match self.gen_scalar_value(value)? {
+                BasicValueEnum::IntValue(int_value) => {
+                    let zero = int_value.get_type().const_zero();
+                    Ok(self.builder.build_int_compare(IntPredicate::NE, int_value, zero, "atomic"))
+                }
+                BasicValueEnum::FloatValue(float_value) => {
+                    let zero = float_value.get_type().const_zero();
+                    Ok(self.builder.build_float_compare(FloatPredicate::ONE, float_value, zero, "atomic"))
+                }
+                _ => Err("Expected a integer or float value as condition".into()),
+            },
+        }
+    }
+
+    /// Compares two values, integers get compared with the signed or unsigned `int_predicates` and
+    /// floats with the `float_predicate`. When one side is a float the integer side gets converted
+    /// to a float, integers of different sizes get extended to the biggest size by their own sign
+    /// and a literal takes the type of the other side: `c == 113`
+    fn gen_compare(
+        &self,
+        lhs: &'ctx Value,
+        rhs: &'ctx Value,
+        (signed_predicate, unsigned_predicate): (IntPredicate, IntPredicate),
+        float_predicate: FloatPredicate,
+    ) -> CompileResult<IntValue> {
+        let (lhs, lhs_sign) = self.gen_signed_scalar(lhs)?;
+        let (rhs, rhs_sign) = self.gen_signed_scalar(rhs)?;
+
+        match (lhs, rhs) {
+            (BasicValueEnum::IntValue(lhs), BasicValueEnum::IntValue(rhs)) => {
+                let (lhs, rhs, sign) = self.gen_int_operands((lhs, lhs_sign), (rhs, rhs_sign));
+                let int_predicate = if sign.is_unsigned() { unsigned_predicate } else { signed_predicate };
+                Ok(self.builder.build_int_compare(int_predicate, lhs, rhs, "comp"))
             }
-            Statements::LessEq(lhs, rhs) => {
-                let value = self.gen_i32_case(lhs, rhs, IntPredicate::SLE, in_block)?;
-                Ok(value)
+            (BasicValueEnum::FloatValue(lhs), BasicValueEnum::FloatValue(rhs)) => {
+                Ok(self.builder.build_float_compare(float_predicate, lhs, rhs, "comp"))
             }
-            Statements::MoreEq(lhs, rhs) => {
-                let value = self.gen_i32_case(lhs, rhs, IntPredicate::SGE, in_block)?;
-                Ok(value)
+            (BasicValueEnum::IntValue(lhs), BasicValueEnum::FloatValue(rhs)) => {
+                let lhs = match lhs_sign {
+                    IntSign::Unsigned => self.builder.build_unsigned_int_to_float(lhs, rhs.get_type(), "to_float"),
+                    _ => self.builder.build_signed_int_to_float(lhs, rhs.get_type(), "to_float"),
+                };
+                Ok(self.builder.build_float_compare(float_predicate, lhs, rhs, "comp"))
             }
-            Statements::EqEq(lhs, rhs) => {
-                let value = self.gen_i32_case(lhs, rhs, IntPredicate::EQ, in_block)?;
-                Ok(value)
+            (BasicValueEnum::FloatValue(lhs), BasicValueEnum::IntValue(rhs)) => {
+                let rhs = match rhs_sign {
+                    IntSign::Unsigned => self.builder.build_unsigned_int_to_float(rhs, lhs.get_type(), "to_float"),
+                    _ => self.builder.build_signed_int_to_float(rhs, lhs.get_type(), "to_float"),
+                };
+                Ok(self.builder.build_float_compare(float_predicate, lhs, rhs, "comp"))
             }
-            _ => todo!(),
+            (one, other) => Err(format!("the statement given couldn't be compared, only integers and floats can be compared. got {one:#?} but other was {other:#?}").into())
         }
     }
 
-    fn gen_i32_case(
-        &self,
-        value: &Value,
-        or: &Value,
-        operator: IntPredicate,
-        in_block: Option<&str>
-    ) -> CompileResult<IntValue> {
-        match (&value.value, &or.value) {
-            (TypeValues::I32(value), TypeValues::I32(other)) => {
-                let i32_type = self.context.i32_type();
-                let (int_value, int_other_value) = (
-                    i32_type.const_int(*value as u64, false),
-                    i32_type.const_int(*other as u64, false),
-                );
-                Ok(self.builder.build_int_compare(operator, int_value, int_other_value, "comp"))
+    /// Generates a integer or float value like [`CodeGen::gen_scalar_value`] and its sign, a number
+    /// literal takes the sign of the other side
+    pub(super) fn gen_signed_scalar(&self, value: &'ctx Value) -> CompileResult<(BasicValueEnum<'ctx>, IntSign)> {
+        let sign = match &value.value {
+            TypeValues::Math(math) => {
+                let (math, sign) = self.gen_signed_math(math)?;
+                return Ok((math.into(), sign));
             }
-            (TypeValues::Identifier(ident), TypeValues::I32(number)) => {
-                let get_ident = self.get_ident(&ident, in_block)?;
-                if get_ident.is_pointer_value() {
-                    let load_ident = self.builder.build_load(get_ident.into_pointer_value(), "if_load");
-                    let BasicValueEnum::IntValue(value) = load_ident else {
-                        return Err("when comparing expected value to be integer".into());
-                    };
-                    let i32_type = self.context.i32_type();
-                    let i32_value = i32_type.const_int(*number as u64, false);
-                    Ok(self.builder.build_int_compare(operator, value, i32_value, "comp"))
-                } else {
-                    if get_ident.is_int_value() {
-                        let int_value = get_ident.into_int_value();
-                        let i32_type = self.context.i32_type();
-                        let i32_value = i32_type.const_int(*number as u64, false);
-                        Ok(self.builder.build_int_compare(operator, int_value, i32_value,  "comp"))
-                    } else {
-                        Err("Can't compare none integer values".into())
-                    }
-                }
+            TypeValues::I32(_) | TypeValues::I32Neg(_) => IntSign::Literal,
+            TypeValues::U8(_) | TypeValues::Char(_) => IntSign::Unsigned,
+            TypeValues::Identifier(ident) => self.get_ident_sign(ident),
+            TypeValues::FieldAccess(ident, fields) => IntSign::of(self.get_field_type(ident, fields)?),
+            TypeValues::FunctionCall(call, _) => self.get_call_sign(call),
+            _ => IntSign::Signed,
+        };
+        Ok((self.gen_scalar_value(value)?, sign))
+    }
+
+    /// Generates a integer or float value, used for the sides of a comparison and the value of a
+    /// if expression. This can be a literal, a identifier, a field, a function call or a math statement
+    pub(super) fn gen_scalar_value(&self, value: &'ctx Value) -> CompileResult<BasicValueEnum<'ctx>> {
+        match &value.value {
+            TypeValues::F32(float) => {
+                let f32_type = self.context.f32_type();
+                Ok(f32_type.const_float(*float as f64).into())
             }
-            (TypeValues::I32(number), TypeValues::Identifier(ident)) => {
-                let get_ident = self.get_ident(&ident, in_block)?;
+            TypeValues::Char(char) => {
+                let i8_type = self.context.i8_type();
+                Ok(i8_type.const_int(*char as u64, false).into())
+            }
+            TypeValues::Identifier(ident) => {
+                let get_ident = self.get_ident(ident)?;
                 if get_ident.is_pointer_value() {
-                    let load_ident = self.builder.build_load(get_ident.into_pointer_value(), "if_load");
-                    let BasicValueEnum::IntValue(value) = load_ident else {
-                        return Err("when comparing expected value to be integer".into());
-                    };
-                    let i32_type = self.context.i32_type();
-                    let i32_value = i32_type.const_int(*number as u64, false);
-                    Ok(self.builder.build_int_compare(operator, i32_value, value, "comp"))
-                } else {
-                    if get_ident.is_int_value() {
-                        let int_value = get_ident.into_int_value();
-                    let i32_type = self.context.i32_type();
-                    let i32_value = i32_type.const_int(*number as u64, false);
-                        Ok(self.builder.build_int_compare(operator, i32_value, int_value, "comp"))
-                    } else {
-                        Err("Can't compare none integer values".into())
-                    }
+                    return Ok(self.builder.build_load(get_ident.into_pointer_value(), "if_load"));
+                }
+                if get_ident.is_int_value() {
+                    return Ok(get_ident.into_int_value().into());
+                }
+                if get_ident.is_float_value() {
+                    return Ok(get_ident.into_float_value().into());
                 }
+                Err(format!("The value of {ident} must be a integer or a float").into())
             }
-            (TypeValues::Identifier(lhv), TypeValues::Identifier(rhv)) => {
-                let (lhv, rhv) = (self.get_ident(&lhv, in_block)?, self.get_ident(&rhv, in_block)?);
-                if lhv.is_pointer_value() && rhv.is_pointer_value() {
-                    let (load1, load2) = 
-                        (self.builder.build_load(lhv.into_pointer_value(), "load1"), self.builder.build_load(rhv.into_pointer_value(), "load2"));
-                    let (lhv, rhv) = (load1.into_int_value(), load2.into_int_value());
-                    Ok(self.builder.build_int_compare(operator,  rhv, lhv,  "comp"))
-                } else if lhv.is_int_value() && rhv.is_int_value() {
-                    Ok(self.builder.build_int_compare(operator,   rhv.into_int_value(), lhv.into_int_value(),  "comp"))
-                } else {
-                    Err("Can't compare none integer values".into())
+            TypeValues::FieldAccess(ident, fields) => self.gen_field_value(ident, fields),
+            TypeValues::FunctionCall(call, arguments) => {
+                let call_value = self.gen_func_call(call, arguments, Some("if_call"))?;
+                match call_value.as_any_value_enum() {
+                    AnyValueEnum::IntValue(int_value) => Ok(int_value.into()),
+                    AnyValueEnum::FloatValue(float_value) => Ok(float_value.into()),
+                    _ => Err(format!("The return value of {} must be a integer or a float", call.calls_to.name).into()),
                 }
             }
-            (one, other) => Err(format!("the statement given couldn't be compared, when comparing values they must be of the same type. got {one:#?} but other was {other:#?}").into())
+            _ => Ok(self.gen_math_operand(value)?.into()),
         }
     }
 }
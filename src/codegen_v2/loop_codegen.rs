@@ -0,0 +1,184 @@
+use inkwell::{
+    types::AnyTypeEnum,
+    values::{BasicValueEnum, PointerValue},
+    IntPredicate,
+};
+
+use super::{math_codegen::{IntSign, MathStatementCodegeneration}, CodeGen, CompileResult};
+use crate::parser_v2::{
+    ast::{TypeValues, Value},
+    parser::loops::{ForIterator, ForLoop, WhileLoop},
+};
+
+impl<'ctx> CodeGen<'ctx> {
+    /// Generates a while loop: the header checks the cases and jumps to the body or the exit, the
+    /// body jumps back to the header. The builder ends in the exit block.
+    pub fn gen_while_loop(&self, while_loop: &'ctx WhileLoop) -> CompileResult<()> {
+        let (func, _) = self.scope.unwrap();
+        let id = self.next_label_id();
+        let header = self.context.append_basic_block(func, &format!("while_header.{id}"));
+        let body = self.context.append_basic_block(func, &format!("while_body.{id}"));
+        let exit = self.context.append_basic_block(func, &format!("while_exit.{id}"));
+
+        self.builder.build_unconditional_branch(header);
+        self.builder.position_at_end(header);
+        self.gen_case(&while_loop.case, body, exit)?;
+
+        self.builder.position_at_end(body);
+        self.loops.borrow_mut().push((header, exit));
+        let body_result = self.in_scope(|| self.gen_nodes(&while_loop.body));
+        self.loops.borrow_mut().pop();
+        body_result?;
+        self.gen_fall_through(header);
+
+        self.builder.position_at_end(exit);
+        Ok(())
+    }
+
+    /// Generates a for loop over a range or an array. A counter goes from the start to the end,
+    /// every iteration the loop variable gets the counter, or the item of the array at the
+    /// counter. Changing the loop variable in the body doesn't change the counter. The counter and
+    /// the loop variable are allocated in the entry block, so the stack doesn't grow when the loop
+    /// is nested in another loop.
+    pub fn gen_for_loop(&self, for_loop: &'ctx ForLoop) -> CompileResult<()> {
+        let (func, _) = self.scope.unwrap();
+        let id = self.next_label_id();
+
+        let (start, end, inclusive, array, sign) = match &for_loop.iterator {
+            ForIterator::Range { start, end, inclusive } => {
+                let (start, start_sign) = self.gen_signed_operand(start)?;
+                let (end, end_sign) = self.gen_signed_operand(end)?;
+                // Literals are i32, the range takes the type of the side that isn't a literal `0..byte`
+                let (int_type, sign) = match (start_sign, end_sign) {
+                    (IntSign::Literal, IntSign::Literal) => (start.get_type(), IntSign::Signed),
+                    (IntSign::Literal, _) => (end.get_type(), end_sign),
+                    _ => (start.get_type(), start_sign),
+                };
+                let start = self.gen_int_resize(start, int_type, start_sign);
+                let end = self.gen_int_resize(end, int_type, end_sign);
+                (start, end, *inclusive, None, sign)
+            }
+            ForIterator::Array(array) => {
+                // The loop variable gets the sign of the items of the array
+                let sign = match &array.value {
+                    TypeValues::Identifier(ident) => self.get_ident_sign(ident),
+                    _ => IntSign::Signed,
+                };
+                let (array, length) = self.gen_array_pointer(array)?;
+                let i32_type = self.context.i32_type();
+                (i32_type.const_zero(), i32_type.const_int(length as u64, false), false, Some(array), sign)
+            }
+        };
+
+        let counter = self.gen_entry_alloca(start.get_type().into(), &format!("for_counter.{id}"))?;
+        self.builder.build_store(counter, start);
+        let binding_type = match array {
+            Some(array) => {
+                let AnyTypeEnum::ArrayType(array_type) = array.get_type().get_element_type() else {
+                    unreachable!("gen_array_pointer only returns pointers to arrays")
+                };
+                array_type.get_element_type()
+            }
+            None => start.get_type().into(),
+        };
+        let binding = self.gen_entry_alloca(binding_type, &for_loop.binding.name)?;
+
+        let header = self.context.append_basic_block(func, &format!("for_header.{id}"));
+        let body = self.context.append_basic_block(func, &format!("for_body.{id}"));
+        let next = self.context.append_basic_block(func, &format!("for_next.{id}"));
+        let exit = self.context.append_basic_block(func, &format!("for_exit.{id}"));
+        self.builder.build_unconditional_branch(header);
+
+        self.builder.position_at_end(header);
+        let index = self.builder.build_load(counter, "for_index").into_int_value();
+        let predicate = match (inclusive, sign.is_unsigned()) {
+            (true, true) => IntPredicate::ULE,
+            (true, false) => IntPredicate::SLE,
+            (false, true) => IntPredicate::ULT,
+            (false, false) => IntPredicate::SLT,
+        };
+        let condition = self.builder.build_int_compare(predicate, index, end, "for_cond");
+        self.builder.build_conditional_branch(condition, body, exit);
+
+        self.builder.position_at_end(body);
+        let value: BasicValueEnum = match array {
+            Some(array) => {
+                let zero = self.context.i32_type().const_zero();
+                // The counter never leaves the bounds of the array
+                let item = unsafe { self.builder.build_in_bounds_gep(array, &[zero, index], "for_item") };
+                self.builder.build_load(item, "for_load")
+            }
+            None => index.into(),
+        };
+        self.builder.build_store(binding, value);
+
+        self.loops.borrow_mut().push((next, exit));
+        let body_result = self.in_scope(|| {
+            self.declare_variable(&for_loop.binding.name, binding.into(), sign);
+            self.gen_nodes(&for_loop.body)
+        });
+        self.loops.borrow_mut().pop();
+        body_result?;
+        self.gen_fall_through(next);
+
+        self.builder.position_at_end(next);
+        let index = self.builder.build_load(counter, "for_index").into_int_value();
+        if inclusive {
+            // The end of `..=` can be the max of the type, the counter would wrap around instead of
+            // getting past the end, so the loop stops at the end before incrementing
+            let increment = self.context.insert_basic_block_after(next, &format!("for_increment.{id}"));
+            let is_end = self.builder.build_int_compare(IntPredicate::EQ, index, end, "for_is_end");
+            self.builder.build_conditional_branch(is_end, exit, increment);
+            self.builder.position_at_end(increment);
+        }
+        let one = index.get_type().const_int(1, false);
+        let index = self.builder.build_int_add(index, one, "for_increment");
+        self.builder.build_store(counter, index);
+        self.builder.build_unconditional_branch(header);
+
+        self.builder.position_at_end(exit);
+        Ok(())
+    }
+
+    /// Returns a pointer to the array a for loop iterates over and the length of the array, array
+    /// paramaters get copied to the stack so they can be indexed
+    fn gen_array_pointer(&self, value: &'ctx Value) -> CompileResult<(PointerValue<'ctx>, u32)> {
+        let TypeValues::Identifier(ident) = &value.value else {
+            return Err("A for loop can only loop over a range or a array variable".into());
+        };
+        let variable = self.get_ident(ident)?;
+
+        let pointer = if variable.is_array_value() {
+            let array = variable.into_array_value();
+            let alloc = self.gen_entry_alloca(array.get_type().into(), "for_array")?;
+            self.builder.build_store(alloc, array);
+            alloc
+        } else if variable.is_pointer_value() {
+            variable.into_pointer_value()
+        } else {
+            return Err(format!("Can't loop over {ident}, it isn't a array").into());
+        };
+
+        match pointer.get_type().get_element_type() {
+            AnyTypeEnum::ArrayType(array_type) => Ok((pointer, array_type.len())),
+            _ => Err(format!("Can't loop over {ident}, it isn't a array").into()),
+        }
+    }
+
+    /// Jumps to the exit of the innermost loop for `break`, or to the start of its next iteration
+    /// for `continue`
+    pub fn gen_loop_jump(&self, is_break: bool, line: usize) -> CompileResult<()> {
+        let (func, _) = self.scope.unwrap();
+        let Some((next, exit)) = self.loops.borrow().last().copied() else {
+            let keyword = if is_break { "break" } else { "continue" };
+            return Err(format!("Found {keyword} outside of a loop on line {line}").into());
+        };
+        self.builder.build_unconditional_branch(if is_break { exit } else { next });
+
+        // The statements after a break or continue are never run, they go in a block nothing jumps to
+        let id = self.next_label_id();
+        let after = self.context.append_basic_block(func, &format!("after_jump.{id}"));
+        self.builder.position_at_end(after);
+        Ok(())
+    }
+}
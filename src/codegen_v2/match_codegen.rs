@@ -0,0 +1,371 @@
+use inkwell::{
+    basic_block::BasicBlock,
+    types::{AnyTypeEnum, IntType},
+    values::{AnyValue, AnyValueEnum, BasicValueEnum, IntValue, PointerValue},
+    IntPredicate,
+};
+
+use super::{math_codegen::IntSign, CodeGen, CompileResult};
+use crate::parser_v2::{
+    ast::{Enum, TypeValues, Value},
+    parser::matches::{Match, MatchArm, Pattern},
+};
+
+/// The most values a range pattern can have to become a case of the switch for every value, a
+/// bigger range is checked by comparing with its start and end
+const MAX_RANGE_CASES: i128 = 1024;
+
+/// The value a match switches on
+enum MatchTarget<'ctx> {
+    /// A pointer to a enum value, the switch is on its tag
+    Enum(PointerValue<'ctx>, &'ctx Enum),
+    /// A integer or char and its sign
+    Int(IntValue<'ctx>, IntSign),
+}
+
+/// The values a arm matches, None is used for the `_` arm
+///
+/// **cases** every value is a case of the switch
+/// **ranges** the ranges that have too many values to be cases, from the start to the end
+struct ArmCases<'ctx> {
+    cases: Vec<IntValue<'ctx>>,
+    ranges: Vec<(IntValue<'ctx>, IntValue<'ctx>)>,
+}
+
+impl<'ctx> CodeGen<'ctx> {
+    /// Generates a match statement, the arms that don't return continue after the match
+    pub fn gen_match(&self, match_expr: &'ctx Match) -> CompileResult<()> {
+        self.gen_match_arms(match_expr, "match", |arm| {
+            self.gen_nodes(&arm.body)?;
+            Ok(None)
+        })?;
+        Ok(())
+    }
+
+    /// Generates a match expression, a phi node picks the value of the arm that was taken
+    pub fn gen_match_value(&self, match_expr: &'ctx Match) -> CompileResult<BasicValueEnum<'ctx>> {
+        let incoming = self.gen_match_arms(match_expr, "match_value", |arm| {
+            Ok(Some(self.gen_block_value(&arm.body)?))
+        })?;
+
+        let Some((first, _)) = incoming.first() else {
+            return Err("a match that is used as a value must have a arm that gives a value".into());
+        };
+        if incoming.iter().any(|(value, _)| value.get_type() != first.get_type()) {
+            return Err("every arm of a match expression must have a value of the same type".into());
+        }
+        let phi = self.builder.build_phi(first.get_type(), "match_value");
+        for (value, block) in &incoming {
+            phi.add_incoming(&[(value, *block)]);
+        }
+        Ok(phi.as_basic_value())
+    }
+
+    /// Lowers a match to a switch, on the tag for enums and on the value itself for integers and
+    /// chars, so LLVM can turn it into a jump table. The bindings of a arm are copies of the
+    /// payload. Returns the values of the arms that gave one with the block they ended in, the
+    /// builder ends in the block after the match.
+    fn gen_match_arms(
+        &self,
+        match_expr: &'ctx Match,
+        name: &str,
+        gen_arm: impl Fn(&'ctx MatchArm) -> CompileResult<Option<BasicValueEnum<'ctx>>>,
+    ) -> CompileResult<Vec<(BasicValueEnum<'ctx>, BasicBlock<'ctx>)>> {
+        let (func, _) = self.scope.unwrap();
+        let target = self.gen_match_target(&match_expr.value)?;
+        let (switch_value, sign, arm_cases) = match target {
+            MatchTarget::Enum(enum_pointer, declaration) => {
+                let Ok(tag_pointer) = self.builder.build_struct_gep(enum_pointer, 0, "tag") else {
+                    return Err(format!("Couldn't get the tag of {}", declaration.ident.name).into());
+                };
+                let tag = self.builder.build_load(tag_pointer, "tag").into_int_value();
+                let arm_cases = self
+                    .gen_enum_cases(match_expr, declaration)?
+                    .into_iter()
+                    .map(|cases| cases.map(|cases| ArmCases { cases, ranges: Vec::new() }))
+                    .collect();
+                (tag, IntSign::Unsigned, arm_cases)
+            }
+            MatchTarget::Int(value, sign) => (value, sign, self.gen_int_cases(match_expr, value.get_type(), sign)?),
+        };
+
+        let id = self.next_label_id();
+        let arm_blocks: Vec<BasicBlock> = (0..match_expr.arms.len())
+            .map(|index| self.context.append_basic_block(func, &format!("{name}_arm{index}.{id}")))
+            .collect();
+        let merge = self.context.append_basic_block(func, &format!("{name}_merge.{id}"));
+
+        // The ranges don't overlap, so the order they are checked in doesn't matter
+        let (at_least, at_most) = match sign {
+            IntSign::Unsigned => (IntPredicate::UGE, IntPredicate::ULE),
+            _ => (IntPredicate::SGE, IntPredicate::SLE),
+        };
+        let mut cases = Vec::new();
+        for (arm_cases, block) in arm_cases.iter().zip(&arm_blocks) {
+            let Some(arm_cases) = arm_cases else { continue };
+            for (start, end) in &arm_cases.ranges {
+                let Some(current) = self.builder.get_insert_block() else {
+                    return Err("expected the match to start in a block".into());
+                };
+                let next = self.context.insert_basic_block_after(current, &format!("{name}_range.{id}"));
+                let after_start = self.builder.build_int_compare(at_least, switch_value, *start, "after_start");
+                let before_end = self.builder.build_int_compare(at_most, switch_value, *end, "before_end");
+                let in_range = self.builder.build_and(after_start, before_end, "in_range");
+                self.builder.build_conditional_branch(in_range, *block, next);
+                self.builder.position_at_end(next);
+            }
+            cases.extend(arm_cases.cases.iter().map(|case| (*case, *block)));
+        }
+        // The arms cover every value, without a `_` arm no value ends up in the default block
+        let wildcard = arm_cases.iter().position(|cases| cases.is_none());
+        let default = match wildcard {
+            Some(index) => arm_blocks[index],
+            None => self.context.append_basic_block(func, &format!("{name}_unreachable.{id}")),
+        };
+        self.builder.build_switch(switch_value, default, &cases);
+        if wildcard.is_none() {
+            self.builder.position_at_end(default);
+            self.builder.build_unreachable();
+        }
+
+        let mut incoming = Vec::new();
+        for (arm, block) in match_expr.arms.iter().zip(arm_blocks) {
+            self.builder.position_at_end(block);
+            let value = self.in_scope(|| {
+                if let (MatchTarget::Enum(enum_pointer, declaration), Pattern::Variant { variant, bindings, .. }) = (&target, &arm.pattern) {
+                    if let Some(tag) = declaration.get_variant_index_with_name(&variant.name) {
+                        self.gen_bindings(*enum_pointer, &declaration.variants[tag], bindings)?;
+                    }
+                }
+                gen_arm(arm)
+            })?;
+
+            let Some(end) = self.builder.get_insert_block() else {
+                return Err("expected the match arm to end in a block".into());
+            };
+            match value {
+                Some(value) if end.get_terminator().is_none() => {
+                    incoming.push((value, end));
+                    self.builder.build_unconditional_branch(merge);
+                }
+                _ => self.gen_fall_through(merge),
+            }
+        }
+        self.builder.position_at_end(merge);
+        Ok(incoming)
+    }
+
+    /// Returns the cases of every arm for a match on a integer or char, None for the `_` arm.
+    /// A arm can match values an arm before it already matches, the first arm gets them. A pattern
+    /// of which every value is already matched is never reached, and the patterns of a arm can't
+    /// overlap each other.
+    fn gen_int_cases(&self, match_expr: &'ctx Match, int_type: IntType<'ctx>, sign: IntSign) -> CompileResult<Vec<Option<ArmCases<'ctx>>>> {
+        let domain = Self::int_domain(int_type.get_bit_width(), sign);
+        let int_value = |value: i128| int_type.const_int(value as u64, false);
+        // The values the arms so far match, sorted and without overlap
+        let mut matched = Vec::new();
+        let mut arm_cases = Vec::new();
+        for arm in &match_expr.arms {
+            if arm_cases.iter().any(Option::is_none) {
+                return Err(format!("The arm on line {} is never reached, the `_` arm before it matches every value", arm.line).into());
+            }
+            let mut ranges = Vec::new();
+            Self::pattern_int_ranges(&arm.pattern, domain, arm.line, &mut ranges)?;
+            if ranges.is_empty() {
+                arm_cases.push(None);
+                continue;
+            }
+
+            let mut cases = ArmCases { cases: Vec::new(), ranges: Vec::new() };
+            for (index, &(start, end)) in ranges.iter().enumerate() {
+                if ranges[..index].iter().any(|&(other_start, other_end)| start <= other_end && other_start <= end) {
+                    return Err(format!("The patterns of the arm on line {} overlap, {} is matched twice", arm.line, Self::range_str(start, end)).into());
+                }
+                let unmatched = Self::subtract_ranges((start, end), &matched);
+                if unmatched.is_empty() {
+                    return Err(format!(
+                        "The pattern {} on line {} is never reached, the arms before it match all of its values",
+                        Self::range_str(start, end), arm.line
+                    ).into());
+                }
+                for (start, end) in unmatched {
+                    if end - start < MAX_RANGE_CASES {
+                        cases.cases.extend((start..=end).map(int_value));
+                    } else {
+                        cases.ranges.push((int_value(start), int_value(end)));
+                    }
+                }
+                Self::insert_range(&mut matched, (start, end));
+            }
+            arm_cases.push(Some(cases));
+        }
+
+        if !arm_cases.iter().any(Option::is_none) {
+            if let Some(missing) = Self::subtract_ranges(domain, &matched).first() {
+                return Err(format!(
+                    "The match doesn't cover every value of the integer, {} isn't matched, add a `_` arm for the values that aren't matched",
+                    missing.0
+                ).into());
+            }
+        }
+        Ok(arm_cases)
+    }
+
+    /// The smallest and biggest value of a integer of `bits` wide with the sign of the value that
+    /// is matched on, a literal is signed
+    fn int_domain(bits: u32, sign: IntSign) -> (i128, i128) {
+        match sign {
+            IntSign::Unsigned => (0, (1i128 << bits) - 1),
+            _ => (-(1i128 << (bits - 1)), (1i128 << (bits - 1)) - 1),
+        }
+    }
+
+    /// Adds the values a pattern matches to `ranges` as ranges that include their end, a `_`
+    /// pattern adds nothing
+    fn pattern_int_ranges(pattern: &Pattern, domain: (i128, i128), line: usize, ranges: &mut Vec<(i128, i128)>) -> CompileResult<()> {
+        match pattern {
+            Pattern::Wildcard => {}
+            Pattern::Literal(value) => {
+                let value = Self::fit_int(Self::pattern_literal(value, line)?, domain, line)?;
+                ranges.push((value, value));
+            }
+            Pattern::Range { start, end, inclusive } => {
+                let start_int = Self::pattern_literal(start, line)?;
+                let mut end_int = Self::pattern_literal(end, line)?;
+                if !inclusive {
+                    end_int -= 1;
+                }
+                if start_int > end_int {
+                    return Err(format!("The range on line {line} is empty, the start must be before the end").into());
+                }
+                ranges.push((Self::fit_int(start_int, domain, line)?, Self::fit_int(end_int, domain, line)?));
+            }
+            Pattern::Or(patterns) => {
+                for pattern in patterns {
+                    Self::pattern_int_ranges(pattern, domain, line, ranges)?;
+                }
+            }
+            Pattern::Variant { enum_ident, variant, .. } => {
+                return Err(format!("Can't match a integer or char with {}::{} on line {line}", enum_ident.name, variant.name).into());
+            }
+        }
+        Ok(())
+    }
+
+    /// Checks that a integer of a pattern fits into the type that is matched on, `-1` doesn't
+    /// fit into a u8 and `255` doesn't fit into a i8
+    fn fit_int(literal: i128, (min, max): (i128, i128), line: usize) -> CompileResult<i128> {
+        if literal < min || literal > max {
+            return Err(format!("The pattern {literal} on line {line} doesn't fit into the value that is matched on, it goes from {min} to {max}").into());
+        }
+        Ok(literal)
+    }
+
+    /// The parts of `range` that aren't in `matched`, `matched` is sorted and has no overlap
+    fn subtract_ranges((mut start, end): (i128, i128), matched: &[(i128, i128)]) -> Vec<(i128, i128)> {
+        let mut unmatched = Vec::new();
+        for &(matched_start, matched_end) in matched {
+            if matched_end < start {
+                continue;
+            }
+            if matched_start > end {
+                break;
+            }
+            if matched_start > start {
+                unmatched.push((start, matched_start - 1));
+            }
+            start = matched_end + 1;
+        }
+        if start <= end {
+            unmatched.push((start, end));
+        }
+        unmatched
+    }
+
+    /// A range of a error, a range of one value is just the value
+    fn range_str(start: i128, end: i128) -> String {
+        match start == end {
+            true => start.to_string(),
+            false => format!("{start}..={end}"),
+        }
+    }
+
+    /// Adds a range to `matched` and keeps it sorted, ranges that overlap or touch are merged
+    fn insert_range(matched: &mut Vec<(i128, i128)>, (start, end): (i128, i128)) {
+        let index = matched.partition_point(|&(matched_start, _)| matched_start < start);
+        matched.insert(index, (start, end));
+        let mut merged: Vec<(i128, i128)> = Vec::with_capacity(matched.len());
+        for &(start, end) in matched.iter() {
+            match merged.last_mut() {
+                Some(last) if start <= last.1 + 1 => last.1 = last.1.max(end),
+                _ => merged.push((start, end)),
+            }
+        }
+        *matched = merged;
+    }
+
+    fn pattern_literal(value: &Value, line: usize) -> CompileResult<i128> {
+        match &value.value {
+            TypeValues::I8(value) => Ok(*value as i128),
+            TypeValues::U8(value) => Ok(*value as i128),
+            TypeValues::I32(value) | TypeValues::I32Neg(value) => Ok(*value as i128),
+            TypeValues::Char(value) => Ok(*value as i128),
+            value => Err(format!("Expected a integer or char in the pattern on line {line}, got {value:?}").into()),
+        }
+    }
+
+    /// Generates the value that gets matched on, enums get stored on the stack so the tag and
+    /// the payload can be read through a pointer
+    fn gen_match_target(&self, value: &'ctx Value) -> CompileResult<MatchTarget<'ctx>> {
+        let match_value: BasicValueEnum = match &value.value {
+            TypeValues::Identifier(ident) => {
+                let variable = self.get_ident(ident)?;
+                if variable.is_pointer_value() {
+                    let pointer = variable.into_pointer_value();
+                    if let AnyTypeEnum::StructType(_) = pointer.get_type().get_element_type() {
+                        return Ok(MatchTarget::Enum(pointer, self.get_enum_of(pointer)?));
+                    }
+                    self.builder.build_load(pointer, "match_load")
+                } else if variable.is_struct_value() {
+                    variable.into_struct_value().into()
+                } else if variable.is_int_value() {
+                    variable.into_int_value().into()
+                } else {
+                    return Err(format!("Can only match on a enum, a integer or a char, {ident} isn't one").into());
+                }
+            }
+            TypeValues::EnumVariant(enum_ident, variant, payload) => self.gen_enum_value(enum_ident, variant, payload)?.into(),
+            TypeValues::FunctionCall(call, arguments) => {
+                let call_value = self.gen_func_call(call, arguments, Some("match_call"))?;
+                match call_value.as_any_value_enum() {
+                    AnyValueEnum::StructValue(struct_value) => struct_value.into(),
+                    AnyValueEnum::IntValue(int_value) => int_value.into(),
+                    _ => return Err(format!("Can only match on a enum, a integer or a char, {} doesn't return one", call.calls_to.name).into()),
+                }
+            }
+            _ => self.gen_scalar_value(value)?,
+        };
+
+        match match_value {
+            BasicValueEnum::IntValue(int_value) => Ok(MatchTarget::Int(int_value, self.gen_match_sign(value)?)),
+            BasicValueEnum::StructValue(struct_value) => {
+                let alloc = self.gen_entry_alloca(struct_value.get_type().into(), "match_value")?;
+                self.builder.build_store(alloc, struct_value);
+                Ok(MatchTarget::Enum(alloc, self.get_enum_of(alloc)?))
+            }
+            _ => Err("Can only match on a enum, a integer or a char".into()),
+        }
+    }
+
+    /// The sign of the integer or char that is matched on, it decides which patterns fit
+    fn gen_match_sign(&self, value: &'ctx Value) -> CompileResult<IntSign> {
+        let sign = match &value.value {
+            TypeValues::Identifier(ident) => self.get_ident_sign(ident),
+            TypeValues::FunctionCall(call, _) => self.get_call_sign(call),
+            TypeValues::FieldAccess(ident, fields) => IntSign::of(self.get_field_type(ident, fields)?),
+            TypeValues::U8(_) | TypeValues::Char(_) => IntSign::Unsigned,
+            _ => IntSign::Signed,
+        };
+        Ok(sign)
+    }
+}
@@ -1,248 +1,216 @@
-use std::collections::LinkedList;
-
-use inkwell::values::{AnyValue, IntValue};
+use inkwell::types::IntType;
+use inkwell::values::{AnyValue, BasicValueEnum, IntValue};
 
 use crate::{
-    parser_v2::ast::{Math, TypeValues},
+    parser_v2::ast::{Math, Type, TypeValues, Types, Value},
     zon_parser::lexer::Operator,
 };
 
 use super::{CodeGen, CompileResult};
 
+/// [`IntSign`]
+/// How a integer gets extended to a wider type and divided
+#[derive(Clone, Copy, PartialEq, Debug)]
+pub enum IntSign {
+    Signed,
+    Unsigned,
+    /// A number literal, it takes the type of the other side, `byte + 1`
+    Literal,
+}
+
+impl IntSign {
+    /// The sign of a variable, paramater or field of the declared type, a char is a unsigned byte
+    pub fn of(declared: &Type) -> IntSign {
+        match declared.r#type {
+            Types::U8 | Types::Char => IntSign::Unsigned,
+            _ => IntSign::Signed,
+        }
+    }
+
+    pub fn is_unsigned(self) -> bool {
+        self == IntSign::Unsigned
+    }
+}
+
 pub trait MathStatementCodegeneration<'ctx> {
-    fn gen_math_value(
+    fn gen_math_value(&self, math_statement: &'ctx Math) -> CompileResult<IntValue<'ctx>>;
+    /// Generates a math statement and the sign of its result
+    fn gen_signed_math(&self, math_statement: &'ctx Math) -> CompileResult<(IntValue<'ctx>, IntSign)>;
+    /// Generates a single value of a math statement, a identifier, a function call or a literal
+    fn gen_math_operand(&self, value: &'ctx Value) -> CompileResult<IntValue<'ctx>>;
+    /// Generates a single value of a math statement and its sign
+    fn gen_signed_operand(&self, value: &'ctx Value) -> CompileResult<(IntValue<'ctx>, IntSign)>;
+    /// Generates the binary integer operation of a math operator. Both sides get the type of the
+    /// widest side first, every side is extended by its own sign
+    fn gen_int_operation(
         &self,
-        math_statement: &'ctx Math,
-        current_block: Option<&str>,
-    ) -> CompileResult<IntValue<'ctx>>;
+        op: &Operator,
+        lhs: (IntValue<'ctx>, IntSign),
+        rhs: (IntValue<'ctx>, IntSign),
+        name: &str,
+    ) -> CompileResult<(IntValue<'ctx>, IntSign)>;
+    /// Gives both sides of a binary operation or comparison the type of the widest side, a literal
+    /// takes the type of the other side. Returns both sides and the sign of the operation
+    fn gen_int_operands(
+        &self,
+        lhs: (IntValue<'ctx>, IntSign),
+        rhs: (IntValue<'ctx>, IntSign),
+    ) -> (IntValue<'ctx>, IntValue<'ctx>, IntSign);
+    /// Truncates or extends a integer to `int_type`, it is extended by its sign
+    fn gen_int_resize(&self, value: IntValue<'ctx>, int_type: IntType<'ctx>, sign: IntSign) -> IntValue<'ctx>;
 }
 
 impl<'ctx> MathStatementCodegeneration<'ctx> for CodeGen<'ctx> {
-    fn gen_math_value(
-        &self,
-        math_statement: &'ctx Math,
-        current_block: Option<&str>,
-    ) -> CompileResult<IntValue<'ctx>> {
-        let mut operator_stack: LinkedList<&Operator> = LinkedList::new();
-        let mut num_stack: LinkedList<IntValue> = LinkedList::new();
-        let mut math_statements = math_statement.0.iter().enumerate();
-
-        while let Some((i, value)) = math_statements.next() {
-            match &value.value {
-                TypeValues::I32(value) => {
-                    let int_type = self.context.i32_type();
-                    let int_value = int_type.const_int(*value as u64, false);
-                    num_stack.push_back(int_value);
-                }
-                TypeValues::I32Neg(neg) => {
-                    operator_stack.push_back(&Operator::Min);
-                    let int_type = self.context.i32_type();
-                    let int_value = int_type.const_int(neg.abs() as u64, false);
-                    num_stack.push_back(int_value);
+    fn gen_math_value(&self, math_statement: &'ctx Math) -> CompileResult<IntValue<'ctx>> {
+        Ok(self.gen_signed_math(math_statement)?.0)
+    }
+
+    fn gen_signed_math(&self, math_statement: &'ctx Math) -> CompileResult<(IntValue<'ctx>, IntSign)> {
+        match math_statement {
+            Math::Value(value) => self.gen_signed_operand(value),
+            Math::Unary(op, operand) => {
+                let (operand, sign) = self.gen_signed_math(operand)?;
+                match op {
+                    Operator::Min => Ok((self.builder.build_int_neg(operand, "neg_op"), sign)),
+                    Operator::Tilde => Ok((self.builder.build_not(operand, "not_op"), sign)),
+                    op => Err(format!("{op:?} is not a unary math operator").into()),
                 }
-                TypeValues::Math(math) => {
-                    let value = self.gen_math_value(math, current_block)?;
-                    num_stack.push_back(value);
+            }
+            Math::Binary(lhs, op, rhs) => {
+                let lhs = self.gen_signed_math(lhs)?;
+                let rhs = self.gen_signed_math(rhs)?;
+                self.gen_int_operation(op, lhs, rhs, "math_op")
+            }
+        }
+    }
+
+    fn gen_math_operand(&self, value: &'ctx Value) -> CompileResult<IntValue<'ctx>> {
+        match &value.value {
+            TypeValues::I32(value) => {
+                let int_type = self.context.i32_type();
+                Ok(int_type.const_int(*value as u64, false))
+            }
+            TypeValues::I32Neg(neg) => {
+                let int_type = self.context.i32_type();
+                Ok(int_type.const_int(*neg as u64, true))
+            }
+            TypeValues::I8(value) => {
+                let int_type = self.context.i8_type();
+                Ok(int_type.const_int(*value as u64, true))
+            }
+            TypeValues::U8(value) => {
+                let int_type = self.context.i8_type();
+                Ok(int_type.const_int(*value as u64, false))
+            }
+            TypeValues::Math(math) => self.gen_math_value(math),
+            TypeValues::Identifier(ident) => {
+                let ident = self.get_ident(ident)?;
+                if ident.is_int_value() {
+                    return Ok(ident.into_int_value());
                 }
-                TypeValues::Identifier(ident) => {
-                    let ident = self.get_ident(&ident, current_block)?;
-                    if ident.is_int_value() {
-                        let int_value = ident.into_int_value();
-                        num_stack.push_back(int_value);
-                        continue;
-                    }
 
-                    if ident.is_pointer_value() {
-                        let load = self
-                            .builder
-                            .build_load(ident.into_pointer_value(), &i.to_string());
+                if ident.is_pointer_value() {
+                    let load = self
+                        .builder
+                        .build_load(ident.into_pointer_value(), "load_math");
 
-                        if load.is_int_value() {
-                            num_stack.push_back(load.into_int_value());
-                            continue;
-                        }
+                    if load.is_int_value() {
+                        return Ok(load.into_int_value());
                     }
-
-                    return Err("Expected int value for Identifier".into());
                 }
-                TypeValues::FunctionCall(function_call, arguments) => {
-                    let gen_call = self.gen_func_call(
-                        function_call,
-                        arguments,
-                        Some(&i.to_string()),
-                        current_block,
-                    )?;
-                    let type_value = gen_call.as_any_value_enum();
-
-                    if type_value.is_int_value() {
-                        let value = type_value.into_int_value();
-                        num_stack.push_back(value);
-                        continue;
-                    }
 
-                    return Err("Expected int value for Function call".into());
+                Err("Expected int value for Identifier".into())
+            }
+            TypeValues::FieldAccess(ident, fields) => match self.gen_field_value(ident, fields)? {
+                BasicValueEnum::IntValue(int_value) => Ok(int_value),
+                _ => Err(format!("Expected the field {ident:?} to be a integer").into()),
+            },
+            TypeValues::FunctionCall(function_call, arguments) => {
+                let gen_call = self.gen_func_call(function_call, arguments, Some("math_call"))?;
+                let type_value = gen_call.as_any_value_enum();
+
+                if type_value.is_int_value() {
+                    return Ok(type_value.into_int_value());
                 }
-                TypeValues::Operator(op) => match op {
-                    Operator::Times => {
-                        let Some(lhs) = num_stack.pop_back() else {
-                           return Err("Couldn't execute the times operator considering there is no number on the left hand side of it...".into());
-                       };
-
-                        let Some((index, value)) = math_statements.next() else {
-                           return Err("Couldn't execute the times operator considering there is no number on the right hand side of it...".into());
-                       };
-
-                        if let TypeValues::I32(num) = value.value {
-                            let int_type = self.context.i32_type();
-                            let rhs = int_type.const_int(num as u64, false);
-
-                            let mul = self.builder.build_int_mul(lhs, rhs, &index.to_string());
-                            num_stack.push_back(mul);
-
-                            continue;
-                        };
-
-                        if let TypeValues::Math(math) = &value.value {
-                            let rhs = self.gen_math_value(&math, current_block)?;
-                            let mul = self.builder.build_int_mul(lhs, rhs, &index.to_string());
-                            num_stack.push_back(mul);
-
-                            continue;
-                        }
-
-                        if let TypeValues::FunctionCall(function_call, arguments) = &value.value {
-                            let gen_call = self.gen_func_call(
-                                function_call,
-                                arguments,
-                                Some(&i.to_string()),
-                                current_block,
-                            )?;
-                            let type_value = gen_call.as_any_value_enum();
-
-                            if type_value.is_int_value() {
-                                let rhs = type_value.into_int_value();
-                                let mul = self.builder.build_int_mul(lhs, rhs, &index.to_string());
-                                num_stack.push_back(mul);
-
-                                continue;
-                            }
-                        }
-
-                        if let TypeValues::Identifier(ident) = &value.value {
-                            let get_ident = self.get_ident(ident, current_block)?;
-
-                            if get_ident.is_int_value() {
-                                let rhs = get_ident.into_int_value();
-                                let mul = self.builder.build_int_mul(lhs, rhs, &index.to_string());
-                                num_stack.push_back(mul);
-
-                                continue;
-                            }
-
-                            if get_ident.is_pointer_value() {
-                                let load = self
-                                    .builder
-                                    .build_load(get_ident.into_pointer_value(), &i.to_string());
-                                if load.is_int_value() {
-                                    let rhs = load.into_int_value();
-                                    let mul =
-                                        self.builder.build_int_mul(lhs, rhs, &index.to_string());
-                                    num_stack.push_back(mul);
-
-                                    continue;
-                                }
-
-                                return Err("expected a int value from Identifier".into());
-                            }
-                        }
-
-                        return Err("Couldn't execute the times operator considering there is no number on the right hand side of it...".into());
-                    }
-                    Operator::Slash => {
-                        let Some(lhs) = num_stack.pop_back() else {
-                           return Err("Couldn't execute the times operator considering there is no number on the left hand side of it...".into());
-                       };
 
-                        let Some((index, value)) = math_statements.next() else {
-                           return Err("Couldn't execute the times operator considering there is no number on the right hand side of it...".into());
-                       };
-
-                        if let TypeValues::I32(num) = value.value {
-                            let int_type = self.context.i32_type();
-                            let rhs = int_type.const_int(num as u64, false);
-
-                            let div =
-                                self.builder
-                                    .build_int_signed_div(lhs, rhs, &index.to_string());
-                            num_stack.push_back(div);
-
-                            continue;
-                        };
-
-                        if let TypeValues::Math(math) = &value.value {
-                            let rhs = self.gen_math_value(&math, current_block)?;
-
-                            let div =
-                                self.builder
-                                    .build_int_signed_div(lhs, rhs, &index.to_string());
-                            num_stack.push_back(div);
-
-                            continue;
-                        }
-
-                        return Err("Couldn't execute the times operator considering there is no number on the right hand side of it...".into());
-                    }
-                    Operator::Plus => {
-                        operator_stack.push_back(op);
-                    }
-                    Operator::Min => {
-                        operator_stack.push_back(op);
-                    }
-                    _ => unreachable!(
-                        "to invalid statements, this error should get caught by the parser"
-                    ),
-                },
-                _ => unreachable!(
-                    "to invalid statements, this error should get caught by the parser"
-                ),
+                Err("Expected int value for Function call".into())
+            }
+            value => {
+                Err(format!("Expected a integer in the math statement but got {value:?}").into())
             }
         }
+    }
 
-        let mut operator_iter = operator_stack.iter().enumerate();
-        while let Some((index, operator)) = operator_iter.next() {
-            match operator {
-                Operator::Plus => {
-                    let Some(lhs) = num_stack.pop_front() else {
-                        return Err("No number on the left hand side of plus operator".into());
-                    };
-
-                    let Some(rhs) = num_stack.pop_front() else {
-                        return Err("No number on the right hand side of plus operator".into());
-                    };
-
-                    let value = self.builder.build_int_add(lhs, rhs, &index.to_string());
-                    num_stack.push_front(value);
-                }
-                Operator::Min => {
-                    let Some(lhs) = num_stack.pop_front() else {
-                        return Err("No number on the left hand side of plus operator".into());
-                    };
-
-                    let Some(rhs) = num_stack.pop_front() else {
-                        return Err("No number on the right hand side of plus operator".into());
-                    };
+    fn gen_signed_operand(&self, value: &'ctx Value) -> CompileResult<(IntValue<'ctx>, IntSign)> {
+        let sign = match &value.value {
+            TypeValues::I32(_) | TypeValues::I32Neg(_) => IntSign::Literal,
+            TypeValues::U8(_) => IntSign::Unsigned,
+            TypeValues::Math(math) => return self.gen_signed_math(math),
+            TypeValues::Identifier(ident) => self.get_ident_sign(ident),
+            TypeValues::FieldAccess(ident, fields) => IntSign::of(self.get_field_type(ident, fields)?),
+            TypeValues::FunctionCall(function_call, _) => self.get_call_sign(function_call),
+            _ => IntSign::Signed,
+        };
+        Ok((self.gen_math_operand(value)?, sign))
+    }
 
-                    let value = self.builder.build_int_sub(lhs, rhs, &index.to_string());
-                    num_stack.push_front(value);
-                }
-                _ => unreachable!("invalid operator on operator stack"),
-            }
-        }
+    fn gen_int_operation(
+        &self,
+        op: &Operator,
+        lhs: (IntValue<'ctx>, IntSign),
+        rhs: (IntValue<'ctx>, IntSign),
+        name: &str,
+    ) -> CompileResult<(IntValue<'ctx>, IntSign)> {
+        let (lhs, rhs, sign) = self.gen_int_operands(lhs, rhs);
+        let value = match op {
+            Operator::Plus => self.builder.build_int_add(lhs, rhs, name),
+            Operator::Min => self.builder.build_int_sub(lhs, rhs, name),
+            Operator::Times => self.builder.build_int_mul(lhs, rhs, name),
+            Operator::Slash if sign.is_unsigned() => self.builder.build_int_unsigned_div(lhs, rhs, name),
+            Operator::Slash => self.builder.build_int_signed_div(lhs, rhs, name),
+            Operator::Percent if sign.is_unsigned() => self.builder.build_int_unsigned_rem(lhs, rhs, name),
+            Operator::Percent => self.builder.build_int_signed_rem(lhs, rhs, name),
+            Operator::ShiftLeft => self.builder.build_left_shift(lhs, rhs, name),
+            // The sign only gets extended for signed integers
+            Operator::ShiftRight => self.builder.build_right_shift(lhs, rhs, !sign.is_unsigned(), name),
+            Operator::And => self.builder.build_and(lhs, rhs, name),
+            Operator::Xor => self.builder.build_xor(lhs, rhs, name),
+            Operator::Or => self.builder.build_or(lhs, rhs, name),
+            op => return Err(format!("{op:?} is not a integer math operator").into()),
+        };
+        Ok((value, sign))
+    }
 
-        let Some(pop_last_value) = num_stack.pop_front() else {
-            return Err("no math value on stack...".into())
+    fn gen_int_operands(
+        &self,
+        (lhs, lhs_sign): (IntValue<'ctx>, IntSign),
+        (rhs, rhs_sign): (IntValue<'ctx>, IntSign),
+    ) -> (IntValue<'ctx>, IntValue<'ctx>, IntSign) {
+        let lhs_width = lhs.get_type().get_bit_width();
+        let rhs_width = rhs.get_type().get_bit_width();
+        let (int_type, sign) = match (lhs_sign, rhs_sign) {
+            // Literals are i32 by default, they take the type of the other side, `byte + 1`
+            (_, IntSign::Literal) => (lhs.get_type(), lhs_sign),
+            (IntSign::Literal, _) => (rhs.get_type(), rhs_sign),
+            _ if lhs_width > rhs_width => (lhs.get_type(), lhs_sign),
+            _ if rhs_width > lhs_width => (rhs.get_type(), rhs_sign),
+            _ if lhs_sign.is_unsigned() || rhs_sign.is_unsigned() => (lhs.get_type(), IntSign::Unsigned),
+            _ => (lhs.get_type(), IntSign::Signed),
         };
+        (self.gen_int_resize(lhs, int_type, lhs_sign), self.gen_int_resize(rhs, int_type, rhs_sign), sign)
+    }
 
-        Ok(pop_last_value)
+    fn gen_int_resize(&self, value: IntValue<'ctx>, int_type: IntType<'ctx>, sign: IntSign) -> IntValue<'ctx> {
+        let width = value.get_type().get_bit_width();
+        let new_width = int_type.get_bit_width();
+        if new_width < width {
+            self.builder.build_int_truncate(value, int_type, "int_truncate")
+        } else if new_width == width {
+            value
+        } else if sign.is_unsigned() {
+            self.builder.build_int_z_extend(value, int_type, "int_zext")
+        } else {
+            self.builder.build_int_s_extend(value, int_type, "int_sext")
+        }
     }
 }
@@ -3,6 +3,11 @@
 pub mod zonc;
 mod math_codegen;
 mod lep_codegen;
+mod loop_codegen;
+mod struct_codegen;
+mod enum_codegen;
+mod match_codegen;
+mod global_codegen;
 
 use inkwell::values::{PointerValue, BasicValueEnum};
 use crate::parser_v2::ast::Assignment;
@@ -10,45 +15,195 @@ use crate::zon_parser::lexer::Operator;
 use inkwell::basic_block::BasicBlock;
 use inkwell::context::Context;
 use inkwell::module::Module;
-use inkwell::types::{AnyTypeEnum, ArrayType, BasicMetadataTypeEnum, BasicType};
+use inkwell::types::{AnyTypeEnum, ArrayType, BasicMetadataTypeEnum, BasicType, BasicTypeEnum, StructType};
 use inkwell::values::{
     AnyValue, AnyValueEnum, ArrayValue, BasicMetadataValueEnum, CallSiteValue, IntValue,
 };
 use inkwell::AddressSpace;
 use inkwell::{builder::Builder, values::FunctionValue};
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
 use std::error::Error;
+use crate::parser_v2::modules::{Program, SourceModule};
 use crate::parser_v2::ast::{
-    Ast, Function, FunctionCall, Node, NodeTypes, Paramater, Type, TypeValues, Types, Value,
-    Variable,
+    Ast, Enum, ExternFunction, Function, FunctionCall, Node, NodeTypes, Paramater, Struct, Type, TypeValues,
+    Types, Value, Variable,
 };
 
-use self::math_codegen::MathStatementCodegeneration;
-use self::zonc::GenC;
+use self::math_codegen::{IntSign, MathStatementCodegeneration};
 
 pub struct CodeGen<'ctx> {
     pub module: Module<'ctx>,
     pub builder: Builder<'ctx>,
     pub context: &'ctx Context,
     pub scope: Option<(FunctionValue<'ctx>, &'ctx Function)>,
+    /// The variables of the current function and their sign, the last scope is the innermost block
+    pub variables: RefCell<Vec<HashMap<String, (BasicMetadataValueEnum<'ctx>, IntSign)>>>,
+    /// Used to give the blocks of every if and loop a unique name
+    pub label_id: Cell<usize>,
+    /// The blocks `continue` and `break` jump to for every loop the builder is in, the last loop is
+    /// the innermost
+    pub loops: RefCell<Vec<(BasicBlock<'ctx>, BasicBlock<'ctx>)>>,
+    /// The declarations of all structs by the name of their LLVM type, used to look up the index of
+    /// a field. The name of a type starts with the prefix of its module, like the name of a function
+    pub structs: HashMap<String, &'ctx Struct>,
+    /// The declarations of all enums by the name of their LLVM type, used to look up the tag of a
+    /// variant
+    pub enums: HashMap<String, &'ctx Enum>,
+    /// The payload types of the variants of every enum by the name of the LLVM type of the enum,
+    /// in the order of the variants. A module that uses the enum can't resolve the types of its
+    /// payloads by their name
+    pub payloads: RefCell<HashMap<String, Vec<StructType<'ctx>>>>,
+    /// The top-level variables by name, a pointer to their global and their sign
+    pub globals: HashMap<String, (PointerValue<'ctx>, IntSign)>,
+    /// The declarations of the functions of all modules by the name of their LLVM function, used
+    /// to look up the type a call returns
+    pub functions: HashMap<String, &'ctx Function>,
+    /// The `extern fn` declarations by name, their calls convert the arguments to what C expects
+    pub externs: HashMap<String, &'ctx ExternFunction>,
+    /// Put in front of the names of the functions and globals of the module that is being
+    /// compiled, empty for the root module
+    pub module_prefix: String,
+    /// The modules the module that is being compiled imports by name
+    pub imports: HashMap<String, &'ctx SourceModule>,
 }
 
 pub(super) type CompileResult<T> = Result<T, Box<dyn Error>>;
 
 impl<'ctx> CodeGen<'ctx> {
     pub fn compile_ast(&mut self, ast: &'ctx Ast) -> CompileResult<()> {
+        self.declare_types(ast)?;
+        self.gen_type_bodies(ast)?;
+        self.compile_items(ast)
+    }
+
+    /// Compiles every module of the program into the one LLVM module. A module only uses its own
+    /// types and those of the modules it imports, which come before it, so the types of every
+    /// module get their body before any function is generated
+    pub fn compile_program(&mut self, program: &'ctx Program) -> CompileResult<()> {
+        for module in &program.modules {
+            self.enter_module(program, module);
+            self.declare_types(&module.ast)?;
+            self.gen_type_bodies(&module.ast)?;
+        }
+        for module in &program.modules {
+            self.enter_module(program, module);
+            self.compile_items(&module.ast)?;
+        }
+        Ok(())
+    }
+
+    /// Makes `module` the module that is being compiled, its names get its prefix and its paths
+    /// resolve through its imports
+    fn enter_module(&mut self, program: &'ctx Program, module: &SourceModule) {
+        self.module_prefix = module.prefix.clone();
+        self.imports = module
+            .imports
+            .iter()
+            .map(|(name, index)| (name.clone(), &program.modules[*index]))
+            .collect();
+    }
+
+    /// Generates the globals, extern functions and functions of a module
+    fn compile_items(&mut self, ast: &'ctx Ast) -> CompileResult<()> {
+        // Every global and extern fn is declared before the functions, so a function can use one
+        // that is declared below it
         for node in &ast.body {
             match &node.node_type {
+                NodeTypes::Variable(variable) => self.gen_global(variable, node, false)?,
+                NodeTypes::Const(constant) => self.gen_global(constant, node, true)?,
+                NodeTypes::ExternFunction(extern_fn) => self.gen_extern(extern_fn, node)?,
                 NodeTypes::Function(func) => {
+                    self.functions.insert(self.mangle(&func.ident.name), func);
+                }
+                _ => {}
+            }
+        }
+        for node in &ast.body {
+            match &node.node_type {
+                NodeTypes::Function(func) => {
+                    if self.externs.contains_key(&self.mangle(&func.ident.name)) {
+                        return Err(format!("The function {} on line {} has the name of a extern fn", func.ident.name, node.line).into());
+                    }
                     let function = self.gen_func(func)?;
                     self.scope = Some((function, func));
                     let _block = self.gen_block(function, &func.body, Some("entry"))?;
+                    self.gen_implicit_return(function)?;
+                }
+                NodeTypes::Struct(_)
+                | NodeTypes::Enum(_)
+                | NodeTypes::Variable(_)
+                | NodeTypes::Const(_)
+                | NodeTypes::ExternFunction(_)
+                | NodeTypes::Import(_) => {}
+                node_type => {
+                    let kind = match node_type {
+                        NodeTypes::FunctionCall(_) => "A function call",
+                        NodeTypes::Assignment(_) => "A assignment",
+                        NodeTypes::LogicalStatement(_) => "A if statement",
+                        NodeTypes::Match(_) => "A match",
+                        NodeTypes::While(_) | NodeTypes::For(_) => "A loop",
+                        NodeTypes::Return => "A return",
+                        _ => "This statement",
+                    };
+                    return Err(format!("{kind} can't be declared at the top level, line {}", node.line).into());
                 }
-                _ => todo!("compile this node"),
             }
         }
         Ok(())
     }
 
+    /// Declares every struct and enum of a module as a named LLVM struct before any of them gets a
+    /// body, so the order of the declarations doesn't matter. The LLVM name has the prefix of the
+    /// module, so two modules can declare a type with the same name
+    fn declare_types(&mut self, ast: &'ctx Ast) -> CompileResult<()> {
+        for node in &ast.body {
+            let (ident, is_new) = match &node.node_type {
+                NodeTypes::Struct(declaration) => (&declaration.ident, self.structs.insert(self.mangle(&declaration.ident.name), declaration).is_none()),
+                NodeTypes::Enum(declaration) => (&declaration.ident, self.enums.insert(self.mangle(&declaration.ident.name), declaration).is_none()),
+                _ => continue,
+            };
+            let name = self.mangle(&ident.name);
+            if !is_new || (self.structs.contains_key(&name) && self.enums.contains_key(&name)) {
+                return Err(format!("The type {} is declared more than once, found the second one on line {}", ident.name, node.line).into());
+            }
+            self.context.opaque_struct_type(&name);
+        }
+        Ok(())
+    }
+
+    /// Sets the fields of every struct and enum of a module once all of them are declared
+    fn gen_type_bodies(&self, ast: &'ctx Ast) -> CompileResult<()> {
+        for node in &ast.body {
+            if let NodeTypes::Struct(declaration) = &node.node_type {
+                self.gen_struct_body(declaration)?;
+            }
+        }
+        self.gen_enum_bodies(ast)
+    }
+
+    /// Terminates the last block of a function if the body doesn't, a void function returns at the
+    /// end of its body.
+    fn gen_implicit_return(&self, function: FunctionValue<'ctx>) -> CompileResult<()> {
+        let Some(last_block) = self.builder.get_insert_block() else {
+            return Err("expected the function to end in a block".into());
+        };
+        if last_block.get_terminator().is_some() {
+            return Ok(());
+        }
+        if function.get_type().get_return_type().is_none() {
+            self.builder.build_return(None);
+            return Ok(());
+        }
+        // Only a merge block nothing jumps to can be left without a return
+        if last_block.get_first_use().is_some() || function.get_first_basic_block() == Some(last_block) {
+            let name = function.get_name().to_string_lossy();
+            return Err(format!("The function {name} doesn't return a value at the end of its body").into());
+        }
+        self.builder.build_unreachable();
+        Ok(())
+    }
+
     fn gen_func(&self, func: &'ctx Function) -> CompileResult<FunctionValue<'ctx>> {
         if func.returns.is_array {
             let return_type = self.gen_type_array(&func.returns)?;
@@ -56,17 +211,17 @@ impl<'ctx> CodeGen<'ctx> {
             let return_type = return_type.fn_type(&params, false);
             Ok(self
                 .module
-                .add_function(&func.ident.name, return_type, None))
+                .add_function(&self.mangle(&func.ident.name), return_type, None))
         } else {
             let params = self.gen_params(&func.paramaters)?;
             if let Ok(return_type) = self.gen_type(&func.returns) {
                 let return_type = return_type.fn_type(&params, false);
                 return Ok(self
                     .module
-                    .add_function(&func.ident.name, return_type, None));
+                    .add_function(&self.mangle(&func.ident.name), return_type, None));
             }
             Ok(self.module.add_function(
-                &func.ident.name,
+                &self.mangle(&func.ident.name),
                 self.context.void_type().fn_type(&params, false),
                 None,
             ))
@@ -76,194 +231,151 @@ impl<'ctx> CodeGen<'ctx> {
     fn gen_block(
         &self,
         func: FunctionValue<'ctx>,
-        nodes: &'ctx Vec<Node>,
+        nodes: &'ctx [Node],
         block_name: Option<&str>,
     ) -> CompileResult<BasicBlock<'ctx>> {
         let block_name = block_name.unwrap_or("entry");
         let block = self.context.append_basic_block(func, block_name);
         self.builder.position_at_end(block);
+        self.in_scope(|| self.gen_nodes(nodes))?;
+        Ok(block)
+    }
 
+    /// Generates the nodes of a block at the current position of the builder
+    fn gen_nodes(&self, nodes: &'ctx [Node]) -> CompileResult<()> {
         for node in nodes {
             match &node.node_type {
                 NodeTypes::Variable(var) => {
                     if let NodeTypes::Value(value) = &node.right.as_ref().unwrap().node_type {
-                        self.gen_alloca_store(var, value, Some(block_name))?;
+                        self.gen_alloca_store(var, value)?;
                     };
 
+                    if let NodeTypes::LogicalStatement(statement) = &node.right.as_ref().unwrap().node_type {
+                        let var_type = self.gen_type(&var.var_type)?;
+                        let alloc = self.gen_entry_alloca(var_type, &var.ident.name)?;
+                        let value = self.gen_if_value(statement)?;
+                        self.builder.build_store(alloc, value);
+                        self.declare_variable(&var.ident.name, alloc.into(), IntSign::of(&var.var_type));
+                    }
+
+                    if let NodeTypes::Match(match_expr) = &node.right.as_ref().unwrap().node_type {
+                        let var_type = self.gen_basic_type(&var.var_type)?;
+                        let alloc = self.gen_entry_alloca(var_type, &var.ident.name)?;
+                        let value = self.gen_match_value(match_expr)?;
+                        self.builder.build_store(alloc, value);
+                        self.declare_variable(&var.ident.name, alloc.into(), IntSign::of(&var.var_type));
+                    }
+
                     if let NodeTypes::FunctionCall(call) = &node.right.as_ref().unwrap().node_type {
                         let Some(arguments) = call.get_args(&node.right.as_ref().unwrap()) else { 
                             panic!("the right node of the function call did not contain any arguments.")
                         };
                         // todo: type check for function call
-                        let _ = self.gen_func_call(call, arguments, Some(&var.ident.name), Some(block_name))?;
+                        let call_value = self.gen_func_call(call, arguments, Some("call"))?;
+                        let value: BasicValueEnum = match call_value.as_any_value_enum() {
+                            AnyValueEnum::IntValue(value) => value.into(),
+                            AnyValueEnum::FloatValue(value) => value.into(),
+                            AnyValueEnum::ArrayValue(value) => value.into(),
+                            AnyValueEnum::PointerValue(value) => value.into(),
+                            AnyValueEnum::StructValue(value) => value.into(),
+                            _ => return Err(format!("{} doesn't return a value that can be assigned to {}", call.calls_to.name, var.ident.name).into()),
+                        };
+                        // The variable lives on the stack so it can be reassigned, like `p.x = 1`
+                        let var_type = self.gen_basic_type(&var.var_type)?;
+                        let value = match (value, var_type) {
+                            (BasicValueEnum::IntValue(int_value), BasicTypeEnum::IntType(int_type)) => {
+                                self.gen_int_resize(int_value, int_type, self.get_call_sign(call)).into()
+                            }
+                            (value, _) => value,
+                        };
+                        let alloc = self.gen_entry_alloca(var_type, &var.ident.name)?;
+                        self.builder.build_store(alloc, value);
+                        self.declare_variable(&var.ident.name, alloc.into(), IntSign::of(&var.var_type));
                     }
                 }
+                // The parser already evaluated the const, its value is a literal
+                NodeTypes::Const(constant) => {
+                    let Some(NodeTypes::Value(value)) = node.right.as_ref().map(|right| &right.node_type) else {
+                        return Err(format!("expected the const {} to have a value", constant.ident.name).into());
+                    };
+                    self.gen_alloca_store(constant, value)?;
+                }
                 NodeTypes::FunctionCall(call) => {
                     let Some(args) = call.get_args(node) else {return Err("expected the arguments of a function to be in the left branch".into())};
-                    self.gen_func_call(call, &args, None, Some(block_name))?;
+                    self.gen_func_call(call, &args, None)?;
                 }
                 NodeTypes::LogicalStatement(statement) => {
-                    self.gen_logcal_statement(statement, Some(block_name))?;
+                    self.gen_logcal_statement(statement)?;
+                }
+                NodeTypes::Match(match_expr) => {
+                    self.gen_match(match_expr)?;
+                }
+                NodeTypes::While(while_loop) => {
+                    self.gen_while_loop(while_loop)?;
+                }
+                NodeTypes::For(for_loop) => {
+                    self.gen_for_loop(for_loop)?;
+                }
+                NodeTypes::Break => {
+                    self.gen_loop_jump(true, node.line)?;
+                }
+                NodeTypes::Continue => {
+                    self.gen_loop_jump(false, node.line)?;
                 }
                 NodeTypes::Assignment(assignment) => {
-                    self.gen_reassignment(assignment, node, Some(block_name))?;
+                    self.gen_reassignment(assignment, node)?;
                 }
                 NodeTypes::Return => {
-                    let _ = self.gen_return(node, Some(block_name));
+                    self.gen_return(node)?;
                 }
+                // The value a block ends in is only used when the block is the branch of a if expression
+                NodeTypes::Value(_) => {}
                 node_type => unimplemented!("Support for {node_type:#?} in blocks is not yet implemented. found this value on line: {}", node.line)
             }
         }
-        Ok(block)
+        Ok(())
     }
-    fn gen_reassignment(&self, assignment: &'ctx Assignment, node: &'ctx Node, block_name: Option<&str>) -> CompileResult<()> {
-        let get_ident = self.get_ident(&assignment.assigns_to.name, block_name)?;
+    fn gen_reassignment(&self, assignment: &'ctx Assignment, node: &'ctx Node) -> CompileResult<()> {
+        let get_ident = self.get_ident(&assignment.assigns_to.name)?;
+        if !get_ident.is_pointer_value() && !assignment.fields.is_empty() {
+            return Err(format!("Can't assign to a field of {}, paramaters are passed by value", assignment.assigns_to.name).into());
+        }
         if get_ident.is_pointer_value() {
             let Some(op) = assignment.get_op(node) else { return Err("expected a operator for gen_reassignment".into()) };
             let Some(value) = assignment.get_value(node) else { return Err("expected a value for gen_reassignment".into()) };
 
+            let mut ptr = get_ident.into_pointer_value();
+            let mut field_type = None;
+            if !assignment.fields.is_empty() {
+                let (field_ptr, field) = self.gen_field_pointer(ptr, &assignment.fields)?;
+                ptr = field_ptr;
+                field_type = Some(field);
+            }
+
             match op {
                 Operator::Eq => {
-                    let ptr = get_ident.into_pointer_value();  
-                    self.gen_store(value, ptr, None, block_name);
-                }
-                Operator::PlusIs => {
-                    let ptr = get_ident.into_pointer_value();  
-                    let load = self.builder.build_load(ptr, "load_val");
-                    match load {
-                        BasicValueEnum::IntValue(int_value) => {
-                            if let TypeValues::I32(value) = value.value {
-                                let int = self.context.i32_type();
-                                let add_value = int.const_int(value as u64, false);
-                                let add = self.builder.build_int_add(int_value, add_value, "add_op");
-                                self.builder.build_store(ptr, add);
-                            };
-                            if let TypeValues::Identifier(ident) = &value.value {
-                                let get_ident = self.get_ident(&ident, block_name)?;
-
-                                if get_ident.is_pointer_value() {
-                                    let load = self.builder.build_load(get_ident.into_pointer_value(), "load_other");
-                                    match load {
-                                        BasicValueEnum::IntValue(add_value) => {
-                                            let add = self.builder.build_int_add(int_value, add_value, "add_op");
-                                            self.builder.build_store(ptr, add);
-                                        }
-                                        _ => return Err("Expected a integer for TimesIs operator".into())
-                                    }
-                                } 
-                                else if get_ident.is_int_value() {
-                                    let add_value = get_ident.into_int_value(); 
-                                    let add = self.builder.build_int_add(int_value, add_value, "add_op");
-                                    self.builder.build_store(ptr, add);
-                                }
-                            }
-                            if let TypeValues::FunctionCall(call, arguments) = &value.value {
-                                let call = self.gen_func_call(call, arguments, Some("tiscall"), block_name)?;
-                                let value = call.as_any_value_enum();
-
-                                match value {
-                                    AnyValueEnum::IntValue(add_value) => {
-                                        let add = self.builder.build_int_add(int_value, add_value, "add_op");
-                                        self.builder.build_store(ptr, add);
-                                    }
-                                    _ => return Err("Expected a integer for TimesIs operator".into())
-                                }
-                            }
-                        }
-                        _ => return Err("Expected a integer for additation operator".into())
-                    }
-                }
-                Operator::TimesIs => {
-                    let ptr = get_ident.into_pointer_value();  
-                    let load = self.builder.build_load(ptr, "load_val");
-                    match load {
-                        BasicValueEnum::IntValue(int_value) => {
-                            if let TypeValues::I32(value) = value.value {
-                                let int = self.context.i32_type();
-                                let add_value = int.const_int(value as u64, false);
-                                let add = self.builder.build_int_mul(int_value, add_value, "times_op");
-                                self.builder.build_store(ptr, add);
-                            };
-                            if let TypeValues::Identifier(ident) = &value.value {
-                                let get_ident = self.get_ident(&ident, block_name)?;
-
-                                if get_ident.is_pointer_value() {
-                                    let load = self.builder.build_load(get_ident.into_pointer_value(), "load_other");
-                                    match load {
-                                        BasicValueEnum::IntValue(add_value) => {
-                                            let add = self.builder.build_int_mul(int_value, add_value, "times_op");
-                                            self.builder.build_store(ptr, add);
-                                        }
-                                        _ => return Err("Expected a integer for TimesIs operator".into())
-                                    }
-                                } 
-                                else if get_ident.is_int_value() {
-                                    let add_value = get_ident.into_int_value(); 
-                                    let add = self.builder.build_int_mul(int_value, add_value, "times_op");
-                                    self.builder.build_store(ptr, add);
-                                }
-                            }
-                            if let TypeValues::FunctionCall(call, arguments) = &value.value {
-                                let call = self.gen_func_call(call, arguments, Some("tiscall"), block_name)?;
-                                let value = call.as_any_value_enum();
-
-                                match value {
-                                    AnyValueEnum::IntValue(add_value) => {
-                                        let add = self.builder.build_int_mul(int_value, add_value, "times_op");
-                                        self.builder.build_store(ptr, add);
-                                    }
-                                    _ => return Err("Expected a integer for TimesIs operator".into())
-                                }
-                            }
+                    match field_type {
+                        Some(field_type) => {
+                            let field_value = self.gen_field_init(value, field_type)?;
+                            self.builder.build_store(ptr, field_value);
                         }
-                        _ => return Err("Expected a integer for TimesIs operator".into())
+                        None => self.gen_store(value, ptr, None)?,
                     }
                 }
-                Operator::MinusIs => {
-                    let ptr = get_ident.into_pointer_value();  
+                Operator::PlusIs | Operator::MinusIs | Operator::TimesIs | Operator::SlashIs | Operator::PercentIs => {
+                    let Some(math_op) = op.compound_operator() else { unreachable!("all compound assignment operators have a math operator") };
                     let load = self.builder.build_load(ptr, "load_val");
-                    match load {
-                        BasicValueEnum::IntValue(int_value) => {
-                            if let TypeValues::I32(value) = value.value {
-                                let int = self.context.i32_type();
-                                let add_value = int.const_int(value as u64, false);
-                                let add = self.builder.build_int_sub(int_value, add_value, "minus_op");
-                                self.builder.build_store(ptr, add);
-                            };
-                            if let TypeValues::Identifier(ident) = &value.value {
-                                let get_ident = self.get_ident(&ident, block_name)?;
-
-                                if get_ident.is_pointer_value() {
-                                    let load = self.builder.build_load(get_ident.into_pointer_value(), "load_other");
-                                    match load {
-                                        BasicValueEnum::IntValue(add_value) => {
-                                            let add = self.builder.build_int_sub(int_value, add_value, "minus_op");
-                                            self.builder.build_store(ptr, add);
-                                        }
-                                        _ => return Err("Expected a integer for TimesIs operator".into())
-                                    }
-                                } 
-                                else if get_ident.is_int_value() {
-                                    let add_value = get_ident.into_int_value(); 
-                                    let add = self.builder.build_int_sub(int_value, add_value, "minus_op");
-                                    self.builder.build_store(ptr, add);
-                                }
-                            }
-                            if let TypeValues::FunctionCall(call, arguments) = &value.value {
-                                let call = self.gen_func_call(call, arguments, Some("tiscall"), block_name)?;
-                                let value = call.as_any_value_enum();
-
-                                match value {
-                                    AnyValueEnum::IntValue(add_value) => {
-                                        let add = self.builder.build_int_sub(int_value, add_value, "minus_op");
-                                        self.builder.build_store(ptr, add);
-                                    }
-                                    _ => return Err("Expected a integer for TimesIs operator".into())
-                                }
-                            }
-                        }
-                        _ => return Err("Expected a integer for MinusIs operator".into())
-                    }
+                    let BasicValueEnum::IntValue(int_value) = load else {
+                        return Err(format!("Expected a integer for the {op:?} operator").into())
+                    };
+                    let sign = match field_type {
+                        Some(field_type) => IntSign::of(field_type),
+                        None => self.get_ident_sign(&assignment.assigns_to.name),
+                    };
+                    let rhs = self.gen_signed_operand(value)?;
+                    let (result, result_sign) = self.gen_int_operation(&math_op, (int_value, sign), rhs, "compound_op")?;
+                    let result = self.gen_int_resize(result, int_value.get_type(), result_sign);
+                    self.builder.build_store(ptr, result);
                 }
                 _ => return Err(format!("the operator {:#?} is not a valid reassignment operator.", op).into())
             }
@@ -271,8 +383,18 @@ impl<'ctx> CodeGen<'ctx> {
         Ok(())
     }
 
-    fn gen_return(&self, return_node: &'ctx Node, block_name: Option<&str>) -> CompileResult<()> {
+    fn gen_return(&self, return_node: &'ctx Node) -> CompileResult<()> {
         let Some(value_node) = &return_node.right else { panic!("Expected right node type of return node to have a value") };
+        if let NodeTypes::LogicalStatement(statement) = &value_node.node_type {
+            let value = self.gen_if_value(statement)?;
+            self.builder.build_return(Some(&value));
+            return Ok(());
+        }
+        if let NodeTypes::Match(match_expr) = &value_node.node_type {
+            let value = self.gen_match_value(match_expr)?;
+            self.builder.build_return(Some(&value));
+            return Ok(());
+        }
         if let NodeTypes::Value(value) = &value_node.node_type {
             match &value.value {
                 TypeValues::I8(num) => {
@@ -309,12 +431,12 @@ impl<'ctx> CodeGen<'ctx> {
                     return Ok(());
                 }
                 TypeValues::Math(math) => {
-                    let math = self.gen_math_value(math, block_name)?;
+                    let math = self.gen_math_value(math)?;
                     self.builder.build_return(Some(&math));
                     return Ok(())
                 }
                 TypeValues::Identifier(ident) => {
-                    let ident = self.get_ident(&ident, block_name)?;
+                    let ident = self.get_ident(&ident)?;
                     let value = ident.as_any_value_enum();
                     match value.get_type() {
                         AnyTypeEnum::IntType(_) => {
@@ -330,6 +452,9 @@ impl<'ctx> CodeGen<'ctx> {
                             let load = self.builder.build_load(ident.into_pointer_value(), "ret_load");
                             self.builder.build_return(Some(&load));
                         }
+                        AnyTypeEnum::StructType(_) => {
+                            self.builder.build_return(Some(&value.into_struct_value()));
+                        }
                         typeof_return => unimplemented!(
                             "Typeof {typeof_return} can not be used for returning values"
                         ),
@@ -341,6 +466,21 @@ impl<'ctx> CodeGen<'ctx> {
                     self.builder.build_return(Some(&i8_str));
                     return Ok(());
                 }
+                TypeValues::Struct(name, fields) => {
+                    let struct_value = self.gen_struct_value(name, fields)?;
+                    self.builder.build_return(Some(&struct_value));
+                    return Ok(());
+                }
+                TypeValues::FieldAccess(ident, fields) => {
+                    let field_value = self.gen_field_value(ident, fields)?;
+                    self.builder.build_return(Some(&field_value));
+                    return Ok(());
+                }
+                TypeValues::EnumVariant(enum_ident, variant, payload) => {
+                    let enum_value = self.gen_enum_value(enum_ident, variant, payload)?;
+                    self.builder.build_return(Some(&enum_value));
+                    return Ok(());
+                }
                 TypeValues::None => {
                     self.builder.build_return(None);
                     return Ok(());
@@ -352,46 +492,48 @@ impl<'ctx> CodeGen<'ctx> {
         }
         if let NodeTypes::FunctionCall(call) = &value_node.node_type {
             let Some(arguments) = call.get_args(&value_node) else { panic!("Expected function call node to have arguments") };
-            if let Some(func) = self.module.get_function(&call.calls_to.name) {
-                let args = self.gen_args(arguments, block_name)?;
-                let call: CallSiteValue<'ctx> = self.builder.build_call(func, &args, "return");
-                let call_type = call.as_any_value_enum();
-
-                // Todo: Add check for the function return type and the calls return type |
-                // give compile error if not equal
-                if call_type.is_int_value() {
-                    let call = call_type.into_int_value();
-                    self.builder.build_return(Some(&call));
-                    return Ok(())
-                }
-
-                if call_type.is_array_value() {
-                    let call = call_type.into_array_value();
-                    self.builder.build_return(Some(&call));
-                    return Ok(())
-                }
+            let call: CallSiteValue<'ctx> = self.gen_func_call(call, arguments, Some("return"))?;
+            let call_type = call.as_any_value_enum();
+
+            // Todo: Add check for the function return type and the calls return type |
+            // give compile error if not equal
+            if call_type.is_int_value() {
+                let call = call_type.into_int_value();
+                self.builder.build_return(Some(&call));
+                return Ok(())
+            }
 
-                if call_type.is_float_value() {
-                    let call = call_type.into_array_value();
-                    self.builder.build_return(Some(&call));
-                    return Ok(())
-                }
+            if call_type.is_array_value() {
+                let call = call_type.into_array_value();
+                self.builder.build_return(Some(&call));
+                return Ok(())
+            }
 
-                if call_type.is_pointer_value() {
-                    let call = call_type.into_array_value();
-                    self.builder.build_return(Some(&call));
-                    return Ok(());
-                }
+            if call_type.is_struct_value() {
+                let call = call_type.into_struct_value();
+                self.builder.build_return(Some(&call));
+                return Ok(())
+            }
 
-                self.builder.build_return(None);
+            if call_type.is_float_value() {
+                let call = call_type.into_float_value();
+                self.builder.build_return(Some(&call));
                 return Ok(())
             }
-            return Err(format!("Couldn't find any function named: {}", call.calls_to.name).into());
+
+            if call_type.is_pointer_value() {
+                let call = call_type.into_pointer_value();
+                self.builder.build_return(Some(&call));
+                return Ok(());
+            }
+
+            self.builder.build_return(None);
+            return Ok(())
         }
         Ok(())
     }
 
-    fn gen_array_values(&self, array_values: &'ctx Vec<TypeValues>, type_of: &Type) -> ArrayValue {
+    fn gen_array_values(&self, array_values: &'ctx Vec<TypeValues>, type_of: &Type) -> ArrayValue<'ctx> {
         let mut values: Vec<IntValue> = Vec::new();
         for value in array_values {
             match value {
@@ -436,24 +578,18 @@ impl<'ctx> CodeGen<'ctx> {
         }
     }
 
-    fn gen_alloca_store(&self, variable: &'ctx Variable, value: &'ctx Value, block_name: Option<&str>) -> CompileResult<()> {
-        if variable.var_type.is_array {
-            let arr_type = self.gen_type_array(&variable.var_type)?;
-            let alloc = self.builder.build_alloca(arr_type, &variable.ident.name);
-            self.gen_store(value, alloc, Some(&variable.var_type), block_name);
-            Ok(())
-        } else {
-            let var_type = self.gen_type(&variable.var_type)?;
-            let alloc = self.builder.build_alloca(var_type, &variable.ident.name);
-            self.gen_store(value, alloc, Some(&variable.var_type), block_name);
-            Ok(())
-        }
+    fn gen_alloca_store(&self, variable: &'ctx Variable, value: &'ctx Value) -> CompileResult<()> {
+        let var_type = self.gen_basic_type(&variable.var_type)?;
+        let alloc = self.gen_entry_alloca(var_type, &variable.ident.name)?;
+        self.gen_store(value, alloc, Some(&variable.var_type))?;
+        self.declare_variable(&variable.ident.name, alloc.into(), IntSign::of(&variable.var_type));
+        Ok(())
     }
 
     fn str_into_array(&self, str: &str) -> Vec<IntValue<'ctx>> {
+        // The string is already decoded by the lexer, so we emit its utf-8 bytes as they are
         let iter = str
-            .chars()
-            .into_iter()
+            .bytes()
             .map(|value| {
                 let i8_type = self.context.i8_type();
                 let value = i8_type.const_int(value as u64, false);
@@ -463,7 +599,7 @@ impl<'ctx> CodeGen<'ctx> {
         iter
     }
 
-    fn gen_store(&self, value: &'ctx Value, alloc_ptr: PointerValue, type_of: Option<&'ctx Type>, block_name: Option<&str>) {
+    fn gen_store(&self, value: &'ctx Value, alloc_ptr: PointerValue<'ctx>, type_of: Option<&'ctx Type>) -> CompileResult<()> {
         match &value.value {
             TypeValues::I8(num) => {
                 let i8_type = self.context.i8_type();
@@ -501,12 +637,15 @@ impl<'ctx> CodeGen<'ctx> {
                 self.builder.build_store(alloc_ptr, array);
             }
             TypeValues::Math(math) => {
-                // TODO: Fix error here, don't just expect 
-                let value = self.gen_math_value(math, block_name).expect("couldn't parse math statement");
+                let (value, sign) = self.gen_signed_math(math)?;
+                let value = match alloc_ptr.get_type().get_element_type() {
+                    AnyTypeEnum::IntType(int_type) => self.gen_int_resize(value, int_type, sign),
+                    _ => value,
+                };
                 self.builder.build_store(alloc_ptr, value);
             }
             TypeValues::Identifier(ident) => {
-                let Ok(ident) = self.get_ident(ident, block_name) else {
+                let Ok(ident) = self.get_ident(ident) else {
                     panic!("temp: no such ident: {ident}");
                 };
                 if ident.is_pointer_value() {
@@ -514,20 +653,35 @@ impl<'ctx> CodeGen<'ctx> {
                     self.builder.build_store(alloc_ptr, load);
                 }
             }
+            TypeValues::Struct(name, fields) => {
+                let struct_value = self.gen_struct_value(name, fields)?;
+                self.builder.build_store(alloc_ptr, struct_value);
+            }
+            TypeValues::FieldAccess(ident, fields) => {
+                let field_value = self.gen_field_value(ident, fields)?;
+                self.builder.build_store(alloc_ptr, field_value);
+            }
+            TypeValues::EnumVariant(enum_ident, variant, payload) => {
+                let enum_value = self.gen_enum_value(enum_ident, variant, payload)?;
+                self.builder.build_store(alloc_ptr, enum_value);
+            }
             typeofval => unimplemented!("typeof {typeofval:#?} is not supported as of right now"),
         }
+        Ok(())
     }
 
-    fn gen_type(&self, gen_type: &Type) -> CompileResult<impl BasicType<'ctx>> {
+    fn gen_type(&self, gen_type: &Type) -> CompileResult<BasicTypeEnum<'ctx>> {
         match &gen_type.r#type {
             Types::I8 | Types::U8 | Types::Char | Types::String => {
                 let i8_type = self.context.i8_type();
-                return Ok(i8_type);
+                return Ok(i8_type.into());
             }
             Types::I32 => {
                 let i32_type = self.context.i32_type();
-                return Ok(i32_type);
+                return Ok(i32_type.into());
             }
+            Types::F32 => Ok(self.context.f32_type().into()),
+            Types::Named(name) => Ok(self.gen_named_type(name)?.into()),
             expected_type => Err(format!(
                 "expected type of {expected_type:#?} cannot be done by gen_type"
             )
@@ -535,6 +689,14 @@ impl<'ctx> CodeGen<'ctx> {
         }
     }
 
+    /// Generates the type of a variable or field, which can be a array
+    fn gen_basic_type(&self, gen_type: &'ctx Type) -> CompileResult<BasicTypeEnum<'ctx>> {
+        if gen_type.is_array {
+            return Ok(self.gen_type_array(gen_type)?.into());
+        }
+        self.gen_type(gen_type)
+    }
+
     fn gen_type_array(&self, gen_type: &'ctx Type) -> CompileResult<ArrayType<'ctx>> {
         if !gen_type.is_array {
             return Err("expected type of string array to be and array".into());
@@ -552,45 +714,64 @@ impl<'ctx> CodeGen<'ctx> {
                 let f32_array = self.context.f32_type().array_type(gen_type.size);
                 Ok(f32_array)
             }
+            Types::Named(ref name) => {
+                let struct_array = self.gen_named_type(name)?.array_type(gen_type.size);
+                Ok(struct_array)
+            }
             _ => Err("Expected array type".into()),
         }
     }
 
+    /// The types of the paramaters of a function, a pointer like `i32^` is a pointer to its type
+    /// and a string is passed as a pointer to its chars
     fn gen_params(
         &self,
         params: &Vec<Paramater>,
     ) -> CompileResult<Vec<BasicMetadataTypeEnum<'ctx>>> {
         let mut meta = Vec::new();
         for param in params {
-            match param.r#type.r#type {
-                Types::I8 | Types::Char | Types::U8 | Types::String if param.r#type.is_array => {
-                    meta.push(self.context.i8_type().array_type(param.r#type.size).into())
+            let mut param_type = match &param.r#type.r#type {
+                Types::I8 | Types::U8 | Types::Char | Types::I32 | Types::F32 | Types::String | Types::Named(_) => {
+                    self.gen_type(&param.r#type)?
                 }
-                Types::I8 | Types::Char | Types::U8 if param.r#type.is_pointer => {
-                    meta.push(self.context.i8_type().ptr_type(Default::default()).into())
+                unsupported => {
+                    return Err(format!("The paramater {} has the type {unsupported:?}, which can't be passed to a function", param.ident.name).into())
                 }
-                Types::I8 | Types::Char | Types::U8 => meta.push(self.context.i8_type().into()),
-                Types::I32 => meta.push(self.context.i32_type().into()),
-                Types::F32 => meta.push(self.context.f32_type().into()),
-                Types::String => meta.push(
-                    self.context
-                        .i8_type()
-                        .ptr_type(AddressSpace::default())
-                        .into(),
-                ),
-                _ => todo!("Found unsuported type"),
+            };
+            if param.r#type.is_pointer || (param.r#type.r#type == Types::String && !param.r#type.is_array) {
+                param_type = param_type.ptr_type(AddressSpace::default()).into();
+            }
+            if param.r#type.is_array {
+                param_type = param_type.array_type(param.r#type.size).into();
             }
+            meta.push(param_type.into());
         }
         Ok(meta)
     }
 
+    /// Generates the arguments of a call and their sign, a extern fn promotes a small integer by
+    /// its sign like C does
     fn gen_args(
         &self,
         arguments: &'ctx Vec<Value>,
-        block_name: Option<&str>
-    ) -> CompileResult<Vec<BasicMetadataValueEnum<'ctx>>> {
+    ) -> CompileResult<(Vec<BasicMetadataValueEnum<'ctx>>, Vec<IntSign>)> {
         let mut args = Vec::new();
+        let mut signs = Vec::new();
         for arg in arguments {
+            let sign = match &arg.value {
+                TypeValues::U8(_) | TypeValues::Char(_) => IntSign::Unsigned,
+                TypeValues::Identifier(ident) => self.get_ident_sign(ident),
+                TypeValues::FieldAccess(ident, fields) => IntSign::of(self.get_field_type(ident, fields)?),
+                TypeValues::FunctionCall(function_call, _) => self.get_call_sign(function_call),
+                TypeValues::Math(math) => {
+                    let (math_value, sign) = self.gen_signed_math(math)?;
+                    args.push(math_value.into());
+                    signs.push(sign);
+                    continue;
+                }
+                _ => IntSign::Signed,
+            };
+            signs.push(sign);
             match &arg.value {
                 TypeValues::I8(i8_value) => {
                     let i8_type = self.context.i8_type();
@@ -628,7 +809,7 @@ impl<'ctx> CodeGen<'ctx> {
                     args.push(value.into());
                 }
                 TypeValues::FunctionCall(calls, arguments) => {
-                    let call = self.gen_func_call(calls, arguments, None, block_name)?;
+                    let call = self.gen_func_call(calls, arguments, None)?;
                     let call_type = call.as_any_value_enum();
 
                     // Todo: Add check for the function return type and the calls return type |
@@ -647,19 +828,30 @@ impl<'ctx> CodeGen<'ctx> {
                         let call = call_type.into_array_value();
                         args.push(call.into());
                     }
+
+                    if call_type.is_struct_value() {
+                        let call = call_type.into_struct_value();
+                        args.push(call.into());
+                    }
                 }
-                TypeValues::Math(math) => {
-                    let math_value = self.gen_math_value(math, block_name)?;
-                    args.push(math_value.into());
+                TypeValues::Struct(name, fields) => {
+                    let struct_value = self.gen_struct_value(name, fields)?;
+                    args.push(struct_value.into());
+                }
+                TypeValues::FieldAccess(ident, fields) => {
+                    let field_value = self.gen_field_value(ident, fields)?;
+                    args.push(field_value.into());
+                }
+                TypeValues::EnumVariant(enum_ident, variant, payload) => {
+                    let enum_value = self.gen_enum_value(enum_ident, variant, payload)?;
+                    args.push(enum_value.into());
                 }
                 TypeValues::Identifier(ident) if arg.is_ptr => {
-                    // TODO: FIX THIS DON'T CONTINUE RETURN ERR, THIS IS JUST FOR NOW, OKAY
-                    let Ok(value) = self.get_ident(&ident, block_name) else { continue };
+                    let value = self.get_ident(ident)?;
                     args.push(value.into_pointer_value().into());
                 }
                 TypeValues::Identifier(ident) => {
-                    // TODO: FIX THIS DON'T CONTINUE RETURN ERR, THIS IS JUST FOR NOW, OKAY
-                    let Ok(value) = self.get_ident(&ident, block_name) else { continue };
+                    let value = self.get_ident(ident)?;
                     if value.is_pointer_value() {
                         let load_value = self
                             .builder
@@ -674,7 +866,7 @@ impl<'ctx> CodeGen<'ctx> {
                 value => unimplemented!("support for type of {value:#?}"),
             }
         }
-        Ok(args)
+        Ok((args, signs))
     }
 
     fn gen_func_call(
@@ -682,41 +874,59 @@ impl<'ctx> CodeGen<'ctx> {
         function_call: &'ctx FunctionCall,
         arguments: &'ctx Vec<Value>,
         call_name: Option<&str>,
-        block_name: Option<&str>
     ) -> CompileResult<CallSiteValue<'ctx>> {
-        if let Some(called_func) = self.module.get_function(&function_call.calls_to.name) {
-            let args = &self.gen_args(arguments, block_name)?;
-            let call_name = call_name.unwrap_or("call");
-            let value = self.builder.build_call(called_func, args, call_name);
-            return Ok(value);
+        let (called_func, extern_fn) = self.resolve_call(function_call)?;
+        let (mut args, signs) = self.gen_args(arguments)?;
+        if let Some(extern_fn) = extern_fn {
+            args = self.gen_extern_args(extern_fn, called_func, args, &signs)?;
+        }
+        let call_name = call_name.unwrap_or("call");
+        let value = self.builder.build_call(called_func, &args, call_name);
+        Ok(value)
+    }
+
+    /// Finds the function a call calls, a function of the module that is being compiled, one of
+    /// its `extern fn` declarations or a function of a imported module, `math::sqrt(2)`
+    fn resolve_call(&self, function_call: &FunctionCall) -> CompileResult<(FunctionValue<'ctx>, Option<&'ctx ExternFunction>)> {
+        let name = &function_call.calls_to.name;
+        if let Some(module) = &function_call.module {
+            let Some(imported) = self.imports.get(&module.name) else {
+                return Err(format!("There is no module called {} imported", module.name).into());
+            };
+            return match self.module.get_function(&format!("{}{name}", imported.prefix)) {
+                Some(function) => Ok((function, None)),
+                None => Err(format!("Couldn't find any function named: {}::{name}", module.name).into()),
+            };
         }
 
-        if let Some(c_func) = self.gen_c_function(&function_call.calls_to.name) {
-            let args = &self.gen_args(arguments, block_name)?;
-            let call_name = call_name.unwrap_or("call");
-            let value = self.builder.build_call(c_func, args, call_name);
-            return Ok(value);
+        if let Some(extern_fn) = self.externs.get(&self.mangle(name)).copied() {
+            if let Some(function) = self.module.get_function(name) {
+                return Ok((function, Some(extern_fn)));
+            }
+        }
+        if let Some(function) = self.module.get_function(&self.mangle(name)) {
+            return Ok((function, None));
         }
+        Err(format!("Couldnt' find any function named: {name}, a C function has to be declared with `extern fn {name}(..)` first").into())
+    }
 
-        Err(format!(
-            "Couldnt' find any function named: {}",
-            function_call.calls_to.name
-        )
-        .into())
+    /// The name of a function or global of the module that is being compiled in the LLVM module
+    fn mangle(&self, name: &str) -> String {
+        format!("{}{name}", self.module_prefix)
     }
 }
 
 impl<'ctx> CodeGen<'ctx> {
-    fn get_ident(&self, name: &str, current_block: Option<&str>) -> CompileResult<BasicMetadataValueEnum<'ctx>> {
+    /// Looks up a variable from the innermost scope outwards, then the paramaters of the function
+    /// and the globals last
+    fn get_ident(&self, name: &str) -> CompileResult<BasicMetadataValueEnum<'ctx>> {
         let Some((function, function_node)) = self.scope else {
             return Err(format!("Tried to get value with the name {}, but the current scope is none", name).into());
         };
 
-        let blocks = function.get_basic_blocks();
-        let Some(current_block) = blocks.iter().find(|x| x.get_name().to_str() == Ok(current_block.unwrap_or("entry"))) else {
-            return Err(format!("Tried to get value with the name {}, but the current scope is none", name).into());
-        };
-
+        if let Some((variable, _)) = self.variables.borrow().iter().rev().find_map(|scope| scope.get(name)) {
+            return Ok(*variable);
+        }
 
         if let Some(param) = function_node.get_param_index_with_name(&name) {
             let Some(param) = function.get_nth_param(param as u32) else {
@@ -725,15 +935,81 @@ impl<'ctx> CodeGen<'ctx> {
             return Ok(param.into());
         };
 
-        let Some(ident) = current_block.get_instruction_with_name(&name) else {
-            return Err(format!("There is no variable called {}", name).into());
+        if let Some((global, _)) = self.globals.get(&self.mangle(name)) {
+            return Ok((*global).into());
+        }
+
+        Err(format!("There is no variable called {}", name).into())
+    }
+
+    /// The sign of a variable, looked up in the same order as [`CodeGen::get_ident`]
+    fn get_ident_sign(&self, name: &str) -> IntSign {
+        if let Some((_, sign)) = self.variables.borrow().iter().rev().find_map(|scope| scope.get(name)) {
+            return *sign;
+        }
+        if let Some((_, function_node)) = self.scope {
+            if let Some(param) = function_node.get_param_index_with_name(name) {
+                return IntSign::of(&function_node.paramaters[param].r#type);
+            }
+        }
+        match self.globals.get(&self.mangle(name)) {
+            Some((_, sign)) => *sign,
+            None => IntSign::Signed,
+        }
+    }
+
+    /// The sign of the value a function returns
+    fn get_call_sign(&self, function_call: &FunctionCall) -> IntSign {
+        let name = &function_call.calls_to.name;
+        let function_name = match function_call.module.as_ref().and_then(|module| self.imports.get(&module.name)) {
+            Some(imported) => format!("{}{name}", imported.prefix),
+            None => self.mangle(name),
         };
+        if let Some(extern_fn) = self.externs.get(&function_name) {
+            return IntSign::of(&extern_fn.returns);
+        }
+        match self.functions.get(&function_name) {
+            Some(function) => IntSign::of(&function.returns),
+            None => IntSign::Signed,
+        }
+    }
+
+    /// Adds a variable to the innermost scope, a variable with the same name gets shadowed
+    fn declare_variable(&self, name: &str, value: BasicMetadataValueEnum<'ctx>, sign: IntSign) {
+        if let Some(scope) = self.variables.borrow_mut().last_mut() {
+            scope.insert(name.to_string(), (value, sign));
+        }
+    }
+
+    /// Runs `gen` in a new scope, the variables declared in the scope are gone after
+    fn in_scope<T>(&self, gen: impl FnOnce() -> CompileResult<T>) -> CompileResult<T> {
+        self.variables.borrow_mut().push(HashMap::new());
+        let result = gen();
+        self.variables.borrow_mut().pop();
+        result
+    }
 
-        match ident.as_any_value_enum() {
-            AnyValueEnum::IntValue(value) => Ok(value.into()),
-            AnyValueEnum::ArrayValue(array) => Ok(array.into()),
-            AnyValueEnum::PointerValue(value) => Ok(value.into()),
-            _ => unimplemented!("Found unimplemented value, this should not be possible."),
+    /// Allocates stack space at the start of the function, unlike a alloca in the current block
+    /// the space doesn't grow when the block runs more then once, like the body of a loop
+    fn gen_entry_alloca(&self, alloc_type: BasicTypeEnum<'ctx>, name: &str) -> CompileResult<PointerValue<'ctx>> {
+        let Some((function, _)) = self.scope else {
+            return Err("expected to be in a function to allocate a value".into());
+        };
+        let Some(entry) = function.get_first_basic_block() else {
+            return Err("expected the function to have a entry block".into());
+        };
+        let entry_builder = self.context.create_builder();
+        match entry.get_first_instruction() {
+            Some(first) => entry_builder.position_before(&first),
+            None => entry_builder.position_at_end(entry),
         }
+        Ok(entry_builder.build_alloca(alloc_type, name))
+    }
+
+    /// Returns a new id for the blocks of a if or a loop, so nested ones get unique block names
+    fn next_label_id(&self) -> usize {
+        let id = self.label_id.get();
+        self.label_id.set(id + 1);
+        id
     }
 }
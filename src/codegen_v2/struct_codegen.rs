@@ -0,0 +1,185 @@
+use inkwell::{
+    types::{AnyType, AnyTypeEnum, BasicTypeEnum, StructType},
+    values::{BasicMetadataValueEnum, BasicValueEnum, PointerValue, StructValue},
+};
+
+use super::{math_codegen::MathStatementCodegeneration, CodeGen, CompileResult};
+use crate::parser_v2::ast::{Ident, Paramater, Struct, Type, TypeValues, Types, Value};
+
+impl<'ctx> CodeGen<'ctx> {
+    /// Sets the fields of a struct that was declared as a opaque struct
+    pub(super) fn gen_struct_body(&self, declaration: &'ctx Struct) -> CompileResult<()> {
+        let mut field_types = Vec::new();
+        for field in &declaration.fields {
+            if field.r#type.r#type == Types::Named(declaration.ident.name.clone()) && !field.r#type.is_pointer {
+                return Err(format!("The struct {} can't contain itself, found in field {}", declaration.ident.name, field.ident.name).into());
+            }
+            field_types.push(self.gen_basic_type(&field.r#type)?);
+        }
+        let struct_type = self.gen_named_type(&declaration.ident.name)?;
+        struct_type.set_body(&field_types, false);
+        Ok(())
+    }
+
+    /// Returns the LLVM type of a struct or enum of the module that is being compiled, or of a
+    /// imported module, `math::Point`
+    pub(super) fn gen_named_type(&self, name: &str) -> CompileResult<StructType<'ctx>> {
+        match self.module.get_struct_type(&self.type_name(name)) {
+            Some(struct_type) => Ok(struct_type),
+            None => Err(format!("There is no struct or type called {name}").into()),
+        }
+    }
+
+    /// The name of the LLVM type of a struct or enum. A name on its own is a type of the module that
+    /// is being compiled, `math::Point` gets the prefix of the imported module
+    pub(super) fn type_name(&self, name: &str) -> String {
+        match name.split_once("::") {
+            Some((module, item)) => match self.imports.get(module) {
+                Some(imported) => format!("{}{item}", imported.prefix),
+                None => name.to_string(),
+            },
+            None => self.mangle(name),
+        }
+    }
+
+    /// Generates the value of a struct literal, every field of the struct has to be given
+    pub(super) fn gen_struct_value(&self, name: &Ident, fields: &'ctx [(Ident, Value)]) -> CompileResult<StructValue<'ctx>> {
+        let Some(declaration) = self.structs.get(&self.type_name(&name.name)).copied() else {
+            return Err(format!("There is no struct called {}", name.name).into());
+        };
+        if let Some((unknown, _)) = fields.iter().find(|(field, _)| declaration.get_field_index_with_name(&field.name).is_none()) {
+            return Err(format!("The struct {} has no field called {}", name.name, unknown.name).into());
+        }
+
+        let mut struct_value = self.gen_named_type(&name.name)?.get_undef();
+        for (index, field) in declaration.fields.iter().enumerate() {
+            let Some((_, value)) = fields.iter().find(|(assigned, _)| *assigned == field.ident) else {
+                return Err(format!("The field {} is missing in the literal of struct {}", field.ident.name, name.name).into());
+            };
+            let value = self.gen_field_init(value, &field.r#type)?;
+            let Some(inserted) = self.builder.build_insert_value(struct_value, value, index as u32, &field.ident.name) else {
+                return Err(format!("Couldn't set the field {} of struct {}", field.ident.name, name.name).into());
+            };
+            struct_value = inserted.into_struct_value();
+        }
+        Ok(struct_value)
+    }
+
+    /// Generates a value that gets stored in a field, integers are resized to the type of the field by
+    /// their sign
+    pub(super) fn gen_field_init(&self, value: &'ctx Value, field_type: &'ctx Type) -> CompileResult<BasicValueEnum<'ctx>> {
+        match &value.value {
+            TypeValues::Struct(name, fields) => Ok(self.gen_struct_value(name, fields)?.into()),
+            TypeValues::EnumVariant(enum_ident, variant, payload) => {
+                Ok(self.gen_enum_value(enum_ident, variant, payload)?.into())
+            }
+            TypeValues::Array(items) if field_type.is_array => Ok(self.gen_array_values(items, field_type).into()),
+            TypeValues::String(str) if field_type.is_array => {
+                let mut bytes = self.str_into_array(str);
+                if bytes.len() > field_type.size as usize {
+                    return Err(format!("The string \"{str}\" doesn't fit in a array of size {}", field_type.size).into());
+                }
+                bytes.resize(field_type.size as usize, self.context.i8_type().const_zero());
+                Ok(self.context.i8_type().const_array(&bytes).into())
+            }
+            _ => {
+                // The field can be declared in another module, only the integer types of it resolve
+                // the same in every module
+                let (value, sign) = self.gen_signed_scalar(value)?;
+                let BasicValueEnum::IntValue(int_value) = value else {
+                    return Ok(value);
+                };
+                match self.gen_basic_type(field_type)? {
+                    BasicTypeEnum::IntType(int_type) => Ok(self.gen_int_resize(int_value, int_type, sign).into()),
+                    _ => Ok(value),
+                }
+            }
+        }
+    }
+
+    /// Returns a pointer to the field at the end of `fields`, `p.start.x`, and the type of that field
+    pub(super) fn gen_field_pointer(&self, pointer: PointerValue<'ctx>, fields: &[Ident]) -> CompileResult<(PointerValue<'ctx>, &'ctx Type)> {
+        let mut pointer = pointer;
+        let mut field_type = None;
+        for field in fields {
+            let AnyTypeEnum::StructType(struct_type) = pointer.get_type().get_element_type() else {
+                return Err(format!("Can't get the field {} of a value that isn't a struct", field.name).into());
+            };
+            let (index, declared) = self.get_field(struct_type, field)?;
+            let Ok(field_pointer) = self.builder.build_struct_gep(pointer, index, &field.name) else {
+                return Err(format!("Couldn't get a pointer to the field {}", field.name).into());
+            };
+            pointer = field_pointer;
+            field_type = Some(&declared.r#type);
+        }
+        match field_type {
+            Some(field_type) => Ok((pointer, field_type)),
+            None => Err("Expected the path to a field to have at least one field".into()),
+        }
+    }
+
+    /// Reads a field of a struct variable or paramater, `p.x`
+    pub(super) fn gen_field_value(&self, ident: &Ident, fields: &[Ident]) -> CompileResult<BasicValueEnum<'ctx>> {
+        let variable = self.get_ident(&ident.name)?;
+        if variable.is_pointer_value() {
+            let (pointer, _) = self.gen_field_pointer(variable.into_pointer_value(), fields)?;
+            return Ok(self.builder.build_load(pointer, "field_load"));
+        }
+        if !variable.is_struct_value() {
+            return Err(format!("{} is not a struct, it has no fields", ident.name).into());
+        }
+
+        // Paramaters are passed by value, their fields get extracted instead of loaded
+        let mut value: BasicValueEnum<'ctx> = variable.into_struct_value().into();
+        for field in fields {
+            let BasicValueEnum::StructValue(struct_value) = value else {
+                return Err(format!("Can't get the field {} of a value that isn't a struct", field.name).into());
+            };
+            let (index, _) = self.get_field(struct_value.get_type(), field)?;
+            let Some(field_value) = self.builder.build_extract_value(struct_value, index, &field.name) else {
+                return Err(format!("Couldn't get the field {} of {}", field.name, ident.name).into());
+            };
+            value = field_value;
+        }
+        Ok(value)
+    }
+
+    /// The declared type of a field of a struct variable or paramater, `p.start.x`
+    pub(super) fn get_field_type(&self, ident: &Ident, fields: &[Ident]) -> CompileResult<&'ctx Type> {
+        let mut struct_type = match self.get_ident(&ident.name)? {
+            BasicMetadataValueEnum::PointerValue(pointer) => pointer.get_type().get_element_type(),
+            BasicMetadataValueEnum::StructValue(struct_value) => struct_value.get_type().into(),
+            _ => return Err(format!("{} is not a struct, it has no fields", ident.name).into()),
+        };
+        let mut field_type = None;
+        for field in fields {
+            let AnyTypeEnum::StructType(declared_struct) = struct_type else {
+                return Err(format!("Can't get the field {} of a value that isn't a struct", field.name).into());
+            };
+            let (index, declared) = self.get_field(declared_struct, field)?;
+            // The struct can be declared in another module, the type of the field is taken from
+            // the LLVM struct instead of resolving its name
+            let Some(next_type) = declared_struct.get_field_type_at_index(index) else {
+                return Err(format!("Couldn't get the type of the field {}", field.name).into());
+            };
+            struct_type = next_type.as_any_type_enum();
+            field_type = Some(&declared.r#type);
+        }
+        match field_type {
+            Some(field_type) => Ok(field_type),
+            None => Err("Expected the path to a field to have at least one field".into()),
+        }
+    }
+
+    /// Looks up the index and the declaration of a field by the name of the LLVM struct type
+    fn get_field(&self, struct_type: StructType<'ctx>, field: &Ident) -> CompileResult<(u32, &'ctx Paramater)> {
+        let name = struct_type.get_name().map(|name| name.to_string_lossy().to_string()).unwrap_or_default();
+        let Some(declaration) = self.structs.get(&name).copied() else {
+            return Err(format!("There is no struct called {name}").into());
+        };
+        let Some(index) = declaration.get_field_index_with_name(&field.name) else {
+            return Err(format!("The struct {name} has no field called {}", field.name).into());
+        };
+        Ok((index as u32, &declaration.fields[index]))
+    }
+}
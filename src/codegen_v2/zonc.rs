@@ -1,60 +1,86 @@
-use inkwell::{module::Linkage, values::FunctionValue};
+use inkwell::{
+    module::Linkage,
+    types::{BasicType, BasicTypeEnum},
+    values::{BasicMetadataValueEnum, FunctionValue},
+    AddressSpace,
+};
 
-use super::CodeGen;
+use super::{
+    math_codegen::{IntSign, MathStatementCodegeneration},
+    CodeGen, CompileResult,
+};
+use crate::parser_v2::ast::{ExternFunction, Node, Type, Types};
 
-pub(super) trait GenC<'ctx> {
-    fn gen_printf(&self) -> FunctionValue<'ctx>;
-    fn gen_abs(&self) -> FunctionValue<'ctx>;
-    fn gen_getchar(&self) -> FunctionValue<'ctx>;
-    fn gen_putchar(&self) -> FunctionValue<'ctx>;
-    fn gen_c_function(&self, name: &str) -> Option<FunctionValue<'ctx>>;
-}
-
-impl<'ctx> GenC<'ctx> for CodeGen<'ctx> {
-    fn gen_printf(&self) -> FunctionValue<'ctx> {
-        let printf_args = [self.context.i8_type().ptr_type(Default::default()).into()];
-        let printf_type = self.context.i32_type().fn_type(&printf_args, true);
-        let printf = self
-            .module
-            .add_function("printf", printf_type, Some(Linkage::External));
-        printf
-    }
+impl<'ctx> CodeGen<'ctx> {
+    /// Declares a `extern fn`, the function is defined in C and gets linked in with the C library.
+    /// Every module declares the C functions it uses, they share one declaration in LLVM
+    pub(super) fn gen_extern(&mut self, extern_fn: &'ctx ExternFunction, node: &'ctx Node) -> CompileResult<()> {
+        let name = &extern_fn.ident.name;
+        if self.externs.contains_key(&self.mangle(name)) {
+            return Err(format!("The extern fn {name} is declared more than once, found the second one on line {}", node.line).into());
+        }
 
-    fn gen_abs(&self) -> FunctionValue<'ctx> {
-        let abs_args = [self.context.i32_type().into()];
-        let abs_type = self.context.i32_type().fn_type(&abs_args, false);
-        let readf = self
-            .module
-            .add_function("abs", abs_type, Some(Linkage::External));
-        readf
+        let params = self.gen_params(&extern_fn.paramaters)?;
+        let fn_type = match extern_fn.returns.r#type {
+            Types::None => self.context.void_type().fn_type(&params, extern_fn.is_variadic),
+            _ => self.gen_extern_type(&extern_fn.returns)?.fn_type(&params, extern_fn.is_variadic),
+        };
+        match self.module.get_function(name) {
+            Some(declared) if declared.get_type() != fn_type => {
+                return Err(format!("The extern fn {name} on line {} doesn't match the declaration of {name} in a other module", node.line).into());
+            }
+            Some(_) => {}
+            None => {
+                self.module.add_function(name, fn_type, Some(Linkage::External));
+            }
+        }
+        self.externs.insert(self.mangle(name), extern_fn);
+        Ok(())
     }
 
-    fn gen_getchar(&self) -> FunctionValue<'ctx> {
-        let getchar_type = self.context.i32_type().fn_type(&[], false);
-        let getchar = self
-            .module
-            .add_function("getchar", getchar_type, Some(Linkage::External));
-        getchar
+    /// The type a extern fn returns, C functions often return a pointer like `i8^`
+    fn gen_extern_type(&self, extern_type: &'ctx Type) -> CompileResult<BasicTypeEnum<'ctx>> {
+        if extern_type.is_pointer {
+            return Ok(self.gen_type(extern_type)?.ptr_type(AddressSpace::default()).into());
+        }
+        self.gen_basic_type(extern_type)
     }
 
-    fn gen_putchar(&self) -> FunctionValue<'ctx> {
-        let putchar_type = self
-            .context
-            .i8_type()
-            .fn_type(&[self.context.i8_type().into()], false);
-        let putchar = self
-            .module
-            .add_function("putchar", putchar_type, Some(Linkage::External));
-        putchar
-    }
+    /// Converts the arguments of a call to a extern fn to what C expects. A integer is resized to
+    /// the width of its paramater by its sign, the extra arguments of a variadic function get the
+    /// default promotions of C: a integer smaller than a int becomes a int by its sign and a float
+    /// becomes a double
+    pub(super) fn gen_extern_args(
+        &self,
+        extern_fn: &'ctx ExternFunction,
+        function: FunctionValue<'ctx>,
+        args: Vec<BasicMetadataValueEnum<'ctx>>,
+        signs: &[IntSign],
+    ) -> CompileResult<Vec<BasicMetadataValueEnum<'ctx>>> {
+        let name = &extern_fn.ident.name;
+        let expected = extern_fn.paramaters.len();
+        if args.len() < expected || (!extern_fn.is_variadic && args.len() > expected) {
+            let amount = if extern_fn.is_variadic { "at least " } else { "" };
+            return Err(format!("The extern fn {name} takes {amount}{expected} arguments, but got {}", args.len()).into());
+        }
 
-    fn gen_c_function(&self, name: &str) -> Option<FunctionValue<'ctx>> {
-        match name {
-            "printf" => Some(self.gen_printf()),
-            "abs" => Some(self.gen_abs()),
-            "getchar" => Some(self.gen_getchar()),
-            "putchar" => Some(self.gen_putchar()),
-            _ => None,
+        let param_types = function.get_type().get_param_types();
+        let mut converted = Vec::with_capacity(args.len());
+        for (index, (arg, sign)) in args.into_iter().zip(signs).enumerate() {
+            let arg = match (arg, param_types.get(index)) {
+                (BasicMetadataValueEnum::IntValue(int), Some(BasicTypeEnum::IntType(param))) => {
+                    self.gen_int_resize(int, *param, *sign).into()
+                }
+                (BasicMetadataValueEnum::IntValue(int), None) if int.get_type().get_bit_width() < 32 => {
+                    self.gen_int_resize(int, self.context.i32_type(), *sign).into()
+                }
+                (BasicMetadataValueEnum::FloatValue(float), None) if float.get_type() == self.context.f32_type() => {
+                    self.builder.build_float_ext(float, self.context.f64_type(), "vararg").into()
+                }
+                (arg, _) => arg,
+            };
+            converted.push(arg);
         }
+        Ok(converted)
     }
 }
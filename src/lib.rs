@@ -2,12 +2,11 @@
 //!
 //! It takes any zontanos source code and compiles it into machine code.
 
-use std::{fs, io::Write};
+use std::{fs, io::Write, path::Path};
 
 use codegen_v2::CodeGen;
 use inkwell::context::Context;
-use parser_v2::parser::Parser;
-use zon_parser::lexer::{Tokenizer, Lexer};
+use parser_v2::modules::Program;
 
 /// The main Ast of the language. It contains the structure of the Ast(Abstract syntax tree) that
 /// gets generated after parsing.
@@ -20,15 +19,35 @@ pub mod parser_v2;
 ///
 pub mod zon_parser;
 
-pub fn compile(string: String) -> Result<(), &'static str> {
-    // Lexing
-    let mut lex = Tokenizer::new(&string);
-    let lex = Tokenizer::lex(&mut lex);
+/// Compiles the source of a program, the files it imports are relative to the current directory
+pub fn compile(string: String) -> Result<(), String> {
+    let program = Program::from_source("main.zon", string).map_err(|error| error.render())?;
+    compile_program(&program)
+}
+
+/// Compiles the file at `path` and every file it imports
+pub fn compile_file(path: impl AsRef<Path>) -> Result<(), String> {
+    let program = Program::load(path).map_err(|error| error.render())?;
+    compile_program(&program)
+}
+
+fn compile_program(program: &Program) -> Result<(), String> {
+    let ir = gen_ir(program)?;
+
+    let create = fs::File::create("./main.l");
+
+    if let Ok(mut file) = create {
+        let Ok(_) = file.write(ir.as_bytes()) else {
+        return Err("Coulnd't write output to file".into());
+    };
+        return Ok(());
+    };
 
-    let mut parser = Parser::new(lex);
-    let ast = parser.parse();
-    let ast = ast.unwrap();
+    Ok(())
+}
 
+/// Compiles a program into the LLVM IR of its module
+pub fn gen_ir(program: &Program) -> Result<String, String> {
     let context = Context::create();
     let builder = context.create_builder();
     let module = context.create_module("main");
@@ -37,19 +56,19 @@ pub fn compile(string: String) -> Result<(), &'static str> {
         builder,
         context: &context,
         scope: None,
+        variables: Default::default(),
+        label_id: Default::default(),
+        loops: Default::default(),
+        structs: Default::default(),
+        enums: Default::default(),
+        payloads: Default::default(),
+        globals: Default::default(),
+        functions: Default::default(),
+        externs: Default::default(),
+        module_prefix: Default::default(),
+        imports: Default::default(),
     };
 
-    let code_gen = codegen.compile_ast(&ast);
-    code_gen.unwrap();
-
-    let create = fs::File::create("./main.l");
-
-    if let Ok(mut file) = create {
-        let Ok(_) = file.write(codegen.module.to_string().as_bytes()) else {
-        return Err("Coulnd't write output to file");
-    };
-        return Ok(());
-    };
-
-    Ok(())
+    codegen.compile_program(program).map_err(|error| error.to_string())?;
+    Ok(codegen.module.to_string())
 }
@@ -7,25 +7,19 @@ mod zon_parser;
 
 use inkwell::context::Context;
 use zontanos::codegen_v2::CodeGen;
-use zontanos::parser_v2::parser::Parser;
+use zontanos::parser_v2::modules::Program;
 
 use std::io::Write;
 use std::{fs, process::exit};
-use zontanos::zon_parser::lexer::{Lexer, Tokenizer};
 
 fn main() -> Result<(), &'static str> {
-    let string_vars = fs::read_to_string("./test_code/main.zon").unwrap_or(
-        "Coulnd't read file at ./test_code/main.zon, you are probably not in the root of project."
-            .into(),
-    );
-
-    // Lexing
-    let mut lex = Tokenizer::new(&string_vars);
-    let lex = Tokenizer::lex(&mut lex);
-
-    let mut parser = Parser::new(lex);
-    let ast = parser.parse();
-    let ast = ast.unwrap();
+    let program = match Program::load("./test_code/main.zon") {
+        Ok(program) => program,
+        Err(error) => {
+            eprintln!("{}", error.render());
+            exit(1);
+        }
+    };
 
     let context = Context::create();
     let builder = context.create_builder();
@@ -35,9 +29,20 @@ fn main() -> Result<(), &'static str> {
         builder,
         context: &context,
         scope: None,
+        variables: Default::default(),
+        label_id: Default::default(),
+        loops: Default::default(),
+        structs: Default::default(),
+        enums: Default::default(),
+        payloads: Default::default(),
+        globals: Default::default(),
+        functions: Default::default(),
+        externs: Default::default(),
+        module_prefix: Default::default(),
+        imports: Default::default(),
     };
 
-    let code_gen = codegen.compile_ast(&ast);
+    let code_gen = codegen.compile_program(&program);
     code_gen.unwrap();
 
     let create = fs::File::create("./main.l");
@@ -0,0 +1,416 @@
+//! Evaluates the value of a `const` while parsing, a small interpreter over [`Math`] and the
+//! functions the const calls. A function can only be called when it is declared before the const
+//! and only uses its own variables, `printf` or a global make the const not constant.
+
+use std::collections::HashMap;
+
+use super::{Function, FunctionCall, Math, Node, NodeTypes, Type, TypeValues, Types, Value};
+use crate::parser_v2::parser::{
+    lep::{LogicalStatement, Statements},
+    loops::ForIterator,
+};
+use crate::zon_parser::lexer::Operator;
+
+/// The most loop iterations and calls a const can take, a loop that never ends stops here
+const MAX_STEPS: usize = 1_000_000;
+/// The deepest a const function can recurse
+const MAX_CALL_DEPTH: usize = 256;
+
+/// The variables of a function that gets evaluated with their value and type, the last scope is
+/// the innermost block
+type Scopes = Vec<HashMap<String, (i64, Types)>>;
+
+/// What a statement does to the statements after it
+enum Flow {
+    Next,
+    Break,
+    Continue,
+    Return(i64),
+}
+
+/// [`ConstEval`]
+/// Evaluates the value of a const
+///
+/// **items** the top-level nodes parsed before the const, holds the functions it can call
+/// **consts** the consts the value can use, the last scope is the innermost block
+pub struct ConstEval<'a> {
+    items: &'a [Node],
+    consts: &'a [HashMap<String, i64>],
+    steps: usize,
+    depth: usize,
+}
+
+impl<'a> ConstEval<'a> {
+    pub fn new(items: &'a [Node], consts: &'a [HashMap<String, i64>]) -> Self {
+        Self {
+            items,
+            consts,
+            steps: 0,
+            depth: 0,
+        }
+    }
+
+    /// Evaluates the value node of a const, a value, math or a function call
+    pub fn eval_node(&mut self, node: &Node) -> Result<i64, String> {
+        self.eval_node_value(node, &mut Vec::new())
+    }
+
+    /// Converts a evaluated value into a literal of `const_type`, the value has to fit the type
+    pub fn to_type_value(value: i64, const_type: &Type) -> Result<TypeValues, String> {
+        if const_type.is_array || const_type.is_pointer {
+            return Err("A const can only be a integer or char".into());
+        }
+        let value = Self::fit(value, &const_type.r#type)?;
+        match const_type.r#type {
+            Types::I8 => Ok(TypeValues::I8(value as i8)),
+            Types::U8 => Ok(TypeValues::U8(value as u8)),
+            Types::I32 => Ok(TypeValues::I32(value as i32)),
+            Types::Char => Ok(TypeValues::Char(char::from(value as u8))),
+            _ => Err("A const can only be a integer or char".into()),
+        }
+    }
+
+    /// Checks that the value fits into the integer type
+    fn fit(value: i64, value_type: &Types) -> Result<i64, String> {
+        match value_type {
+            Types::I8 | Types::U8 | Types::I32 => Ok(value_type.convert_integer(&value.to_string())? as i64),
+            Types::Char if (0..=u8::MAX as i64).contains(&value) => Ok(value),
+            Types::Char => Err(format!("The value {value} is out of range for type char, expected a value in between 0 and 255")),
+            value_type => Err(format!("Only integers and chars can be evaluated at compile time, not {value_type:?}")),
+        }
+    }
+
+    fn step(&mut self) -> Result<(), String> {
+        self.steps += 1;
+        if self.steps > MAX_STEPS {
+            return Err(format!("The value took more than {MAX_STEPS} steps to evaluate, it might never end"));
+        }
+        Ok(())
+    }
+
+    /// Calls a function that is declared before the const with the values of the arguments
+    fn call(&mut self, call: &FunctionCall, arguments: &[Value], scopes: &mut Scopes) -> Result<i64, String> {
+        let name = call.calls_to.name.as_str();
+        if let Some(module) = &call.module {
+            return Err(format!("{}::{name} is a function of a other module, only functions of the same file can be called", module.name));
+        }
+        let Some(function) = self.items.iter().find_map(|item| match &item.node_type {
+            NodeTypes::Function(function) if function.ident.name == name => Some(function),
+            _ => None,
+        }) else {
+            return Err(format!("{name} isn't a function declared before the const, only those can be called"));
+        };
+        let arguments = arguments.iter().map(|argument| self.eval_value(argument, scopes)).collect::<Result<Vec<_>, _>>()?;
+        if function.paramaters.len() != arguments.len() {
+            return Err(format!("{name} takes {} arguments but got {}", function.paramaters.len(), arguments.len()));
+        }
+        if self.depth >= MAX_CALL_DEPTH {
+            return Err(format!("{name} recursed more than {MAX_CALL_DEPTH} calls deep"));
+        }
+        self.step()?;
+
+        let mut scope = HashMap::new();
+        for (param, argument) in function.paramaters.iter().zip(arguments) {
+            if param.r#type.is_array || param.r#type.is_pointer {
+                return Err(format!("The paramater {} of {name} isn't a integer or char", param.ident.name));
+            }
+            let param_type = param.r#type.r#type.clone();
+            scope.insert(param.ident.name.clone(), (Self::fit(argument, &param_type)?, param_type));
+        }
+
+        self.depth += 1;
+        let value = self.eval_function(function, &mut vec![scope]);
+        self.depth -= 1;
+        Self::fit(value?, &function.returns.r#type)
+    }
+
+    /// Runs the body of a function, a function returns with `return` or the value its body ends in
+    fn eval_function(&mut self, function: &Function, scopes: &mut Scopes) -> Result<i64, String> {
+        let name = &function.ident.name;
+        let (statements, tail) = match function.body.split_last() {
+            Some((last, statements)) if matches!(last.node_type, NodeTypes::Value(_)) => (statements, Some(last)),
+            _ => (&function.body[..], None),
+        };
+        if let Flow::Return(value) = self.eval_nodes(statements, scopes)? {
+            return Ok(value);
+        }
+        match tail {
+            Some(tail) => self.eval_node_value(tail, scopes),
+            None => Err(format!("{name} doesn't return a value")),
+        }
+    }
+
+    /// Runs the nodes of a block in a new scope
+    fn eval_block(&mut self, nodes: &[Node], scopes: &mut Scopes) -> Result<Flow, String> {
+        scopes.push(HashMap::new());
+        let flow = self.eval_nodes(nodes, scopes);
+        scopes.pop();
+        flow
+    }
+
+    fn eval_nodes(&mut self, nodes: &[Node], scopes: &mut Scopes) -> Result<Flow, String> {
+        for node in nodes {
+            let flow = self.eval_statement(node, scopes)?;
+            if !matches!(flow, Flow::Next) {
+                return Ok(flow);
+            }
+        }
+        Ok(Flow::Next)
+    }
+
+    fn eval_statement(&mut self, node: &Node, scopes: &mut Scopes) -> Result<Flow, String> {
+        match &node.node_type {
+            NodeTypes::Variable(variable) | NodeTypes::Const(variable) => {
+                let Some(right) = &node.right else {
+                    return Err(format!("The variable {} has no value", variable.ident.name));
+                };
+                if variable.var_type.is_array || variable.var_type.is_pointer {
+                    return Err(format!("The variable {} isn't a integer or char", variable.ident.name));
+                }
+                let var_type = variable.var_type.r#type.clone();
+                let value = Self::fit(self.eval_node_value(right, scopes)?, &var_type)?;
+                if let Some(scope) = scopes.last_mut() {
+                    scope.insert(variable.ident.name.clone(), (value, var_type));
+                }
+            }
+            NodeTypes::Assignment(assignment) => {
+                let name = &assignment.assigns_to.name;
+                if !assignment.fields.is_empty() {
+                    return Err(format!("Can't assign to a field of {name} at compile time"));
+                }
+                let (Some(op), Some(value)) = (assignment.get_op(node), assignment.get_value(node)) else {
+                    return Err(format!("Expected a operator and a value in the assignment to {name}"));
+                };
+                let value = self.eval_value(value, scopes)?;
+                let Some((variable, var_type)) = scopes.iter_mut().rev().find_map(|scope| scope.get_mut(name)) else {
+                    return Err(format!("Can't assign to {name}, only the variables of the function can be changed"));
+                };
+                // The new value has to fit the type of the variable, `x += 100` on a i8 can overflow
+                let value = match op {
+                    Operator::Eq => value,
+                    op => match op.compound_operator() {
+                        Some(math_op) => Self::apply(&math_op, *variable, value)?,
+                        None => return Err(format!("{op:?} is not a assignment operator")),
+                    },
+                };
+                *variable = Self::fit(value, var_type)?;
+            }
+            NodeTypes::LogicalStatement(statement) => {
+                if let Some(nodes) = self.eval_branch(statement, scopes)? {
+                    return self.eval_block(nodes, scopes);
+                }
+            }
+            NodeTypes::While(while_loop) => {
+                while self.eval_case(&while_loop.case, scopes)? {
+                    self.step()?;
+                    match self.eval_block(&while_loop.body, scopes)? {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Next | Flow::Continue => {}
+                    }
+                }
+            }
+            NodeTypes::For(for_loop) => {
+                let ForIterator::Range { start, end, inclusive } = &for_loop.iterator else {
+                    return Err("Only a for loop over a range can be evaluated at compile time".into());
+                };
+                // The counter has the type of a variable the range uses, like the codegen does
+                let counter_type = [start, end]
+                    .into_iter()
+                    .find_map(|bound| Self::local_type(bound, scopes))
+                    .unwrap_or(Types::I32);
+                let start = self.eval_value(start, scopes)?;
+                let end = self.eval_value(end, scopes)?;
+                let end = if *inclusive { end } else { end - 1 };
+                for counter in start..=end {
+                    self.step()?;
+                    scopes.push(HashMap::from([(for_loop.binding.name.clone(), (counter, counter_type.clone()))]));
+                    let flow = self.eval_block(&for_loop.body, scopes);
+                    scopes.pop();
+                    match flow? {
+                        Flow::Break => break,
+                        Flow::Return(value) => return Ok(Flow::Return(value)),
+                        Flow::Next | Flow::Continue => {}
+                    }
+                }
+            }
+            NodeTypes::Return => {
+                let Some(right) = &node.right else {
+                    return Err("Expected a value to return".into());
+                };
+                return Ok(Flow::Return(self.eval_node_value(right, scopes)?));
+            }
+            NodeTypes::Break => return Ok(Flow::Break),
+            NodeTypes::Continue => return Ok(Flow::Continue),
+            NodeTypes::FunctionCall(_) => {
+                self.eval_node_value(node, scopes)?;
+            }
+            // The value a block ends in is only used when the block is the branch of a if expression
+            NodeTypes::Value(_) => {}
+            node_type => return Err(format!("{node_type:?} can't be evaluated at compile time")),
+        }
+        Ok(Flow::Next)
+    }
+
+    /// Returns the block of the branch the cases pick, following the `else if` chain. Returns None
+    /// when no case is true and there is no else block
+    fn eval_branch<'n>(&mut self, statement: &'n LogicalStatement, scopes: &mut Scopes) -> Result<Option<&'n [Node]>, String> {
+        let mut statement = statement;
+        loop {
+            if self.eval_case(&statement.case, scopes)? {
+                let NodeTypes::Block(nodes) = &statement.if_do else {
+                    return Err("Expected a if block".into());
+                };
+                return Ok(Some(nodes));
+            }
+            match &statement.else_do {
+                Some(NodeTypes::Block(nodes)) => return Ok(Some(nodes)),
+                Some(NodeTypes::LogicalStatement(else_if)) => statement = else_if,
+                _ => return Ok(None),
+            }
+        }
+    }
+
+    /// Evaluates a node that gives a value, a value, a function call or a if expression
+    fn eval_node_value(&mut self, node: &Node, scopes: &mut Scopes) -> Result<i64, String> {
+        match &node.node_type {
+            NodeTypes::Value(value) => self.eval_value(value, scopes),
+            NodeTypes::FunctionCall(call) => {
+                let Some(arguments) = call.get_args(node) else {
+                    return Err(format!("Expected the arguments of {}", call.calls_to.name));
+                };
+                self.call(call, arguments, scopes)
+            }
+            NodeTypes::LogicalStatement(statement) => match self.eval_branch(statement, scopes)? {
+                Some(nodes) => self.eval_block_value(nodes, scopes),
+                None => Err("A if that is used as a value must have a else block".into()),
+            },
+            node_type => Err(format!("{node_type:?} can't be evaluated at compile time")),
+        }
+    }
+
+    /// The value of a block is the value of its last node
+    fn eval_block_value(&mut self, nodes: &[Node], scopes: &mut Scopes) -> Result<i64, String> {
+        let Some((last, statements)) = nodes.split_last() else {
+            return Err("Expected the block of a if expression to end in a value".into());
+        };
+        scopes.push(HashMap::new());
+        let value = match self.eval_nodes(statements, scopes) {
+            Ok(Flow::Next) => self.eval_node_value(last, scopes),
+            Ok(_) => Err("A if expression can't return, break or continue at compile time".into()),
+            Err(error) => Err(error),
+        };
+        scopes.pop();
+        value
+    }
+
+    fn eval_value(&mut self, value: &Value, scopes: &mut Scopes) -> Result<i64, String> {
+        match &value.value {
+            TypeValues::I8(value) => Ok(*value as i64),
+            TypeValues::U8(value) => Ok(*value as i64),
+            TypeValues::I32(value) | TypeValues::I32Neg(value) => Ok(*value as i64),
+            TypeValues::Char(value) => Ok(*value as i64),
+            TypeValues::True => Ok(1),
+            TypeValues::False => Ok(0),
+            TypeValues::Math(math) => self.eval_math(math, scopes),
+            TypeValues::Identifier(name) => self.lookup(name, scopes),
+            TypeValues::FunctionCall(call, arguments) => {
+                self.call(call, arguments, scopes)
+            }
+            value => Err(format!("{value:?} isn't known at compile time")),
+        }
+    }
+
+    /// Looks up a variable of the function, then a const. A function only sees the top-level
+    /// consts, the consts of the block the const is in are only visible to the const itself
+    fn lookup(&self, name: &str, scopes: &Scopes) -> Result<i64, String> {
+        if let Some((value, _)) = scopes.iter().rev().find_map(|scope| scope.get(name)) {
+            return Ok(*value);
+        }
+        let consts = if self.depth == 0 { self.consts } else { &self.consts[..self.consts.len().min(1)] };
+        match consts.iter().rev().find_map(|scope| scope.get(name)) {
+            Some(value) => Ok(*value),
+            None => Err(format!("{name} isn't a const, only consts and the variables of a const function are known at compile time")),
+        }
+    }
+
+    /// The type of a value that is a variable of the function
+    fn local_type(value: &Value, scopes: &Scopes) -> Option<Types> {
+        let TypeValues::Identifier(name) = &value.value else { return None };
+        scopes.iter().rev().find_map(|scope| scope.get(name)).map(|(_, local_type)| local_type.clone())
+    }
+
+    fn eval_math(&mut self, math: &Math, scopes: &mut Scopes) -> Result<i64, String> {
+        match math {
+            Math::Value(value) => self.eval_value(value, scopes),
+            Math::Unary(Operator::Min, operand) => {
+                let operand = self.eval_math(operand, scopes)?;
+                operand.checked_neg().ok_or_else(|| format!("Negating {operand} overflows"))
+            }
+            Math::Unary(Operator::Tilde, operand) => Ok(!self.eval_math(operand, scopes)?),
+            Math::Unary(op, _) => Err(format!("{op:?} is not a unary math operator")),
+            Math::Binary(lhs, op, rhs) => {
+                let lhs = self.eval_math(lhs, scopes)?;
+                let rhs = self.eval_math(rhs, scopes)?;
+                Self::apply(op, lhs, rhs)
+            }
+        }
+    }
+
+    /// Applies a binary math operator, a overflow or a division by zero is a error
+    fn apply(op: &Operator, lhs: i64, rhs: i64) -> Result<i64, String> {
+        let value = match op {
+            Operator::Plus => lhs.checked_add(rhs),
+            Operator::Min => lhs.checked_sub(rhs),
+            Operator::Times => lhs.checked_mul(rhs),
+            Operator::Slash if rhs == 0 => return Err(format!("Can't divide {lhs} by zero")),
+            Operator::Slash => lhs.checked_div(rhs),
+            Operator::Percent if rhs == 0 => return Err(format!("Can't take the remainder of {lhs} divided by zero")),
+            Operator::Percent => lhs.checked_rem(rhs),
+            Operator::ShiftLeft => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_shl(rhs)),
+            Operator::ShiftRight => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_shr(rhs)),
+            Operator::And => Some(lhs & rhs),
+            Operator::Xor => Some(lhs ^ rhs),
+            Operator::Or => Some(lhs | rhs),
+            op => return Err(format!("{op:?} is not a integer math operator")),
+        };
+        value.ok_or_else(|| format!("{lhs} {op:?} {rhs} overflows"))
+    }
+
+    /// Evaluates the cases of a if or while, '&&' binds tighter then '||' and a case only gets
+    /// evaluated when it can still change the outcome
+    fn eval_case(&mut self, statements: &[Statements], scopes: &mut Scopes) -> Result<bool, String> {
+        for and_cases in statements.split(|statement| *statement == Statements::Or) {
+            let mut all = true;
+            for case in and_cases.split(|statement| *statement == Statements::And) {
+                let [case] = case else {
+                    return Err("Expected a single case in between '&&' and '||'".into());
+                };
+                if !self.eval_statement_case(case, scopes)? {
+                    all = false;
+                    break;
+                }
+            }
+            if all {
+                return Ok(true);
+            }
+        }
+        Ok(false)
+    }
+
+    fn eval_statement_case(&mut self, statement: &Statements, scopes: &mut Scopes) -> Result<bool, String> {
+        let (lhs, rhs, compare): (&Value, &Value, fn(&i64, &i64) -> bool) = match statement {
+            Statements::Group(statements) => return self.eval_case(statements, scopes),
+            Statements::Not(statement) => return Ok(!self.eval_statement_case(statement, scopes)?),
+            Statements::Atomic(value) => return Ok(self.eval_value(value, scopes)? != 0),
+            Statements::EqEq(lhs, rhs) => (lhs, rhs, i64::eq),
+            Statements::NotEq(lhs, rhs) => (lhs, rhs, i64::ne),
+            Statements::More(lhs, rhs) => (lhs, rhs, i64::gt),
+            Statements::Less(lhs, rhs) => (lhs, rhs, i64::lt),
+            Statements::MoreEq(lhs, rhs) => (lhs, rhs, i64::ge),
+            Statements::LessEq(lhs, rhs) => (lhs, rhs, i64::le),
+            statement => return Err(format!("{statement:?} can't be turned into a single value")),
+        };
+        Ok(compare(&self.eval_value(lhs, scopes)?, &self.eval_value(rhs, scopes)?))
+    }
+}
@@ -2,11 +2,16 @@
 #![allow(unused)]
 
 use crate::zon_parser::lexer::{Tokens, Token};
+use crate::zon_parser::span::Span;
 
 use crate::{ast::variable, zon_parser::lexer::Operator};
 
 use super::parser::lep::LogicalStatement;
+use super::parser::loops::{ForLoop, WhileLoop};
+use super::parser::matches::Match;
 
+pub mod const_eval;
+pub mod number_literal;
 pub mod types_from_str;
 pub mod types_match;
 
@@ -14,12 +19,19 @@ pub mod types_match;
 pub struct Ast {
     pub r#type: NodeTypes,
     pub body: Vec<Node>,
+    /// Every path to a function of a imported module, `math::sqrt`. The modules are loaded after
+    /// parsing, so the paths get checked once the module they point into is known
+    pub module_paths: Vec<ModulePath>,
+    /// Every use of a struct or enum by its name, the loader of the modules checks a type of
+    /// another module is pub
+    pub type_uses: Vec<TypeUse>,
 }
 
 /// [`Node`]
 /// A Node in the abstract syntax tree;
 ///
 /// **Line** the line of where the node is located in source code
+/// **span** the exact location of the node in source code
 /// **right** The values/nodes right of the Node
 /// **left** The values/nodes left of the Node
 /// **NodeTypes** The type of a Node
@@ -29,6 +41,7 @@ pub struct Node {
     pub left: Option<Box<Node>>,
     pub right: Option<Box<Node>>,
     pub line: usize,
+    pub span: Span,
 }
 
 /// [`Function`]
@@ -37,12 +50,68 @@ pub struct Node {
 /// **ident** Identifier/name of the function
 /// **body** The body `{<body>}` of a function
 /// **returns** The type the function returns
+/// **is_pub** If the function is declared with `pub fn`, only those can be called from other modules
 #[derive(Debug)]
 pub struct Function {
     pub ident: Ident,
     pub body: Vec<Node>,
     pub paramaters: Vec<Paramater>,
     pub returns: Type,
+    pub is_pub: bool,
+}
+
+/// [`Import`]
+/// A import of a other file, `import "math.zon"`
+///
+/// **path** The path of the file, relative to the file that imports it
+/// **module** The name of the module, the name of the file without extension
+#[derive(Debug)]
+pub struct Import {
+    pub path: String,
+    pub module: Ident,
+}
+
+/// [`ModulePath`]
+/// A path to a function of a imported module, `math::sqrt`
+///
+/// **module** The name of the imported module
+/// **item** The name of the function in the module
+/// **span** Where the path was written in source code
+#[derive(Debug, Clone, PartialEq)]
+pub struct ModulePath {
+    pub module: Ident,
+    pub item: Ident,
+    pub span: Span,
+}
+
+/// [`TypeUse`]
+/// A use of a struct or enum by its name, as a type `p: Point`, a literal `Point { x: 1 }` or a
+/// variant `Shape::Empty`. A type of a imported module is used with the name of the module in
+/// front of it, `math::Point`
+///
+/// **module** The imported module the type is declared in, None for a type of the module itself
+/// **ident** The name of the type
+/// **span** Where the name was written in source code
+#[derive(Debug, Clone, PartialEq)]
+pub struct TypeUse {
+    pub module: Option<Ident>,
+    pub ident: Ident,
+    pub span: Span,
+}
+
+/// [`ExternFunction`]
+/// A function that is declared in Zontanos but defined in C, like `printf`
+///
+/// **ident** Identifier/name of the C function
+/// **paramaters** The paramaters every call has to pass
+/// **returns** The type the function returns
+/// **is_variadic** If the declaration ends with `...`, calls can pass more arguments than the paramaters
+#[derive(Debug)]
+pub struct ExternFunction {
+    pub ident: Ident,
+    pub paramaters: Vec<Paramater>,
+    pub returns: Type,
+    pub is_variadic: bool,
 }
 
 /// [`Variable`]
@@ -60,9 +129,49 @@ pub struct Variable {
 /// Assigment to a variable that exists
 ///
 /// **assigns_to** the ident of the variable being reassigned/assigned to
+/// **fields** the path to the field of a struct that gets assigned to, `p.x = 1`, empty when the
+/// variable itself gets assigned to
 #[derive(Debug)]
 pub struct Assignment {
     pub assigns_to: Ident,
+    pub fields: Vec<Ident>,
+}
+
+/// [`Struct`]
+/// A struct declaration, `struct Point { x: i32, y: i32 }`
+///
+/// **ident** the name of the struct, it's also the name of its type
+/// **fields** the fields of the struct in the order they are declared
+/// **is_pub** If the struct is declared with `pub struct`, only those can be used by other modules
+#[derive(Debug)]
+pub struct Struct {
+    pub ident: Ident,
+    pub fields: Vec<Paramater>,
+    pub is_pub: bool,
+}
+
+/// [`Enum`]
+/// A enum declaration, `enum Shape { Circle(i32), Rect(i32, i32), Empty }`
+///
+/// **ident** the name of the enum, it's also the name of its type
+/// **variants** the variants in the order they are declared, the index of a variant is its tag
+/// **is_pub** If the enum is declared with `pub enum`, only those can be used by other modules
+#[derive(Debug)]
+pub struct Enum {
+    pub ident: Ident,
+    pub variants: Vec<Variant>,
+    pub is_pub: bool,
+}
+
+/// [`Variant`]
+/// A variant of a enum
+///
+/// **ident** the name of the variant
+/// **fields** the types of the payload of the variant, empty if it has no payload
+#[derive(Debug)]
+pub struct Variant {
+    pub ident: Ident,
+    pub fields: Vec<Type>,
 }
 
 /// [`Type`]
@@ -70,6 +179,7 @@ pub struct Assignment {
 ///
 /// **r#type** the type it is
 /// **generics** all of the generic values if any, will be empty if there are none
+/// **span** where the type was written in source code
 #[derive(Clone, Debug)]
 pub struct Type {
     pub r#type: Types,
@@ -77,6 +187,7 @@ pub struct Type {
     pub is_pointer: bool,
     pub size: u32,
     pub generics: Vec<Type>,
+    pub span: Span,
 }
 
 /// [`Paramater`]
@@ -104,29 +215,43 @@ pub struct Ident {
 ///
 /// **r#name** the name of that value
 /// **value** the value
+/// **span** where the value was written in source code
 #[derive(Debug, Clone, PartialEq)]
 pub struct Value {
     pub value: TypeValues,
     pub is_ptr: bool,
+    pub span: Span,
 }
 
 /// [`FunctionCall`]
 /// Call to Function
 ///
 /// **r#calls_to** the Ident of the function being called upon
+/// **module** the module the function is in, `math` in `math::sqrt(2)`, None for a function of
+/// the same module
 /// **arguments** all the arguments found in the function call
 #[derive(Debug, PartialEq, Clone)]
 pub struct FunctionCall {
     pub calls_to: Ident,
+    pub module: Option<Ident>,
 }
 
 /// [`Math`]
-/// All the tokens found in the mathematical statement that can either be a number, operator, or
-/// identifier.
+/// The expression tree of a mathematical statement, the tree is built with the precedence of
+/// [`Operator::precedence`], so `a + b * c` becomes:
+///
+/// `Binary(Value(a), Plus, Binary(Value(b), Times, Value(c)))`
 ///
-/// All other tokens should throw a parse error
+/// Braces don't show up in the tree, they only change its shape.
 #[derive(Debug, PartialEq, Clone)]
-pub struct Math(pub Vec<Value>);
+pub enum Math {
+    /// A number, identifier or function call
+    Value(Box<Value>),
+    /// A '-' or '~' in front of a operand, `-a`
+    Unary(Operator, Box<Math>),
+    /// Two operands with a operator in between, `a + b`
+    Binary(Box<Math>, Operator, Box<Math>),
+}
 
 /// [`Types`]
 /// All current types in a language
@@ -140,6 +265,9 @@ pub enum Types {
     String,
     Array,
     Ident,
+    /// A struct or enum declared by the program, holds its name. The name of a type of a imported
+    /// module starts with the name of the module, `math::Point`
+    Named(String),
     // Should only be used if the type can not be known during parsing.
     None,
     UnknownType(String),
@@ -159,6 +287,12 @@ pub enum TypeValues {
     FunctionCall(FunctionCall, Vec<Value>),
     Array(Vec<TypeValues>),
     Identifier(String),
+    /// A struct literal, `Point { x: 1, y: 2 }`
+    Struct(Ident, Vec<(Ident, Value)>),
+    /// A field of a struct variable, `p.x` or `line.start.x`
+    FieldAccess(Ident, Vec<Ident>),
+    /// A variant of a enum with its payload, `Shape::Circle(5)` or `Shape::Empty`
+    EnumVariant(Ident, Ident, Vec<Value>),
     NoneVal(String),
     Math(Math),
     Operator(Operator),
@@ -174,48 +308,63 @@ pub enum NodeTypes {
     Program,
     Block(Vec<Node>),
     Function(Function),
+    ExternFunction(ExternFunction),
+    Import(Import),
+    Struct(Struct),
+    Enum(Enum),
     Variable(Variable),
+    /// A `const`, its value is the literal it evaluated to while parsing
+    Const(Variable),
     Assignment(Assignment),
     Operator(Operator),
     Value(Value),
     FunctionCall(FunctionCall),
     Arguments(Vec<Value>),
     LogicalStatement(Box<LogicalStatement>),
+    While(Box<WhileLoop>),
+    For(Box<ForLoop>),
+    Match(Box<Match>),
     Math(Math),
     Return,
+    Break,
+    Continue,
 }
 
 impl Node {
-    pub fn new(node_type: NodeTypes, line: usize) -> Self {
+    pub fn new(node_type: NodeTypes, line: usize, span: Span) -> Self {
         Self {
             node_type,
             left: None,
             right: None,
             line,
+            span,
         }
     }
 
-    pub fn variable(variable: Variable, left_operator: Operator, line: usize) -> Node {
+    pub fn variable(variable: Variable, left_operator: Operator, line: usize, span: Span) -> Node {
         let node = Node {
             node_type: NodeTypes::Variable(variable),
             left: Some(Box::from(Node::new(
                 NodeTypes::Operator(left_operator),
                 line,
+                span,
             ))),
             right: None,
             line,
+            span,
         };
         node
     }
 
-    pub fn fn_call(function_call: FunctionCall, arguments: NodeTypes, line: usize) -> Node {
+    pub fn fn_call(function_call: FunctionCall, arguments: NodeTypes, line: usize, span: Span) -> Node {
         let node_type = NodeTypes::FunctionCall(function_call);
-        let arguments = Node::new(arguments, line);
+        let arguments = Node::new(arguments, line, span);
         Node {
             node_type,
             left: Some(Box::from(arguments)),
             right: None,
             line,
+            span,
         }
     }
 }
@@ -228,6 +377,17 @@ impl Type {
             is_pointer: false,
             size: 0,
             generics: Vec::new(),
+            span: Span::default(),
+        }
+    }
+}
+
+impl Value {
+    pub fn new(value: TypeValues, is_ptr: bool, span: Span) -> Self {
+        Self {
+            value,
+            is_ptr,
+            span,
         }
     }
 }
@@ -237,6 +397,7 @@ impl From<TypeValues> for Value {
         Self {
             value,
             is_ptr: false,
+            span: Span::default(),
         }
     }
 }
@@ -267,6 +428,18 @@ impl Function {
     }
 }
 
+impl Struct {
+    pub fn get_field_index_with_name(&self, name_field: &str) -> Option<usize> {
+        self.fields.iter().position(|field| field.ident.name == name_field)
+    }
+}
+
+impl Enum {
+    pub fn get_variant_index_with_name(&self, name_variant: &str) -> Option<usize> {
+        self.variants.iter().position(|variant| variant.ident.name == name_variant)
+    }
+}
+
 impl Assignment {
     pub fn get_op<'ctx>(&self, node: &'ctx Node) -> Option<&'ctx Operator> {
         let Some(node) = &node.left else { return None; };
@@ -0,0 +1,109 @@
+//! Splits number literals such as `0xFF`, `-3i8` and `1.5e3f32` into their parts.
+
+use super::Types;
+
+/// The type suffixes a number literal can end with
+const SUFFIXES: [(&str, Types); 4] = [
+    ("u8", Types::U8),
+    ("i8", Types::I8),
+    ("i32", Types::I32),
+    ("f32", Types::F32),
+];
+
+/// [`NumberLiteral`]
+/// A number literal split into its parts
+///
+/// **negative** if the literal started with a '-'
+/// **radix** the base of the digits, 16 for 0x, 2 for 0b, 8 for 0o and 10 otherwise
+/// **digits** the digits without the sign, the prefix and the suffix
+/// **is_float** if the literal contains a '.' or a exponent
+/// **suffix** the type suffix of the literal if any, `255u8` -> Some(Types::U8)
+#[derive(Debug, PartialEq, Clone)]
+pub struct NumberLiteral {
+    pub negative: bool,
+    pub radix: u32,
+    pub digits: String,
+    pub is_float: bool,
+    pub suffix: Option<Types>,
+}
+
+impl NumberLiteral {
+    /// Returns None if the value doesn't start like a number literal
+    pub fn parse(value: &str) -> Option<Self> {
+        let (negative, value) = match value.strip_prefix('-') {
+            Some(value) => (true, value),
+            None => (false, value),
+        };
+        if !value.starts_with(|char: char| char.is_ascii_digit()) {
+            return None;
+        }
+
+        let (radix, mut digits) = match value.get(..2) {
+            Some("0x") => (16, &value[2..]),
+            Some("0b") => (2, &value[2..]),
+            Some("0o") => (8, &value[2..]),
+            _ => (10, value),
+        };
+
+        let mut suffix = None;
+        for (name, suffix_type) in SUFFIXES {
+            // f is a valid hex digit, so 0x1f32 is a number and not 0x1 with a f32 suffix
+            if radix == 16 && suffix_type == Types::F32 {
+                continue;
+            }
+            if let Some(without_suffix) = digits.strip_suffix(name) {
+                digits = without_suffix;
+                suffix = Some(suffix_type);
+                break;
+            }
+        }
+
+        let is_float = radix == 10 && digits.contains(['.', 'e', 'E']);
+        Some(Self {
+            negative,
+            radix,
+            digits: digits.to_string(),
+            is_float,
+            suffix,
+        })
+    }
+
+    /// The type the literal has on its own, a suffix always wins, otherwise floats are f32 and
+    /// None is returned for integers
+    pub fn literal_type(&self) -> Option<Types> {
+        if let Some(suffix) = &self.suffix {
+            return Some(suffix.clone());
+        }
+        if self.is_float {
+            return Some(Types::F32);
+        }
+        None
+    }
+
+    /// Returns the value of a integer literal, this does not check if the value fits a type.
+    pub fn integer_value(&self) -> Option<i128> {
+        if self.is_float || self.digits.is_empty() {
+            return None;
+        }
+        let magnitude = u128::from_str_radix(&self.digits, self.radix).ok()?;
+        let magnitude = i128::try_from(magnitude).ok()?;
+        if self.negative {
+            Some(-magnitude)
+        } else {
+            Some(magnitude)
+        }
+    }
+
+    /// Returns the value of the literal as a float, integer literals are converted.
+    pub fn float_value(&self) -> Option<f64> {
+        if !self.is_float {
+            return self.integer_value().map(|value| value as f64);
+        }
+        let value = self.digits.parse::<f64>().ok()?;
+        if self.negative {
+            Some(-value)
+        } else {
+            Some(value)
+        }
+    }
+}
@@ -1,6 +1,6 @@
 //!  Used to match Types::T against a value;
 
-use super::{TypeValues, Types};
+use super::{number_literal::NumberLiteral, TypeValues, Types};
 
 impl Types {
     // Returns if the value string can be parsed into it's type,
@@ -17,29 +17,29 @@ impl Types {
 
     pub fn type_value_convert(&self, value: &str) -> Result<TypeValues, String> {
         match &self {
-            Self::I8 => {
-                let Ok(value) = value.parse::<i8>() else {
-                    return  Err(format!("Expected type value i8 but got value {value}"));
-                };
-                Ok(TypeValues::I8(value))
-            }
-            Self::U8 => {
-                let Ok(value) = value.parse::<u8>() else {
-                    return  Err(format!("Expected type value u8 but got value {value}"));
-                };
-                Ok(TypeValues::U8(value))
-            }
-            Self::I32 => {
-                let Ok(value) = value.parse::<i32>() else {
-                    return  Err(format!("Expected type value i32 but got value {value}"));
-                };
-                Ok(TypeValues::I32(value))
-            }
+            Self::I8 => Ok(TypeValues::I8(self.convert_integer(value)? as i8)),
+            Self::U8 => Ok(TypeValues::U8(self.convert_integer(value)? as u8)),
+            Self::I32 => Ok(TypeValues::I32(self.convert_integer(value)? as i32)),
             Self::F32 => {
-                let Ok(value) = value.parse::<f32>() else {
+                let Some(literal) = NumberLiteral::parse(value) else {
+                    return  Err(format!("Expected type value f32 but got value {value}"));
+                };
+                if let Some(suffix) = literal.suffix.as_ref().filter(|suffix| **suffix != Self::F32) {
+                    return Err(format!(
+                        "Expected type value f32 but got value {value} of type {suffix:?}"
+                    ));
+                }
+                let Some(float) = literal.float_value() else {
                     return  Err(format!("Expected type value f32 but got value {value}"));
                 };
-                Ok(TypeValues::F32(value))
+                let float = float as f32;
+                if float.is_infinite() {
+                    return Err(format!(
+                        "The literal {value} is out of range for type f32, expected a value in between {} and {}",
+                        f32::MIN, f32::MAX
+                    ));
+                }
+                Ok(TypeValues::F32(float))
             }
             Self::String => {
                 let Ok(value) = value.parse::<String>() else {
@@ -55,9 +55,58 @@ impl Types {
             }
             Self::Ident => Ok(TypeValues::Identifier(value.into())),
             Self::None => Ok(TypeValues::NoneVal(value.into())),
-            not_supported_conversion => panic!(
-                "Type value conversion shouldn't get called on type {not_supported_conversion:#?}"
-            ),
+            Self::Named(name) => Err(format!("Expected a value of type {name} but got value {value}")),
+            not_supported_conversion => Err(format!(
+                "Expected a value of type {not_supported_conversion:?} but got value {value}"
+            )),
+        }
+    }
+
+    /// Returns the type a number literal has on its own, being the type of its suffix, f32 for
+    /// floats and None for integers without a suffix.
+    pub fn number_literal_type(value: &str) -> Option<Types> {
+        NumberLiteral::parse(value)?.literal_type()
+    }
+
+    /// Converts a integer literal into its value, it returns a error if the literal is not a
+    /// integer, has a suffix of a different type or doesn't fit into the type.
+    ///
+    /// # Example
+    ///
+    /// `Types::U8.convert_integer("0xFF")` -> Ok(255)
+    /// `Types::I8.convert_integer("255")` -> Err(out of range)
+    pub fn convert_integer(&self, value: &str) -> Result<i128, String> {
+        let (type_name, min, max) = match self {
+            Self::I8 => ("i8", i8::MIN as i128, i8::MAX as i128),
+            Self::U8 => ("u8", u8::MIN as i128, u8::MAX as i128),
+            Self::I32 => ("i32", i32::MIN as i128, i32::MAX as i128),
+            not_integer => return Err(format!("Expected a integer type but got {not_integer:?}")),
+        };
+
+        let Some(literal) = NumberLiteral::parse(value) else {
+            return Err(format!("Expected type value {type_name} but got value {value}"));
+        };
+        if let Some(suffix) = literal.suffix.as_ref().filter(|suffix| *suffix != self) {
+            return Err(format!(
+                "Expected type value {type_name} but got value {value} of type {suffix:?}"
+            ));
+        }
+        if literal.is_float {
+            return Err(format!(
+                "Expected type value {type_name} but got the float value {value}"
+            ));
+        }
+
+        match literal.integer_value() {
+            Some(integer) if (min..=max).contains(&integer) => Ok(integer),
+            Some(_) => Err(format!(
+                "The literal {value} is out of range for type {type_name}, expected a value in between {min} and {max}"
+            )),
+            // All digits are valid, so the number is too big to even fit a i128
+            None if literal.digits.chars().all(|char| char.is_digit(literal.radix)) && !literal.digits.is_empty() => Err(format!(
+                "The literal {value} is out of range for type {type_name}, expected a value in between {min} and {max}"
+            )),
+            None => Err(format!("Expected type value {type_name} but got value {value}")),
         }
     }
 }
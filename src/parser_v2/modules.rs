@@ -0,0 +1,321 @@
+//! Loads a program that is split over more than one file. Every file is a module, `import
+//! "math.zon"` loads the file relative to the file that imports it and `math::sqrt(2)` calls a
+//! `pub fn` of it. A module uses its own structs and enums by their name and the `pub struct` and
+//! `pub enum` declarations of the modules it imports with the name of the module in front of
+//! them, `math::Point`. Two modules can declare a type with the same name.
+use std::collections::{HashMap, HashSet};
+use std::fmt::Display;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use super::ast::{Ast, Import, NodeTypes};
+use super::parser::errors::{ParseError, ParseErrorKind};
+use super::parser::Parser;
+use crate::zon_parser::lexer::{LexDiagnostic, Lexer, Tokenizer};
+use crate::zon_parser::span::Span;
+
+/// [`SourceFile`]
+/// A file of the program, a span points into the file with the same `file_id` as its index
+#[derive(Debug)]
+pub struct SourceFile {
+    pub path: PathBuf,
+    pub source: String,
+}
+
+/// [`SourceModule`]
+/// The parsed file of a module
+///
+/// **name** The name of the module, the root file is the module `main`
+/// **prefix** Put in front of the names of the functions and globals of the module, so modules
+/// can have functions with the same name. The root module has no prefix
+/// **imports** The modules this module imports by their name, the index of the module in [`Program::modules`]
+#[derive(Debug)]
+pub struct SourceModule {
+    pub file_id: usize,
+    pub name: String,
+    pub prefix: String,
+    pub ast: Ast,
+    pub imports: HashMap<String, usize>,
+}
+
+impl SourceModule {
+    /// If the module declares a struct or enum with the name, if it is pub and what kind of type it is
+    fn find_type(&self, name: &str) -> Option<(bool, &'static str)> {
+        self.ast.body.iter().find_map(|item| match &item.node_type {
+            NodeTypes::Struct(declaration) if declaration.ident.name == name => Some((declaration.is_pub, "struct")),
+            NodeTypes::Enum(declaration) if declaration.ident.name == name => Some((declaration.is_pub, "enum")),
+            _ => None,
+        })
+    }
+}
+
+/// [`Program`]
+/// All the modules of a program, a module comes after every module it imports so the root file
+/// is the last module
+#[derive(Debug)]
+pub struct Program {
+    pub files: Vec<SourceFile>,
+    pub modules: Vec<SourceModule>,
+}
+
+/// [`ModuleError`]
+/// The reasons loading a program can fail
+#[derive(Debug)]
+pub enum ModuleError {
+    /// The root file of the program couldn't be read
+    Read { path: PathBuf, error: io::Error },
+    /// The lexer found invalid tokens in a file
+    Lex { path: PathBuf, diagnostics: Vec<LexDiagnostic> },
+    /// The files don't parse, or a import or path doesn't resolve. The span of every error points
+    /// into the file with its `file_id`
+    Parse { files: Vec<SourceFile>, errors: Vec<ParseError> },
+}
+
+impl ModuleError {
+    /// Renders every error, a parse error is rendered with the line of the file it points at
+    pub fn render(&self) -> String {
+        match self {
+            Self::Parse { files, errors } => errors
+                .iter()
+                .map(|error| match files.get(error.span.file_id) {
+                    Some(file) => error.render_in_file(&file.source, &file.path),
+                    None => error.to_string(),
+                })
+                .collect::<Vec<_>>()
+                .join("\n"),
+            error => error.to_string(),
+        }
+    }
+}
+
+impl Display for ModuleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Read { path, error } => write!(f, "Couldn't read {}: {error}", path.display()),
+            Self::Lex { path, diagnostics } => {
+                let diagnostics: Vec<String> = diagnostics.iter().map(|diagnostic| diagnostic.to_string()).collect();
+                write!(f, "In {}:\n{}", path.display(), diagnostics.join("\n"))
+            }
+            Self::Parse { errors, .. } => {
+                let errors: Vec<String> = errors.iter().map(|error| error.to_string()).collect();
+                write!(f, "{}", errors.join("\n"))
+            }
+        }
+    }
+}
+
+impl Program {
+    /// Loads the file at `path` and every file it imports
+    pub fn load(path: impl AsRef<Path>) -> Result<Program, ModuleError> {
+        let path = path.as_ref().to_path_buf();
+        let source = fs::read_to_string(&path).map_err(|error| ModuleError::Read { path: path.clone(), error })?;
+        Self::from_source(path, source)
+    }
+
+    /// Loads a program from the source of its root file, the imports are relative to `path`
+    pub fn from_source(path: impl Into<PathBuf>, source: String) -> Result<Program, ModuleError> {
+        let mut loader = Loader::default();
+        let path = path.into();
+        let key = fs::canonicalize(&path).unwrap_or_else(|_| path.clone());
+        loader.load_module(path, key, source, &mut Vec::new())?;
+        loader.check_type_uses();
+        if !loader.errors.is_empty() {
+            return Err(ModuleError::Parse { files: loader.files, errors: loader.errors });
+        }
+        Ok(Program { files: loader.files, modules: loader.modules })
+    }
+}
+
+/// A file that is being loaded, the files that are loading form the chain of imports to the
+/// current file
+struct Loading {
+    key: PathBuf,
+    path: PathBuf,
+}
+
+#[derive(Default)]
+struct Loader {
+    files: Vec<SourceFile>,
+    modules: Vec<SourceModule>,
+    /// The index of every module that is loaded by the canonical path of its file
+    loaded: HashMap<PathBuf, usize>,
+    prefixes: HashSet<String>,
+    errors: Vec<ParseError>,
+}
+
+impl Loader {
+    /// Loads a imported file, None if the file couldn't be loaded and the reason is in the errors
+    fn load_import(&mut self, import: &Import, span: Span, importer: &Path, stack: &mut Vec<Loading>) -> Result<Option<usize>, ModuleError> {
+        let path = importer.parent().unwrap_or(Path::new("")).join(&import.path);
+        let source = fs::canonicalize(&path).and_then(|key| Ok((key, fs::read_to_string(&path)?)));
+        let (key, source) = match source {
+            Ok(loaded) => loaded,
+            Err(error) => {
+                self.errors.push(
+                    ParseError::new(ParseErrorKind::UnresolvedImport, format!("Couldn't read {}: {error}", path.display()), span)
+                        .help(format!("the path of a import is relative to the folder of {}", importer.display())),
+                );
+                return Ok(None);
+            }
+        };
+
+        if let Some(start) = stack.iter().position(|loading| loading.key == key) {
+            let mut chain: Vec<String> = stack[start..].iter().map(|loading| loading.path.display().to_string()).collect();
+            chain.push(chain[0].clone());
+            self.errors.push(
+                ParseError::new(ParseErrorKind::ImportCycle, format!("{} imports itself through its imports", chain[0]), span)
+                    .help(format!("the imports form a cycle: {}", chain.join(" -> "))),
+            );
+            return Ok(None);
+        }
+        if let Some(index) = self.loaded.get(&key) {
+            return Ok(Some(*index));
+        }
+        self.load_module(path, key, source, stack)
+    }
+
+    /// Parses a file, loads the files it imports and checks its paths into other modules
+    fn load_module(&mut self, path: PathBuf, key: PathBuf, source: String, stack: &mut Vec<Loading>) -> Result<Option<usize>, ModuleError> {
+        let file_id = self.files.len();
+        let parsed = {
+            let mut tokenizer = Tokenizer::new(&source).with_file_id(file_id);
+            let (tokens, diagnostics) = Tokenizer::lex_with_diagnostics(&mut tokenizer);
+            if !diagnostics.is_empty() {
+                return Err(ModuleError::Lex { path, diagnostics });
+            }
+            Parser::new(&tokens).parse()
+        };
+        self.files.push(SourceFile { path: path.clone(), source });
+        let ast = match parsed {
+            Ok(ast) => ast,
+            Err(errors) => {
+                self.errors.extend(errors);
+                return Ok(None);
+            }
+        };
+
+        stack.push(Loading { key: key.clone(), path: path.clone() });
+        // A path into a import that failed to load isn't checked, the import already has a error
+        let mut imports = HashMap::new();
+        let mut attempted = HashSet::new();
+        for node in &ast.body {
+            let NodeTypes::Import(import) = &node.node_type else { continue };
+            if !attempted.insert(import.module.name.clone()) {
+                self.errors.push(
+                    ParseError::new(ParseErrorKind::DuplicateName, format!("There already is a module called {} imported", import.module.name), node.span)
+                        .help(format!("the module of {} is named after its file, rename one of the files", import.path)),
+                );
+                continue;
+            }
+            if let Some(index) = self.load_import(import, node.span, &path, stack)? {
+                imports.insert(import.module.name.clone(), index);
+            }
+        }
+        stack.pop();
+
+        for module_path in &ast.module_paths {
+            let Some(index) = imports.get(&module_path.module.name) else {
+                if !attempted.contains(&module_path.module.name) {
+                    self.errors.push(
+                        ParseError::new(ParseErrorKind::UnresolvedImport, format!("There is no module called {} imported", module_path.module.name), module_path.span)
+                            .help(format!("import the file of the module first, `import \"{}.zon\"`", module_path.module.name)),
+                    );
+                }
+                continue;
+            };
+            let module = &self.modules[*index];
+            let function = module.ast.body.iter().find_map(|item| match &item.node_type {
+                NodeTypes::Function(function) if function.ident == module_path.item => Some(function),
+                _ => None,
+            });
+            let name = format!("{}::{}", module_path.module.name, module_path.item.name);
+            match function {
+                Some(function) if function.is_pub => {}
+                Some(_) => self.errors.push(
+                    ParseError::new(ParseErrorKind::PrivateItem, format!("The function {name} isn't pub"), module_path.span)
+                        .help(format!("declare it as `pub fn {}` to call it from other modules", module_path.item.name)),
+                ),
+                None => self.errors.push(ParseError::new(
+                    ParseErrorKind::UnresolvedImport,
+                    format!("There is no function called {} in the module {}", module_path.item.name, module_path.module.name),
+                    module_path.span,
+                )),
+            }
+        }
+
+        let name = match stack.is_empty() {
+            true => "main".to_string(),
+            false => path.file_stem().map(|stem| stem.to_string_lossy().to_string()).unwrap_or_default(),
+        };
+        let prefix = self.unique_prefix(&name, stack.is_empty());
+        self.modules.push(SourceModule { file_id, name, prefix, ast, imports });
+        self.loaded.insert(key, self.modules.len() - 1);
+        Ok(Some(self.modules.len() - 1))
+    }
+
+    /// Checks every struct and enum a module uses. A name on its own is a type of the module
+    /// itself, `math::Point` is a pub type of the imported module math. A path into a module that
+    /// isn't imported, `nope::f()`, parses as a enum variant and ends up here as a type that isn't
+    /// declared anywhere
+    fn check_type_uses(&mut self) {
+        let mut errors = Vec::new();
+        for module in &self.modules {
+            // Only the first use of every type gets a error
+            let mut checked = HashSet::new();
+            for type_use in &module.ast.type_uses {
+                let name = &type_use.ident.name;
+                if !checked.insert((type_use.module.as_ref().map(|path| &path.name), name)) {
+                    continue;
+                }
+                let error = match &type_use.module {
+                    Some(path) => {
+                        // A import that failed to load already has a error
+                        let Some(&owner) = module.imports.get(&path.name) else { continue };
+                        match self.modules[owner].find_type(name) {
+                            Some((true, _)) => continue,
+                            Some((false, kind)) => ParseError::new(ParseErrorKind::PrivateItem, format!("The {kind} {name} of the module {} isn't pub", path.name), type_use.span)
+                                .help(format!("declare it as `pub {kind} {name}` to use it from other modules")),
+                            None => ParseError::new(ParseErrorKind::UnresolvedImport, format!("There is no struct or enum called {name} in the module {}", path.name), type_use.span),
+                        }
+                    }
+                    None if module.find_type(name).is_some() => continue,
+                    None => self.undeclared_type(module, name, type_use.span),
+                };
+                errors.push(error);
+            }
+        }
+        self.errors.extend(errors);
+    }
+
+    /// The error of a name of a type that the module doesn't declare itself, a type of another
+    /// module needs the name of the module in front of it
+    fn undeclared_type(&self, module: &SourceModule, name: &str, span: Span) -> ParseError {
+        let imported = module.imports.iter().find_map(|(path, &index)| Some((path, self.modules[index].find_type(name)?)));
+        if let Some((path, (_, kind))) = imported {
+            return ParseError::new(ParseErrorKind::UnresolvedImport, format!("The {kind} {name} is declared in the module {path}, not in this module"), span)
+                .help(format!("use it with the name of its module, `{path}::{name}`"));
+        }
+        match self.modules.iter().find_map(|owner| Some((owner, owner.find_type(name)?))) {
+            Some((owner, (_, kind))) => ParseError::new(ParseErrorKind::UnresolvedImport, format!("The {kind} {name} is declared in the module {}, which isn't imported", owner.name), span)
+                .help(format!("import the file of the module {} to use its pub types", owner.name)),
+            None => ParseError::new(ParseErrorKind::UnresolvedImport, format!("There is no struct, enum or module called {name}"), span)
+                .help(format!("a module has to be imported before it can be used, `import \"{name}.zon\"`")),
+        }
+    }
+
+    /// The prefix of a module, two files with the same name in different folders get a number
+    /// after their name
+    fn unique_prefix(&mut self, name: &str, is_root: bool) -> String {
+        if is_root {
+            return String::new();
+        }
+        let mut prefix = format!("{name}::");
+        let mut count = 1;
+        while !self.prefixes.insert(prefix.clone()) {
+            prefix = format!("{name}.{count}::");
+            count += 1;
+        }
+        prefix
+    }
+}
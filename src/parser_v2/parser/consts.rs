@@ -0,0 +1,96 @@
+//! Parses `const` declarations, the value of a const is evaluated while parsing so it can be
+//! used where the parser needs a number, like the size of a array `i32[SIZE]`
+use std::collections::HashMap;
+
+use super::{errors::ParseErrorKind, ParseResult, Parser};
+use crate::parser_v2::ast::{
+    const_eval::ConstEval, Ident, Node, NodeTypes, Types, Value, Variable,
+};
+use crate::zon_parser::lexer::{Keywords, Operator, Tokens};
+
+impl Parser<'_, '_> {
+    /// Parses a const, starting after the const keyword. The node holds the value the const
+    /// evaluated to instead of its expression
+    ///
+    /// # Example
+    ///
+    /// `const SIZE: i32 = 16 * 4`
+    pub fn parse_const_expr(&mut self) -> ParseResult<Node> {
+        let const_token = self.assert_prev_token();
+        assert!(const_token.token_type == Tokens::Kw(Keywords::Const));
+
+        let ident = self.parse_next_ident_expr()?;
+        if self.get_const(&ident.name).is_some() {
+            return Err(self.shadows_const(&ident).into());
+        }
+        if !self.consume_if_next(Tokens::Colon) {
+            return Err(self.expected_type_seperator().into());
+        }
+        let const_type = self.parse_type_expr()?;
+        if const_type.is_array || const_type.is_pointer || !matches!(const_type.r#type, Types::I8 | Types::U8 | Types::I32 | Types::Char) {
+            return Err(self.expected_const_type().into());
+        }
+        if !self.consume_if_next(Tokens::Op(Operator::Eq)) {
+            return Err(self.expected_assign_token().into());
+        }
+
+        let value_node = self.parse_value_expr(&const_type)?;
+        let evaluated = ConstEval::new(&self.items, &self.consts)
+            .eval_node(&value_node)
+            .and_then(|value| Ok((value, ConstEval::to_type_value(value, &const_type)?)));
+        let (value, literal) = match evaluated {
+            Ok(evaluated) => evaluated,
+            Err(reason) => return Err(self.not_constant(&ident, reason, value_node.span).into()),
+        };
+        if let Some(scope) = self.consts.last_mut() {
+            scope.insert(ident.name.clone(), value);
+        }
+
+        let span = const_token.span.to(value_node.span);
+        let mut node = Node::new(NodeTypes::Const(Variable { ident, var_type: const_type }), const_token.line, span);
+        let literal = Value::new(literal, false, value_node.span);
+        node.right = Some(Box::new(Node::new(NodeTypes::Value(literal), value_node.line, value_node.span)));
+        Ok(node)
+    }
+
+    /// The value of a const in the current block or a block around it
+    pub(super) fn get_const(&self, name: &str) -> Option<i64> {
+        self.consts.iter().rev().find_map(|scope| scope.get(name)).copied()
+    }
+
+    /// Runs `parse` with a new scope for consts, the consts declared in it are gone after
+    pub(super) fn in_const_scope<T>(&mut self, parse: impl FnOnce(&mut Self) -> ParseResult<T>) -> ParseResult<T> {
+        self.consts.push(HashMap::new());
+        let result = parse(self);
+        self.consts.pop();
+        result
+    }
+
+    /// A variable or paramater can't have the name of a const, the const would be used in array
+    /// sizes while the variable is used everywhere else
+    pub(super) fn expect_not_const(&self, ident: &Ident) -> ParseResult<()> {
+        match self.get_const(&ident.name) {
+            Some(_) => Err(self.shadows_const(ident).into()),
+            None => Ok(()),
+        }
+    }
+
+    /// Parses the size of a array type, a number or a const, `[16]` or `[SIZE]`
+    pub(super) fn parse_array_size(&mut self) -> ParseResult<u32> {
+        if self.consume_if_next(Tokens::Number) {
+            let size = self.assert_prev_token();
+            return Ok(Types::I32.convert_integer(&size.value).map_err(|error| self.invalid_literal(error))? as u32);
+        }
+        if !self.consume_if_next(Tokens::Identifier) {
+            return Err(self.expected_array_size().into());
+        }
+        let name = self.assert_prev_token().value.to_string();
+        match self.get_const(&name) {
+            Some(size) if size >= 0 && size <= i32::MAX as i64 => Ok(size as u32),
+            Some(size) => Err(self.invalid_literal(format!("The size of a array can't be {size}, the const {name} has to be positive")).into()),
+            None => Err(self
+                .error_at_prev(ParseErrorKind::InvalidArrayType, format!("There is no const called {name} for the size of the array"))
+                .expected("a number or a const").into()),
+        }
+    }
+}
@@ -0,0 +1,73 @@
+use crate::parser_v2::ast::{Enum, Ident, TypeValues, Variant};
+use crate::parser_v2::parser::errors::{ParseError, ParseErrorKind};
+use crate::parser_v2::parser::ParseResult;
+use crate::parser_v2::parser::Parser;
+use crate::zon_parser::lexer::Tokens;
+
+impl Parser<'_, '_> {
+    /// Parses a enum declaration, starting after the enum keyword, the variants can be separated
+    /// by commas
+    ///
+    /// # Example
+    ///
+    /// enum `->starts here` Shape {
+    ///     Circle(i32),
+    ///     Rect(i32, i32),
+    ///     Empty,
+    /// }
+    pub fn parse_enum_expr(&mut self) -> ParseResult<Enum> {
+        let ident = self.parse_next_ident_expr()?;
+        if !self.consume_if_next(Tokens::OpenCurlyBracket) {
+            return Err(self.enum_expected_body(&ident).into());
+        }
+
+        let mut variants: Vec<Variant> = Vec::new();
+        loop {
+            while self.consume_if_next(Tokens::Comment) {}
+            if self.consume_if_next(Tokens::CloseCurlyBracket) {
+                return Ok(Enum { ident, variants, is_pub: false });
+            }
+            if self.tokens.get(self.pos).is_none() {
+                return Err(self.expected_end_expr("enum", "}").into());
+            }
+
+            let variant = self.parse_next_ident_expr()?;
+            if variants.iter().any(|declared| declared.ident == variant) {
+                return Err(self.enum_duplicate_variant(&ident, &variant).into());
+            }
+            let mut fields = Vec::new();
+            if self.consume_if_next(Tokens::OpenBrace) {
+                while !self.consume_if_next(Tokens::CloseBrace) {
+                    fields.push(self.parse_type_expr()?);
+                    if !self.consume_if_next(Tokens::Comma) && !self.next_is(Tokens::CloseBrace) {
+                        return Err(self.expected_end_expr("payload of the variant", ")").into());
+                    }
+                }
+            }
+            variants.push(Variant { ident: variant, fields });
+            self.consume_if_next(Tokens::Comma);
+        }
+    }
+
+    /// Parses the variant of a enum after the name of the enum, `::Circle(5)`, the payload gets
+    /// parsed like the arguments of a function call
+    pub fn parse_enum_variant_value(&mut self, enum_ident: Ident) -> ParseResult<TypeValues> {
+        assert!(self.consume_if_next(Tokens::DoubleColon));
+        let variant = self.parse_next_ident_expr()?;
+        let payload = if self.next_is(Tokens::OpenBrace) {
+            self.parse_args_expr()?
+        } else {
+            Vec::new()
+        };
+        Ok(TypeValues::EnumVariant(enum_ident, variant, payload))
+    }
+
+    fn enum_expected_body(&self, ident: &Ident) -> ParseError {
+        self.error_at_next(ParseErrorKind::ExpectedBody, format!("Expected the variants of enum {} in between '{{' and '}}'", ident.name))
+            .expected("'{'")
+    }
+
+    fn enum_duplicate_variant(&self, ident: &Ident, variant: &Ident) -> ParseError {
+        self.error_at_prev(ParseErrorKind::DuplicateName, format!("The variant {} of enum {} is declared more than once", variant.name, ident.name))
+    }
+}
@@ -1,131 +1,390 @@
+use std::fmt::Display;
+use std::path::Path;
+
 use super::Parser;
+use crate::parser_v2::ast::Ident;
+use crate::zon_parser::span::Span;
+
+/// [`ParseErrorKind`]
+/// The kind of a [`ParseError`], every kind has a code that stays the same between versions so
+/// tests and tools can match on it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// A type was expected, `let a: = 1`
+    ExpectedType,
+    /// A identifier was expected, `fn () void`
+    ExpectedIdent,
+    /// A specific token was expected, like the ':' in `let a i32 = 1`
+    ExpectedToken,
+    /// A list, block or group was never closed, `(a + 1`
+    UnclosedDelimiter,
+    /// A token that can't be used at this place
+    UnexpectedToken,
+    /// A value that doesn't have the type it is expected to have
+    MismatchedType,
+    /// A number, char or string literal that isn't valid for its type, `let a: u8 = 256`
+    InvalidLiteral,
+    /// A function, struct, enum or match without its body in between '{' and '}'
+    ExpectedBody,
+    /// A array type without a size or generic type, `let a: i32[] = [1]`
+    InvalidArrayType,
+    /// The value of a block that isn't the last node of the block, `{ 1 let a: i32 = 2 }`
+    BlockValueNotLast,
+    /// A field or variant that is declared more than once
+    DuplicateName,
+    /// A pattern of a match arm that isn't valid
+    InvalidPattern,
+    /// The value of a const that can't be evaluated at compile time, `const A: i32 = getchar()`
+    NotConstant,
+    /// A assignment to a const, `SIZE += 1`
+    AssignToConst,
+    /// A import of a file that can't be read, or a path to a function a module doesn't have
+    UnresolvedImport,
+    /// A module that imports itself through the modules it imports
+    ImportCycle,
+    /// A path to a function of a other module that isn't pub, `math::helper`
+    PrivateItem,
+}
+
+impl ParseErrorKind {
+    /// The code of the error, `E0001`
+    pub fn code(&self) -> &'static str {
+        match self {
+            Self::ExpectedType => "E0001",
+            Self::ExpectedIdent => "E0002",
+            Self::ExpectedToken => "E0003",
+            Self::UnclosedDelimiter => "E0004",
+            Self::UnexpectedToken => "E0005",
+            Self::MismatchedType => "E0006",
+            Self::InvalidLiteral => "E0007",
+            Self::ExpectedBody => "E0008",
+            Self::InvalidArrayType => "E0009",
+            Self::BlockValueNotLast => "E0010",
+            Self::DuplicateName => "E0011",
+            Self::InvalidPattern => "E0012",
+            Self::NotConstant => "E0013",
+            Self::AssignToConst => "E0014",
+            Self::UnresolvedImport => "E0015",
+            Self::ImportCycle => "E0016",
+            Self::PrivateItem => "E0017",
+        }
+    }
+}
+
+/// [`ParseError`]
+/// A syntax error found by the parser
+///
+/// **kind** the kind of error, holds the code of the error
+/// **message** what went wrong, without the location
+/// **span** the location of the token the error is about
+/// **expected** what the parser expected to find, `':'` or `a type`
+/// **found** the token the parser found instead, or `end of file`
+/// **help** notes on how to fix the error
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub kind: ParseErrorKind,
+    pub message: String,
+    pub span: Span,
+    pub expected: Option<String>,
+    pub found: Option<String>,
+    pub help: Vec<String>,
+}
+
+impl ParseError {
+    pub fn new(kind: ParseErrorKind, message: impl Into<String>, span: Span) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            span,
+            expected: None,
+            found: None,
+            help: Vec::new(),
+        }
+    }
+
+    pub fn expected(mut self, expected: impl Into<String>) -> Self {
+        self.expected = Some(expected.into());
+        self
+    }
+
+    pub fn found(mut self, found: impl Into<String>) -> Self {
+        self.found = Some(found.into());
+        self
+    }
+
+    pub fn help(mut self, help: impl Into<String>) -> Self {
+        self.help.push(help.into());
+        self
+    }
+
+    pub fn code(&self) -> &'static str {
+        self.kind.code()
+    }
+
+    /// Renders the error with the line of source it points at, like rustc does
+    ///
+    /// # Example
+    ///
+    /// ```text
+    /// error[E0001]: Expected a type after ':'
+    ///  --> line 1, column 8
+    ///   |
+    /// 1 | let a: = 1
+    ///   |        ^ expected a type, found '='
+    /// ```
+    pub fn render(&self, source: &str) -> String {
+        let location = format!("line {}, column {}", self.span.line.max(1), self.span.column.max(1));
+        self.render_at(source, &location)
+    }
+
+    /// Renders the error like [`ParseError::render`], with the file the error is in, for a
+    /// program that has more than one file
+    ///
+    /// ```text
+    ///  --> lib/math.zon:1:8
+    /// ```
+    pub fn render_in_file(&self, source: &str, path: &Path) -> String {
+        let location = format!("{}:{}:{}", path.display(), self.span.line.max(1), self.span.column.max(1));
+        self.render_at(source, &location)
+    }
+
+    fn render_at(&self, source: &str, location: &str) -> String {
+        let line = self.span.line.max(1);
+        let column = self.span.column.max(1);
+        let line_text = source.lines().nth(line - 1).unwrap_or_default();
+        let gutter = " ".repeat(line.to_string().len());
+
+        // Tabs are kept in front of the carets so they line up with the source line
+        let indent: String = line_text
+            .chars()
+            .take(column - 1)
+            .map(|char| if char == '\t' { '\t' } else { ' ' })
+            .collect();
+        let rest_of_line = line_text.chars().count().saturating_sub(column - 1);
+        let width = self
+            .span
+            .source_text(source)
+            .map(|text| text.lines().next().unwrap_or_default().chars().count())
+            .unwrap_or_default()
+            .min(rest_of_line)
+            .max(1);
+
+        let label = match (&self.expected, &self.found) {
+            (Some(expected), Some(found)) => format!(" expected {expected}, found {found}"),
+            (Some(expected), None) => format!(" expected {expected}"),
+            (None, Some(found)) => format!(" found {found}"),
+            (None, None) => String::new(),
+        };
+
+        let mut rendered = format!("error[{}]: {}\n", self.code(), self.message);
+        rendered.push_str(&format!("{gutter}--> {location}\n"));
+        rendered.push_str(&format!("{gutter} |\n"));
+        rendered.push_str(&format!("{line} | {line_text}\n"));
+        rendered.push_str(&format!("{gutter} | {indent}{}{label}\n", "^".repeat(width)));
+        for help in &self.help {
+            rendered.push_str(&format!("{gutter} = help: {help}\n"));
+        }
+        rendered
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "[Parse Error {}] {} on line {}, column {}",
+            self.code(),
+            self.message,
+            self.span.line,
+            self.span.column
+        )
+    }
+}
+
+impl Parser<'_, '_> {
+    /// A error about the token that was consumed last, that token is the one that didn't fit
+    pub fn error_at_prev(&self, kind: ParseErrorKind, message: impl Into<String>) -> ParseError {
+        let Some(token) = self.pos.checked_sub(1).and_then(|pos| self.tokens.get(pos)) else {
+            return ParseError::new(kind, message, Span::default()).found("end of file");
+        };
+        ParseError::new(kind, message, token.span).found(format!("'{}'", token.value))
+    }
+
+    /// A error about the next token, that token is not what the parser expected. At the end of
+    /// the source the error points right after the last token
+    pub fn error_at_next(&self, kind: ParseErrorKind, message: impl Into<String>) -> ParseError {
+        if let Some(token) = self.tokens.get(self.pos) {
+            return ParseError::new(kind, message, token.span).found(format!("'{}'", token.value));
+        }
+        let span = match self.tokens.last() {
+            Some(last) => Span {
+                start: last.span.end,
+                column: last.span.column + last.span.len(),
+                ..last.span
+            },
+            None => Span::default(),
+        };
+        ParseError::new(kind, message, Span { end: span.start, ..span }).found("end of file")
+    }
+
+    /// A literal that couldn't be converted into its type, the message comes from the conversion
+    pub fn invalid_literal(&self, message: String) -> ParseError {
+        self.error_at_prev(ParseErrorKind::InvalidLiteral, message)
+    }
+
+    pub fn expected_type(&self) -> ParseError {
+        self.error_at_prev(ParseErrorKind::ExpectedType, "Expected a type after ':'")
+            .expected("a type")
+            .help("the types are i8, u8, i32, f32, char, string, void, array or the name of a struct or enum")
+    }
+
+    pub fn expected_array_value_comma(&self) -> ParseError {
+        self.error_at_prev(ParseErrorKind::UnexpectedToken, "Expected an array value, but got a comma")
+            .expected("a array value")
+            .help("consider putting a comma in between array values, `[1, 2]`")
+    }
+
+    pub fn expected_params_openbrace(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::ExpectedToken, "Expected a open brace '(' to the paramaters")
+            .expected("'('")
+    }
+
+    pub fn expected_array_generic(&self) -> ParseError {
+        self.error_at_prev(ParseErrorKind::InvalidArrayType, "Expected an array generic type: array<T>, but got array without generic type")
+            .expected("a array type")
+    }
+
+    pub fn expected_end_expr(&self, to: &str, end: &str) -> ParseError {
+        self.error_at_next(ParseErrorKind::UnclosedDelimiter, format!("Expected a end to {to} '{end}'"))
+            .expected(format!("'{end}'"))
+    }
+
+    pub fn expected_block_value_end(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::BlockValueNotLast, "Expected the value of a block to be followed by the end of the block '}'")
+            .expected("'}'")
+            .help("only the last node of a block can be a value")
+    }
+
+    pub fn comma_without_type_generic(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::ExpectedToken, "Expected a comma after a type <T, T>")
+            .expected("','")
+    }
+
+    pub fn invalid_token_in_expr(&self, expr: &str, expected: &str) -> ParseError {
+        self.error_at_prev(ParseErrorKind::UnexpectedToken, format!("Found a invalid token while parsing {expr}"))
+            .expected(format!("a {expected}"))
+    }
+
+    pub fn expected_assign_token(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::ExpectedToken, "Expected assignment token")
+            .expected("'=' or a assignment operator like '+='")
+    }
+
+    pub fn expected_ident(&self) -> ParseError {
+        self.error_at_prev(ParseErrorKind::ExpectedIdent, "Expected a variable identifier")
+            .expected("a identifier")
+    }
+
+    pub fn expected_type_seperator(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::ExpectedToken, "Expected a type seperator ':'")
+            .expected("':'")
+            .help("a type is given after a ':', `a: i32`")
+    }
+
+    pub fn expected_value_seprator(&self) -> ParseError {
+        self.error_at_prev(ParseErrorKind::ExpectedToken, "Expected a value seperator ','")
+            .expected("','")
+    }
+
+    pub fn invalid_expected_type(&self, type_expected: &str, value_received: &str) -> ParseError {
+        self.error_at_prev(ParseErrorKind::MismatchedType, format!("Expected a type of {type_expected} but got {value_received}"))
+            .expected(format!("a {type_expected}"))
+    }
+
+    pub fn expected_body_openbracket(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::ExpectedBody, "Expected a openbracket to the body '{'")
+            .expected("'{'")
+    }
+
+    pub fn not_supported_array_in_array(&self) -> ParseError {
+        self.error_at_prev(ParseErrorKind::InvalidArrayType, "Found '[' in a array, arrays in arrays are not supported")
+            .help("altough supporting arrays in arrays is planned it is currently not supported")
+    }
+
+    pub fn expected_array_size(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::InvalidArrayType, "Pleas specify the size of the array")
+            .expected("the size of the array")
+            .help("example: char[S]; where S is the Size")
+    }
+
+    pub fn invalid_top_level_token(&self) -> ParseError {
+        self.error_at_prev(ParseErrorKind::UnexpectedToken, "Found a token that can't be at the top level of a file")
+            .expected("a fn, pub fn, extern fn, import, let, const, struct or enum")
+    }
+
+    /// The value of a const couldn't be evaluated, `reason` says why
+    pub fn not_constant(&self, ident: &Ident, reason: String, span: Span) -> ParseError {
+        ParseError::new(ParseErrorKind::NotConstant, format!("The value of const {} can't be evaluated at compile time", ident.name), span)
+            .help(reason)
+    }
+
+    pub fn assign_to_const(&self, ident: &Ident) -> ParseError {
+        self.error_at_prev(ParseErrorKind::AssignToConst, format!("Can't assign to {}, it is a const", ident.name))
+            .help(format!("use `let` instead of `const` to declare {} if it has to change", ident.name))
+    }
+
+    /// A variable or paramater with the name of a const
+    pub fn shadows_const(&self, ident: &Ident) -> ParseError {
+        self.error_at_prev(ParseErrorKind::DuplicateName, format!("There already is a const called {}", ident.name))
+    }
+
+    pub fn expected_const_type(&self) -> ParseError {
+        self.error_at_prev(ParseErrorKind::ExpectedType, "Expected the type of a const to be a integer or char")
+            .expected("i8, u8, i32 or char")
+    }
+
+    pub fn expected_extern_fn(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::ExpectedToken, "Expected fn after extern, only functions can be extern")
+            .expected("'fn'")
+    }
+
+    pub fn extern_with_body(&self, ident: &Ident) -> ParseError {
+        self.error_at_next(ParseErrorKind::UnexpectedToken, format!("The extern fn {} can't have a body", ident.name))
+            .help("a extern fn is defined in C, remove the body or remove extern")
+    }
+
+    pub fn variadic_not_extern(&self) -> ParseError {
+        self.error_at_prev(ParseErrorKind::UnexpectedToken, "Only a extern fn can have variadic paramaters '...'")
+    }
+
+    pub fn variadic_not_last(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::ExpectedToken, "The variadic paramaters '...' must be the last paramater")
+            .expected("')'")
+    }
+
+    pub fn expected_import_path(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::ExpectedToken, "Expected the path of the file to import after import")
+            .expected("a path like \"math.zon\"")
+    }
+
+    pub fn invalid_module_name(&self, path: &str) -> ParseError {
+        self.error_at_prev(ParseErrorKind::InvalidLiteral, format!("The file {path} can't be imported, the name of the file is the name of the module"))
+            .help("rename the file so its name without extension is a identifier, like math.zon")
+    }
+
+    pub fn duplicate_import(&self, module: &Ident) -> ParseError {
+        self.error_at_prev(ParseErrorKind::DuplicateName, format!("There already is a module called {} imported", module.name))
+    }
+
+    pub fn expected_module_call(&self, module: &Ident, item: &Ident) -> ParseError {
+        self.error_at_next(ParseErrorKind::ExpectedToken, format!("Expected the arguments of the call to {}::{}", module.name, item.name))
+            .expected("'('")
+            .help("only the functions of a module can be used from other modules")
+    }
 
-impl Parser {
-    pub fn expected_type(&mut self) -> String {
-        let current = self.assert_prev_token();
-        let msg = format!(
-            "[Parse Error] Expected a type after ':' on line {}",
-            current.line
-        );
-        msg
-    }
-
-    pub fn expected_array_value_comma(&mut self) -> String {
-        let current = self.assert_prev_token();
-        let msg = format!(
-            "[Parse Error] Expected an array value, but got a comma, consider putting a comma in between array values, on line {}",
-            current.line
-        );
-        msg
-    }
-
-    pub fn expected_params_openbrace(&mut self) -> String {
-        let current = self.assert_prev_token();
-        let msg = format!(
-            "[Parse Error] Expected a open brace '(' to the paramaters on line {}",
-            current.line
-        );
-        msg
-    }
-
-    pub fn expected_array_generic(&mut self) -> String {
-        let current = self.assert_prev_token();
-        let msg = format!(
-            "[Parse Error] Expected an array generic type: array<T>, but got array without generic type  on line {}",
-            current.line
-        );
-        msg
-    }
-
-    pub fn expected_end_expr(&mut self, to: &str, end: &str) -> String {
-        self.pos -= 1;
-        let current = self.assert_prev_token();
-        let msg = format!(
-            "[Parse Error] Expected a end to {to} '{end}' on line {}",
-            current.line
-        );
-        msg
-    }
-
-    pub fn comma_without_type_generic(&mut self) -> String {
-        let current = self.assert_prev_token();
-        let msg = format!(
-            "[Parse Error] Expected a comma after a type <T, T> on line {}",
-            current.line
-        );
-        msg
-    }
-
-    pub fn invalid_token_in_expr(&mut self, expr: &str, expected: &str) -> String {
-        let current = self.assert_prev_token();
-        let msg = format!(
-            "[Parse Error] Found a invalid token while parsing {expr} on line {}, expected a {expected} got {}",
-            current.line, current.value
-        );
-        msg
-    }
-
-    pub fn expected_assign_token(&mut self) -> String {
-        let current = self.assert_prev_token();
-        let msg = format!(
-            "[Parse Error] Expected assignment token on line {}",
-            current.line
-        );
-        msg
-    }
-
-    pub fn expected_ident(&mut self) -> String {
-        let current = self.assert_prev_token();
-        let msg = format!(
-            "[Parse Error] Expected a variable identifier on line {}",
-            current.line
-        );
-        msg
-    }
-
-    pub fn expected_type_seperator(&mut self) -> String {
-        let current = self.assert_prev_token();
-        let msg = format!(
-            "[Parse Error] Expected a type seperator ':' on line {} for {}",
-            current.line, current.value
-        );
-        msg
-    }
-
-    pub fn expected_value_seprator(&mut self) -> String {
-        let current = self.assert_prev_token();
-        let msg = format!(
-            "[Parse Error] Expected a value seperator ',' on line {}",
-            current.line
-        );
-        msg
-    }
-
-    pub fn invalid_expected_type(&mut self, type_expected: &str, value_received: &str) -> String {
-        let msg =
-            format!("[parse error] Expected a type of {type_expected} but got {value_received}",);
-        msg
-    }
-
-    pub fn expected_body_openbracket(&mut self) -> String {
-        let current_line = self.assert_prev_token();
-        let msg =
-            format!("[parse error] Expected a openbracket to the body of a function '{}<fn_body>{}', line {} ", '{', '}', current_line.line);
-        msg
-    }
-
-    pub fn not_supported_array_in_array(&mut self) -> String {
-        let array_value = self.assert_prev_token();
-        let msg = format!("Altough supporting arrays in arrays is planned it is currently not supported, found ']' in array on line {}", array_value.line);
-        return msg;
-    }
-
-    pub fn expected_array_size(&mut self) -> String {
-        let array_value = self.assert_prev_token();
-        let msg = format!(
-            "Pleas specify the size of array on line: {}; example: char[S]; where S is the Size",
-            array_value.line
-        );
-        return msg;
+    pub fn expected_pub_item(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::UnexpectedToken, "Only a fn, struct or enum can be pub")
+            .expected("'fn', 'struct' or 'enum'")
+            .help("globals and consts can only be used in the module they are declared in")
     }
 }
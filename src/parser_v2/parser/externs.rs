@@ -0,0 +1,36 @@
+//! Parses `extern fn` declarations, functions that are defined in C and called from Zontanos
+use super::{ParseResult, Parser};
+use crate::parser_v2::ast::{ExternFunction, Node, NodeTypes};
+use crate::zon_parser::lexer::{Keywords, Tokens};
+
+impl Parser<'_, '_> {
+    /// Parses a extern function, starting after the extern keyword. A extern function has no
+    /// body and its paramaters can end with `...` to take any number of arguments
+    ///
+    /// # Example
+    ///
+    /// `extern fn printf(fmt: i8^, ...) i32;`
+    pub fn parse_extern_expr(&mut self) -> ParseResult<Node> {
+        let extern_token = self.assert_prev_token();
+        assert!(extern_token.token_type == Tokens::Kw(Keywords::Extern));
+
+        if !self.consume_if_next(Tokens::Kw(Keywords::Fn)) {
+            return Err(self.expected_extern_fn().into());
+        }
+        let ident = self.parse_next_ident_expr()?;
+        let (paramaters, is_variadic) = self.parse_params_with(true)?;
+        let returns = self.parse_type_expr()?;
+        if self.next_is(Tokens::OpenCurlyBracket) {
+            return Err(self.extern_with_body(&ident).into());
+        }
+
+        let function = ExternFunction {
+            ident,
+            paramaters,
+            returns,
+            is_variadic,
+        };
+        let span = extern_token.span.to(self.prev_span());
+        Ok(Node::new(NodeTypes::ExternFunction(function), extern_token.line, span))
+    }
+}
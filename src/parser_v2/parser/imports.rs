@@ -0,0 +1,142 @@
+//! Parses `import` and `pub`, the parts of a file that make it a module of a bigger program
+use std::collections::HashSet;
+use std::path::Path;
+
+use super::{ParseResult, Parser};
+use crate::parser_v2::ast::{FunctionCall, Ident, Import, ModulePath, Node, NodeTypes, TypeUse, Types};
+use crate::zon_parser::lexer::{Keywords, Lexer, Token, Tokenizer, Tokens};
+use crate::zon_parser::span::Span;
+
+/// The name of the module a file becomes, the name of the file without its extension.
+/// `lib/math.zon` becomes `math`, None if that name isn't a identifier
+pub fn module_name(path: &str) -> Option<String> {
+    let name = Path::new(path).file_stem()?.to_str()?;
+    let mut tokenizer = Tokenizer::new(name);
+    match &Tokenizer::lex(&mut tokenizer)[..] {
+        [token] if token.token_type == Tokens::Identifier && token.value == name => Some(name.to_string()),
+        _ => None,
+    }
+}
+
+/// The names of the modules imported anywhere in the file, a path can point into a module that
+/// is imported below it
+pub(super) fn imported_modules(tokens: &[Token]) -> HashSet<String> {
+    tokens
+        .windows(2)
+        .filter(|pair| pair[0].token_type == Tokens::Kw(Keywords::Import) && pair[1].token_type == Tokens::String)
+        .filter_map(|pair| module_name(&pair[1].value))
+        .collect()
+}
+
+impl Parser<'_, '_> {
+    /// Parses a import, starting after the import keyword
+    ///
+    /// # Example
+    ///
+    /// `import "math.zon"`
+    pub fn parse_import_expr(&mut self) -> ParseResult<Node> {
+        let import_token = self.assert_prev_token();
+        assert!(import_token.token_type == Tokens::Kw(Keywords::Import));
+
+        if !self.consume_if_next(Tokens::String) {
+            return Err(self.expected_import_path().into());
+        }
+        let path = self.assert_prev_token().value.to_string();
+        let Some(name) = module_name(&path) else {
+            return Err(self.invalid_module_name(&path).into());
+        };
+        let module = Ident { name };
+        let is_imported = self.items.iter().any(|item| matches!(&item.node_type, NodeTypes::Import(import) if import.module == module));
+        if is_imported {
+            return Err(self.duplicate_import(&module).into());
+        }
+
+        let span = import_token.span.to(self.prev_span());
+        Ok(Node::new(NodeTypes::Import(Import { path, module }), import_token.line, span))
+    }
+
+    /// Parses a item that starts with pub, a function, struct or enum can be pub
+    ///
+    /// # Example
+    ///
+    /// `pub fn sqrt(x: i32) i32 { .. }` or `pub struct Point { .. }`
+    pub fn parse_pub_expr(&mut self) -> ParseResult<Node> {
+        let pub_token = self.assert_prev_token();
+        assert!(pub_token.token_type == Tokens::Kw(Keywords::Pub));
+
+        let node_type = if self.consume_if_next(Tokens::Kw(Keywords::Fn)) {
+            let mut node = self.parse_fn_expr()?;
+            if let NodeTypes::Function(function) = &mut node.node_type {
+                function.is_pub = true;
+            }
+            node.node_type
+        } else if self.consume_if_next(Tokens::Kw(Keywords::Struct)) {
+            let mut parsed_struct = self.parse_struct_expr()?;
+            parsed_struct.is_pub = true;
+            NodeTypes::Struct(parsed_struct)
+        } else if self.consume_if_next(Tokens::Kw(Keywords::Enum)) {
+            let mut parsed_enum = self.parse_enum_expr()?;
+            parsed_enum.is_pub = true;
+            NodeTypes::Enum(parsed_enum)
+        } else {
+            return Err(self.expected_pub_item().into());
+        };
+        let span = pub_token.span.to(self.prev_span());
+        Ok(Node::new(node_type, pub_token.line, span))
+    }
+
+    /// If the identifier that was just consumed is a imported module and a path to a function
+    /// follows it, `math::sqrt`
+    pub(super) fn is_module_path(&self, name: &str) -> bool {
+        self.modules.contains(name) && self.next_is(Tokens::DoubleColon) && !self.is_module_type_path(name)
+    }
+
+    /// If the identifier that was just consumed is a imported module and a struct literal or enum
+    /// variant of the module follows it, `math::Point { x: 1 }` or `math::Shape::Empty`
+    pub(super) fn is_module_type_path(&self, name: &str) -> bool {
+        let token_at = |offset: usize| self.tokens.get(self.pos + offset).map(|token| &token.token_type);
+        let is_struct_literal = token_at(2) == Some(&Tokens::OpenCurlyBracket)
+            && token_at(3) == Some(&Tokens::Identifier)
+            && token_at(4) == Some(&Tokens::Colon);
+        self.modules.contains(name)
+            && token_at(0) == Some(&Tokens::DoubleColon)
+            && token_at(1) == Some(&Tokens::Identifier)
+            && (token_at(2) == Some(&Tokens::DoubleColon) || is_struct_literal)
+    }
+
+    /// Records a use of a struct or enum of the module itself by its name, so the loader of the
+    /// modules can check the module declares it
+    pub(super) fn use_type(&mut self, name: &str, span: Span) -> Types {
+        self.type_uses.push(TypeUse { module: None, ident: Ident { name: name.to_string() }, span });
+        Types::Named(name.to_string())
+    }
+
+    /// Parses the type after the name of a imported module, `::Point`, and records the use so
+    /// the loader of the modules can check the type is pub. Returns the name of the type with the
+    /// name of the module in front of it, `math::Point`
+    pub(super) fn parse_type_path(&mut self, module: Ident) -> ParseResult<Ident> {
+        let start = self.prev_span();
+        assert!(self.consume_if_next(Tokens::DoubleColon));
+        let item = self.parse_next_ident_expr()?;
+        let name = format!("{}::{}", module.name, item.name);
+        self.type_uses.push(TypeUse { module: Some(module), ident: item, span: start.to(self.prev_span()) });
+        Ok(Ident { name })
+    }
+
+    /// Parses the function of a call into a module after the name of the module, `::sqrt`. The
+    /// path is kept so the loader of the modules can check the function is pub
+    pub(super) fn parse_module_call(&mut self, module: Ident) -> ParseResult<FunctionCall> {
+        let start = self.prev_span();
+        assert!(self.consume_if_next(Tokens::DoubleColon));
+        let item = self.parse_next_ident_expr()?;
+        if !self.next_is(Tokens::OpenBrace) {
+            return Err(self.expected_module_call(&module, &item).into());
+        }
+        self.module_paths.push(ModulePath {
+            module: module.clone(),
+            item: item.clone(),
+            span: start.to(self.prev_span()),
+        });
+        Ok(FunctionCall { calls_to: item, module: Some(module) })
+    }
+}
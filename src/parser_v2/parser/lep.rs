@@ -1,22 +1,34 @@
 use crate::parser_v2::ast::{NodeTypes, Type, Value};
+use crate::parser_v2::parser::errors::{ParseError, ParseErrorKind};
 use crate::parser_v2::parser::ParseResult;
 use crate::parser_v2::parser::Parser;
-use crate::zon_parser::lexer::{Keywords, Operator, Tokens};
+use crate::zon_parser::lexer::{Keywords, Operator, Token, Tokens};
 
+/// A single case of a logical statement, the cases of a logical statement are separated by
+/// [`Statements::And`] and [`Statements::Or`]. '&&' binds tighter then '||'.
 #[derive(Debug, PartialEq)]
 pub enum Statements {
     Or,
     And,
     EqEq(Value, Value),
+    NotEq(Value, Value),
     More(Value, Value),
     Less(Value, Value),
     MoreEq(Value, Value),
     LessEq(Value, Value),
-    OrOr(Value, Value),
-    AndAnd(Value, Value),
     Atomic(Value),
+    /// Cases in between braces, `(a > b || c < d)`
+    Group(Vec<Statements>),
+    /// `!done` or `!(a > b)`
+    Not(Box<Statements>),
 }
 
+/// [`LogicalStatement`]
+/// An if statement, or an if expression when it's used as a value `let a: i32 = if b { 1 } else { 2 }`
+///
+/// **case** the cases that decide which block is taken
+/// **if_do** the block of the if
+/// **else_do** the else block, or the [`NodeTypes::LogicalStatement`] of an `else if`
 #[derive(Debug)]
 pub struct LogicalStatement {
     pub case: Vec<Statements>,
@@ -34,12 +46,17 @@ impl LogicalStatement {
     }
 }
 
-impl Parser {
+impl Parser<'_, '_> {
     pub fn lep_parse(&mut self, type_expected: &Type) -> ParseResult<LogicalStatement> {
         let statements = self.lep_parse_statements()?;
         let if_block = self.parse_block_expr(type_expected)?;
         let else_block = if self.consume_if_next(Tokens::Kw(Keywords::Else)) {
-            Some(NodeTypes::Block(self.parse_block_expr(type_expected)?.0))
+            if self.consume_if_next(Tokens::Kw(Keywords::If)) {
+                let else_if = self.lep_parse(type_expected)?;
+                Some(NodeTypes::LogicalStatement(Box::new(else_if)))
+            } else {
+                Some(NodeTypes::Block(self.parse_block_expr(type_expected)?.0))
+            }
         } else {
             None
         };
@@ -50,105 +67,151 @@ impl Parser {
         ))
     }
 
+    /// Parses the cases of a logical statement up until the '{' of the if block, the '{' doesn't
+    /// get consumed
     pub fn lep_parse_statements(&mut self) -> ParseResult<Vec<Statements>> {
-        let mut statements = Vec::new();
-        while let Some(_) = self.next() {
-            self.walk_back(1);
-            // We expect the first token to be that of a value
-            let value = self.parse_not_know_type_value()?;
-
-            // We expect there to be some operator | token next
-            let Some(operator) = self.next() else {
-                return Err(self.lep_parse_expected_op());
-            };
-
-            // Token is and operator, this could mean there is and '||' | '!='...
-            if let Tokens::Op(op) = operator.token_type {
-                match op {
-                    Operator::More => {
-                        let other_value = self.parse_not_know_type_value()?;
-                        statements.push(Statements::More(value, other_value));
-                    }
-                    Operator::Less => {
-                        let other_value = self.parse_not_know_type_value()?;
-                        statements.push(Statements::Less(value, other_value));
-                    }
-                    Operator::MoreEq => {
-                        let other_value = self.parse_not_know_type_value()?;
-                        statements.push(Statements::MoreEq(value, other_value));
-                    }
-                    Operator::LessEq => {
-                        let other_value = self.parse_not_know_type_value()?;
-                        statements.push(Statements::LessEq(value, other_value));
-                    }
-                    Operator::OrOr => {
-                        let other_value = self.parse_not_know_type_value()?;
-                        statements.push(Statements::OrOr(value, other_value));
-                    }
-                    Operator::AndAnd => {
-                        let other_value = self.parse_not_know_type_value()?;
-                        statements.push(Statements::AndAnd(value, other_value));
-                    }
-                    Operator::EqEq => {
-                        let other_value = self.parse_not_know_type_value()?;
-                        statements.push(Statements::EqEq(value, other_value));
-                    }
-                    _ => return Err(self.lep_unexpected_token()),
-                }
+        let statements = self.lep_parse_cases()?;
+        match self.tokens.get(self.pos) {
+            Some(token) if token.token_type == Tokens::OpenCurlyBracket => Ok(statements),
+            Some(_) => {
+                self.next();
+                Err(self.lep_unexpected_token().into())
+            }
+            None => Err(self.lep_expected_lep_or_end().into()),
+        }
+    }
+
+    /// Parses cases separated by '&&' and '||' until a token is found that doesn't continue them
+    fn lep_parse_cases(&mut self) -> ParseResult<Vec<Statements>> {
+        let mut statements = vec![self.lep_parse_statement()?];
+        loop {
+            if self.consume_if_next(Tokens::Op(Operator::AndAnd)) {
+                statements.push(Statements::And);
+            } else if self.consume_if_next(Tokens::Op(Operator::OrOr)) {
+                statements.push(Statements::Or);
             } else {
-                statements.push(Statements::Atomic(value));
-                self.walk_back(1);
-            };
-
-            if let Some(token_continue_op) = self.next() {
-                if token_continue_op.token_type == Tokens::OpenCurlyBracket {
-                    self.walk_back(1);
-                    return Ok(statements);
-                }
-                if let Tokens::Op(op) = token_continue_op.token_type {
-                    match op {
-                        Operator::AndAnd => {
-                            statements.push(Statements::And);
-                        }
-                        Operator::OrOr => {
-                            statements.push(Statements::Or);
-                        }
-                        _ => return Err(self.lep_unexpected_token()),
-                    }
-                } 
-                continue;
-            };
-            return Err(self.lep_expected_lep_or_end());
+                return Ok(statements);
+            }
+            statements.push(self.lep_parse_statement()?);
+        }
+    }
+
+    /// Parses a single case, a comparison, a single value, a group or a negated case
+    fn lep_parse_statement(&mut self) -> ParseResult<Statements> {
+        if self.consume_if_next(Tokens::Bang) {
+            let negated = self.lep_parse_negated()?;
+            return Ok(Statements::Not(Box::new(negated)));
+        }
+        if self.lep_next_is_group() {
+            return self.lep_parse_group();
+        }
+
+        // We expect the first token to be that of a value
+        let value = self.parse_expr_value()?;
+        let Some(Tokens::Op(op)) = self.tokens.get(self.pos).map(|token| &token.token_type) else {
+            return Ok(Statements::Atomic(value));
+        };
+        let op = op.clone();
+        if !Self::is_comparison(&op) {
+            return Ok(Statements::Atomic(value));
+        }
+        self.next();
+
+        let other_value = self.parse_expr_value()?;
+        let statement = match op {
+            Operator::More => Statements::More(value, other_value),
+            Operator::Less => Statements::Less(value, other_value),
+            Operator::MoreEq => Statements::MoreEq(value, other_value),
+            Operator::LessEq => Statements::LessEq(value, other_value),
+            Operator::EqEq => Statements::EqEq(value, other_value),
+            Operator::Nq => Statements::NotEq(value, other_value),
+            _ => unreachable!("is_comparison only accepts comparison operators"),
+        };
+        Ok(statement)
+    }
+
+    /// '!' only negates the case right after it, so `!done && ready` is `(!done) && ready`
+    fn lep_parse_negated(&mut self) -> ParseResult<Statements> {
+        if self.consume_if_next(Tokens::Bang) {
+            let negated = self.lep_parse_negated()?;
+            return Ok(Statements::Not(Box::new(negated)));
+        }
+        if self.lep_next_is_group() {
+            return self.lep_parse_group();
+        }
+        Ok(Statements::Atomic(self.parse_not_know_type_value()?))
+    }
+
+    /// Expects the next token to be the '(' of the group, consumes the closing ')'
+    fn lep_parse_group(&mut self) -> ParseResult<Statements> {
+        assert!(self.consume_if_next(Tokens::OpenBrace));
+        let statements = self.lep_parse_cases()?;
+        if !self.consume_if_next(Tokens::CloseBrace) {
+            return Err(self.expected_end_expr("logical statement", ")").into());
+        }
+        Ok(Statements::Group(statements))
+    }
+
+    /// A '(' can either start a group `(a > b || c)` or a math value `(a + 1) > b`, it is a
+    /// value if a comparison or math operator follows the matching ')'
+    fn lep_next_is_group(&self) -> bool {
+        let Some(token) = self.tokens.get(self.pos) else {
+            return false;
+        };
+        if token.token_type != Tokens::OpenBrace {
+            return false;
         }
-        Err(self.lep_expected_lep_or_end())
+
+        let mut depth = 0;
+        for (offset, token) in self.tokens[self.pos..].iter().enumerate() {
+            match token.token_type {
+                Tokens::OpenBrace => depth += 1,
+                Tokens::CloseBrace => depth -= 1,
+                _ => continue,
+            }
+            if depth == 0 {
+                let after = self.pos + offset + 1;
+                let is_comparison = match self.tokens.get(after) {
+                    Some(Token {
+                        token_type: Tokens::Op(op),
+                        ..
+                    }) => Self::is_comparison(op),
+                    _ => false,
+                };
+                return !is_comparison && self.math_operator_at(after).is_none();
+            }
+        }
+        // The group reports the missing ')'
+        true
+    }
+
+    fn is_comparison(op: &Operator) -> bool {
+        matches!(
+            op,
+            Operator::More
+                | Operator::Less
+                | Operator::MoreEq
+                | Operator::LessEq
+                | Operator::EqEq
+                | Operator::Nq
+        )
     }
 
-    fn lep_parse_expected_op(&mut self) -> String {
-        let prev = self.assert_prev_token();
-        let msg = format!("expected the next token to be and operator or atleast a close brace, if there is not more cases, found on line {}", prev.line);
-        msg
+    fn lep_parse_expected_op(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::UnexpectedToken, "Expected the next token to be a operator or a close brace, if there are no more cases")
+            .expected("a operator or ')'")
     }
 
-    fn lep_parse_expected_end(&mut self) -> String {
-        let prev = self.assert_prev_token();
-        let msg = format!(
-            "expected a end to the logical expression on line {}",
-            prev.line
-        );
-        msg
+    fn lep_parse_expected_end(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::UnclosedDelimiter, "Expected a end to the logical expression")
     }
 
-    fn lep_expected_lep_or_end(&mut self) -> String {
-        let prev = self.assert_prev_token();
-        let msg = format!(
-            "expected a end to the logical expression or a logical expression. on line {}",
-            prev.line
-        );
-        msg
+    fn lep_expected_lep_or_end(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::UnclosedDelimiter, "Expected a end to the logical expression or a logical expression")
+            .expected("a logical expression or '{'")
     }
 
-    fn lep_unexpected_token(&mut self) -> String {
-        let token = self.assert_prev_token();
-        format!("Got a token: {} that does not belong in the context of a logical expression. on line: {}", token.value, token.line)
+    fn lep_unexpected_token(&self) -> ParseError {
+        self.error_at_prev(ParseErrorKind::UnexpectedToken, "Found a token that does not belong in the context of a logical expression")
     }
 }
@@ -0,0 +1,101 @@
+use crate::parser_v2::ast::{Ident, Node, Type, Value};
+use crate::parser_v2::parser::lep::Statements;
+use crate::parser_v2::parser::errors::{ParseError, ParseErrorKind};
+use crate::parser_v2::parser::ParseResult;
+use crate::parser_v2::parser::Parser;
+use crate::zon_parser::lexer::{Keywords, Operator, Tokens};
+
+/// [`WhileLoop`]
+/// `while cond { body }`, the body runs for as long as the cases are true
+///
+/// **case** the cases that get checked before every run of the body
+/// **body** the nodes of the body, these can contain `break` and `continue`
+#[derive(Debug)]
+pub struct WhileLoop {
+    pub case: Vec<Statements>,
+    pub body: Vec<Node>,
+}
+
+/// What a for loop iterates over
+#[derive(Debug, PartialEq)]
+pub enum ForIterator {
+    /// `0..n` or `0..=n`, the end gets evaluated once before the loop starts
+    Range {
+        start: Value,
+        end: Value,
+        inclusive: bool,
+    },
+    /// A fixed size array, `for letter in name`
+    Array(Value),
+}
+
+/// [`ForLoop`]
+/// `for i in 0..10 { body }`
+///
+/// **binding** the loop variable, it is a new variable every iteration that only exists in the body
+/// **iterator** the range or array the loop variable gets its values from
+/// **body** the nodes of the body, these can contain `break` and `continue`
+#[derive(Debug)]
+pub struct ForLoop {
+    pub binding: Ident,
+    pub iterator: ForIterator,
+    pub body: Vec<Node>,
+}
+
+impl Parser<'_, '_> {
+    /// Parses a while loop, starting after the while keyword
+    ///
+    /// # Example
+    ///
+    /// while `->starts here` i < 10 {
+    ///     i += 1
+    /// }
+    pub fn parse_while_expr(&mut self, type_expected: &Type) -> ParseResult<WhileLoop> {
+        let case = self.lep_parse_statements()?;
+        let (body, _) = self.parse_block_expr(type_expected)?;
+        Ok(WhileLoop { case, body })
+    }
+
+    /// Parses a for loop, starting after the for keyword
+    ///
+    /// # Example
+    ///
+    /// for `->starts here` i in 0..=10 {
+    ///     printf(^"%d", i)
+    /// }
+    pub fn parse_for_expr(&mut self, type_expected: &Type) -> ParseResult<ForLoop> {
+        let binding = self.parse_next_ident_expr()?;
+        if !self.consume_if_next(Tokens::Kw(Keywords::In)) {
+            return Err(self.for_expected_in().into());
+        }
+
+        let start = self.parse_expr_value()?;
+        let iterator = if self.consume_if_next(Tokens::Op(Operator::Range)) {
+            let end = self.parse_expr_value()?;
+            ForIterator::Range { start, end, inclusive: false }
+        } else if self.consume_if_next(Tokens::Op(Operator::RangeEq)) {
+            let end = self.parse_expr_value()?;
+            ForIterator::Range { start, end, inclusive: true }
+        } else {
+            ForIterator::Array(start)
+        };
+
+        match self.tokens.get(self.pos) {
+            Some(token) if token.token_type == Tokens::OpenCurlyBracket => {}
+            _ => return Err(self.for_expected_body().into()),
+        }
+        let (body, _) = self.parse_block_expr(type_expected)?;
+        Ok(ForLoop { binding, iterator, body })
+    }
+
+    fn for_expected_in(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::ExpectedToken, "Expected 'in' after the variable of the for loop")
+            .expected("'in'")
+            .help("a for loop looks like `for i in 0..10`")
+    }
+
+    fn for_expected_body(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::ExpectedBody, "Expected a range `start..end` or a array followed by the body of the for loop")
+            .expected("'{'")
+    }
+}
@@ -0,0 +1,192 @@
+use crate::parser_v2::ast::{Ident, Node, Type, Value};
+use crate::parser_v2::parser::errors::{ParseError, ParseErrorKind};
+use crate::parser_v2::parser::ParseResult;
+use crate::parser_v2::parser::Parser;
+use crate::zon_parser::lexer::{Operator, Tokens};
+
+/// The pattern of a match arm
+#[derive(Debug, PartialEq)]
+pub enum Pattern {
+    /// `Shape::Rect(w, h)`, the bindings are new variables holding the payload of the variant, a
+    /// `_` binding skips that field
+    Variant {
+        enum_ident: Ident,
+        variant: Ident,
+        bindings: Vec<Ident>,
+    },
+    /// A integer or char, `1`, `-1` or `'a'`
+    Literal(Value),
+    /// A range of integers or chars, `'a'..='z'` or `0..10`
+    Range {
+        start: Value,
+        end: Value,
+        inclusive: bool,
+    },
+    /// `1 | 2 | 3`, matches if one of the patterns matches
+    Or(Vec<Pattern>),
+    /// `_`, matches every value
+    Wildcard,
+}
+
+/// [`MatchArm`]
+/// `Shape::Circle(r) => r * r`
+///
+/// **pattern** the pattern the value has to match for the body to run
+/// **body** the nodes of the arm, a arm without braces is a single value
+/// **line** the line the arm starts on
+#[derive(Debug)]
+pub struct MatchArm {
+    pub pattern: Pattern,
+    pub body: Vec<Node>,
+    pub line: usize,
+}
+
+/// [`Match`]
+/// `match value { arms }`, runs the first arm of which the pattern matches the value. The arms
+/// have to cover every possible value, a `_` arm covers all values the other arms don't
+///
+/// **value** the value that gets matched on
+/// **arms** the arms in the order they are written
+#[derive(Debug)]
+pub struct Match {
+    pub value: Value,
+    pub arms: Vec<MatchArm>,
+}
+
+impl Parser<'_, '_> {
+    /// Parses a match, starting after the match keyword, the arms can be separated by commas
+    ///
+    /// # Example
+    ///
+    /// match `->starts here` shape {
+    ///     Shape::Circle(r) => r * r * 3,
+    ///     Shape::Rect(w, h) => { w * h }
+    ///     _ => 0
+    /// }
+    pub fn parse_match_expr(&mut self, type_expected: &Type) -> ParseResult<Match> {
+        let value = self.parse_expr_value()?;
+        if !self.consume_if_next(Tokens::OpenCurlyBracket) {
+            return Err(self.match_expected_arms().into());
+        }
+
+        let mut arms = Vec::new();
+        loop {
+            while self.consume_if_next(Tokens::Comment) {}
+            if self.consume_if_next(Tokens::CloseCurlyBracket) {
+                return Ok(Match { value, arms });
+            }
+            let Some(start) = self.tokens.get(self.pos) else {
+                return Err(self.expected_end_expr("match", "}").into());
+            };
+
+            let pattern = self.parse_pattern()?;
+            if !self.consume_if_next(Tokens::FatArrow) {
+                return Err(self.match_expected_arrow().into());
+            }
+            let body = if self.next_is(Tokens::OpenCurlyBracket) {
+                self.parse_block_expr(type_expected)?.0
+            } else {
+                vec![self.parse_value_expr(type_expected)?]
+            };
+            arms.push(MatchArm { pattern, body, line: start.line });
+            self.consume_if_next(Tokens::Comma);
+        }
+    }
+
+    /// Parses the pattern of a match arm up until the '=>', patterns separated by a '|' become a
+    /// [`Pattern::Or`]
+    fn parse_pattern(&mut self) -> ParseResult<Pattern> {
+        let mut patterns = vec![self.parse_single_pattern()?];
+        while self.consume_if_next(Tokens::Op(Operator::Or)) {
+            patterns.push(self.parse_single_pattern()?);
+        }
+        if patterns.len() == 1 {
+            return Ok(patterns.remove(0));
+        }
+        if patterns.contains(&Pattern::Wildcard) {
+            return Err(self.match_wildcard_in_or().into());
+        }
+        Ok(Pattern::Or(patterns))
+    }
+
+    fn parse_single_pattern(&mut self) -> ParseResult<Pattern> {
+        if self.next_is_literal() {
+            return self.parse_literal_pattern();
+        }
+        if !self.consume_if_next(Tokens::Identifier) {
+            return Err(self.match_expected_pattern().into());
+        }
+        let mut enum_ident = Ident { name: self.assert_prev_token().value.to_string() };
+        if enum_ident.name == "_" {
+            return Ok(Pattern::Wildcard);
+        }
+        if self.is_module_type_path(&enum_ident.name) {
+            enum_ident = self.parse_type_path(enum_ident)?;
+        } else if self.next_is(Tokens::DoubleColon) {
+            self.use_type(&enum_ident.name, self.prev_span());
+        }
+        if !self.consume_if_next(Tokens::DoubleColon) {
+            return Err(self.match_expected_pattern().into());
+        }
+
+        let variant = self.parse_next_ident_expr()?;
+        let mut bindings = Vec::new();
+        if self.consume_if_next(Tokens::OpenBrace) {
+            while !self.consume_if_next(Tokens::CloseBrace) {
+                bindings.push(self.parse_next_ident_expr()?);
+                if !self.consume_if_next(Tokens::Comma) && !self.next_is(Tokens::CloseBrace) {
+                    return Err(self.expected_end_expr("pattern", ")").into());
+                }
+            }
+        }
+        Ok(Pattern::Variant { enum_ident, variant, bindings })
+    }
+
+    /// Parses a literal or a range pattern, `'a'` or `'a'..='z'`
+    fn parse_literal_pattern(&mut self) -> ParseResult<Pattern> {
+        let start = self.parse_not_know_type_value()?;
+        let inclusive = if self.consume_if_next(Tokens::Op(Operator::RangeEq)) {
+            true
+        } else if self.consume_if_next(Tokens::Op(Operator::Range)) {
+            false
+        } else {
+            return Ok(Pattern::Literal(start));
+        };
+        if !self.next_is_literal() {
+            return Err(self.match_expected_range_end().into());
+        }
+        let end = self.parse_not_know_type_value()?;
+        Ok(Pattern::Range { start, end, inclusive })
+    }
+
+    fn next_is_literal(&self) -> bool {
+        let next = self.tokens.get(self.pos).map(|token| &token.token_type);
+        matches!(next, Some(Tokens::Number | Tokens::NegativeNumber | Tokens::Char))
+    }
+
+    fn match_expected_arms(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::ExpectedBody, "Expected the arms of the match in between '{' and '}'")
+            .expected("'{'")
+    }
+
+    fn match_expected_arrow(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::ExpectedToken, "Expected a '=>' in between the pattern and the body of the match arm")
+            .expected("'=>'")
+    }
+
+    fn match_expected_pattern(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::InvalidPattern, "Expected a pattern in the match")
+            .expected("a pattern")
+            .help("a pattern looks like `Shape::Circle(r)`, `1`, `'a'..='z'` or `_`")
+    }
+
+    fn match_expected_range_end(&self) -> ParseError {
+        self.error_at_next(ParseErrorKind::InvalidPattern, "Expected a integer or char as the end of the range pattern")
+            .expected("a integer or char")
+    }
+
+    fn match_wildcard_in_or(&self) -> ParseError {
+        self.error_at_prev(ParseErrorKind::InvalidPattern, "A `_` pattern can't be combined with '|'")
+            .help("a `_` pattern already matches every value, the other patterns can be removed")
+    }
+}
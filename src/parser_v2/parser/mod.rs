@@ -2,30 +2,76 @@
 //! Converts the tokens into a Abstract Syntax Tree
 #![allow(dead_code)]
 
+mod consts;
+pub mod enums;
 pub mod errors;
+mod externs;
+mod imports;
 pub mod lep;
+pub mod loops;
+pub mod matches;
+mod recovery;
+pub mod structs;
+
+use std::collections::{HashMap, HashSet};
 
 use super::ast::{
-    Assignment, Ast, FunctionCall, Ident, Math, Node, Paramater, Type, Types, Value, Variable,
+    Assignment, Ast, FunctionCall, Ident, Math, ModulePath, Node, Paramater, Type, TypeUse, Types, Value,
+    Variable,
 };
 use crate::{
     parser_v2::ast::{Function, NodeTypes, TypeValues},
-    zon_parser::lexer::{Keywords, Operator, Token, Tokens},
+    parser_v2::parser::errors::{ParseError, ParseErrorKind},
+    zon_parser::{
+        lexer::{Keywords, Operator, Token, Tokens},
+        span::Span,
+    },
 };
 
-pub struct Parser {
-    tokens: Vec<Token>,
+/// Reads the tokens by reference, so no token gets copied while parsing
+pub struct Parser<'t, 'src> {
+    tokens: &'t [Token<'src>],
     pos: usize,
+    /// The number of generics around the current generics that a `>>` already closed, every
+    /// generics that returns to the generics around it closes one of them
+    close_outer_generics: usize,
+    /// Set by [`Parser::parse`], the blocks skip a statement with a error instead of returning it
+    recovering: bool,
+    /// The errors the parser recovered from, [`Parser::parse`] returns all of them
+    errors: Vec<ParseError>,
+    /// The top-level nodes parsed so far, a const can call the functions among them
+    items: Vec<Node>,
+    /// The values of the consts by name, the first scope is the top level and the last scope is
+    /// the innermost block
+    consts: Vec<HashMap<String, i64>>,
+    /// The names of the modules the file imports, `math::sqrt` is a path into a module
+    modules: HashSet<String>,
+    /// The paths into other modules found so far
+    module_paths: Vec<ModulePath>,
+    /// The uses of structs and enums by name found so far
+    type_uses: Vec<TypeUse>,
 }
 
-type ParseResult<T> = Result<T, String>;
-
-impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, pos: 0 }
+/// The error is boxed, so a [`ParseResult`] stays small on the happy path
+type ParseResult<T> = Result<T, Box<ParseError>>;
+
+impl<'t, 'src> Parser<'t, 'src> {
+    pub fn new(tokens: &'t [Token<'src>]) -> Self {
+        Self {
+            tokens,
+            pos: 0,
+            close_outer_generics: 0,
+            recovering: false,
+            errors: Vec::new(),
+            items: Vec::new(),
+            consts: vec![HashMap::new()],
+            modules: imports::imported_modules(tokens),
+            module_paths: Vec::new(),
+            type_uses: Vec::new(),
+        }
     }
 
-    pub fn peak(&mut self) -> Option<&Token> {
+    pub fn peak(&mut self) -> Option<&'t Token<'src>> {
         return self.tokens.get(self.pos + 1);
     }
 
@@ -34,40 +80,80 @@ impl Parser {
         self.pos -= n;
     }
 
-    pub fn parse(&mut self) -> ParseResult<Ast> {
-        let mut ast = Ast {
-            body: Vec::new(),
-            r#type: NodeTypes::Program,
-        };
+    /// Parses the tokens into a [`Ast`]. The parser skips to the next item or statement after a
+    /// syntax error, so it returns every error in the source instead of only the first one.
+    pub fn parse(&mut self) -> Result<Ast, Vec<ParseError>> {
+        self.recovering = true;
         while let Some(token) = self.next() {
-            match token.token_type {
+            let start = self.pos - 1;
+            let node = match &token.token_type {
                 Tokens::Kw(Keywords::Let) => {
                     // We found the let token, but parse_let_expr expects the
                     // next token to be a let token so we walk one back
                     self.walk_back(1);
-
-                    let let_expr = self.parse_let_expr()?;
-                    ast.body.push(let_expr);
-                }
-                Tokens::Kw(Keywords::Fn) => {
-                    let function = self.parse_fn_expr()?;
-                    ast.body.push(function);
+                    self.parse_let_expr()
                 }
+                Tokens::Kw(Keywords::Fn) => self.parse_fn_expr(),
+                Tokens::Kw(Keywords::Const) => self.parse_const_expr(),
+                Tokens::Kw(Keywords::Extern) => self.parse_extern_expr(),
+                Tokens::Kw(Keywords::Import) => self.parse_import_expr(),
+                Tokens::Kw(Keywords::Pub) => self.parse_pub_expr(),
+                Tokens::Kw(Keywords::Struct) => self.parse_struct_expr().map(|parsed_struct| {
+                    let span = token.span.to(self.prev_span());
+                    Node::new(NodeTypes::Struct(parsed_struct), token.line, span)
+                }),
+                Tokens::Kw(Keywords::Enum) => self.parse_enum_expr().map(|parsed_enum| {
+                    let span = token.span.to(self.prev_span());
+                    Node::new(NodeTypes::Enum(parsed_enum), token.line, span)
+                }),
                 Tokens::Comment => continue,
-                kw => todo!("found {kw:#?}"),
+                _ => Err(self.invalid_top_level_token().into()),
+            };
+            match node {
+                Ok(node) => self.items.push(node),
+                Err(error) => {
+                    self.errors.push(*error);
+                    self.recover_top_level(start);
+                }
             }
         }
-        Ok(ast)
+
+        self.recovering = false;
+        if !self.errors.is_empty() {
+            return Err(std::mem::take(&mut self.errors));
+        }
+        Ok(Ast {
+            body: std::mem::take(&mut self.items),
+            r#type: NodeTypes::Program,
+            module_paths: std::mem::take(&mut self.module_paths),
+            type_uses: std::mem::take(&mut self.type_uses),
+        })
     }
 
-    pub fn prev_token(&mut self) -> Option<&Token> {
+    pub fn prev_token(&mut self) -> Option<&'t Token<'src>> {
         self.tokens.get(self.pos - 1)
     }
 
-    pub fn assert_prev_token(&mut self) -> Token {
+    pub fn assert_prev_token(&mut self) -> &'t Token<'src> {
         let token = self.prev_token();
         assert!(token.is_some());
-        token.unwrap().clone()
+        token.unwrap()
+    }
+
+    /// Returns the span of the last consumed token, or a empty span if no token was consumed yet.
+    pub fn prev_span(&self) -> Span {
+        if self.pos == 0 {
+            return Span::default();
+        }
+        self.tokens
+            .get(self.pos - 1)
+            .map(|token| token.span)
+            .unwrap_or_default()
+    }
+
+    /// Returns true if the next token is of the given type, without consuming it
+    pub fn next_is(&self, token_type: Tokens) -> bool {
+        matches!(self.tokens.get(self.pos), Some(token) if token.token_type == token_type)
     }
 
     pub fn consume_if_next(&mut self, next: Tokens) -> bool {
@@ -82,11 +168,11 @@ impl Parser {
     /// Expects the next token to be a ident, returns the value of the identifier, returns a error
     /// if it is not a identifier
     pub fn parse_next_ident_expr(&mut self) -> ParseResult<Ident> {
-        let Some(ident) = self.next() else {return Err(self.expected_ident())};
+        let Some(ident) = self.next() else {return Err(self.expected_ident().into())};
         if ident.token_type != Tokens::Identifier {
-            return Err(self.expected_ident());
+            return Err(self.expected_ident().into());
         };
-        Ok(Ident { name: ident.value })
+        Ok(Ident { name: ident.value.to_string() })
     }
 
     /// Parses any generic expr inbetween a <T...>; It does so recursively, meaning any generic can
@@ -109,25 +195,29 @@ impl Parser {
             is_pointer: false,
             size: 0,
             generics: Vec::new(),
+            span: Span::default(),
         };
 
         while let Some(generic) = self.next() {
             match generic.token_type {
-                Tokens::Kw(_) => {
+                Tokens::Kw(_) | Tokens::Identifier => {
                     if generic_type.r#type != Types::UnknownType("".into()) {
-                        return Err(self.expected_type_seperator());
+                        return Err(self.expected_type_seperator().into());
                     }
-                    generic_type.r#type = Types::from(generic.value.as_str());
+                    generic_type.r#type = match generic.token_type {
+                        Tokens::Identifier if self.modules.contains(generic.value.as_ref()) && self.next_is(Tokens::DoubleColon) => {
+                            Types::Named(self.parse_type_path(Ident { name: generic.value.to_string() })?.name)
+                        }
+                        Tokens::Identifier => self.use_type(&generic.value, generic.span),
+                        _ => Types::from(generic.value.as_ref()),
+                    };
+                    generic_type.span = generic.span.to(self.prev_span());
                 }
                 Tokens::OpenBracket => {
                     generic_type.is_array = true;
-                    if !self.consume_if_next(Tokens::Number) {
-                        return Err(self.expected_array_size());
-                    }
-                    let size = self.assert_prev_token();
-                    generic_type.size = size.value.parse().unwrap();
+                    generic_type.size = self.parse_array_size()?;
                     if !self.consume_if_next(Tokens::CloseBracket) {
-                        return Err(self.expected_end_expr("array type", "]"));
+                        return Err(self.expected_end_expr("array type", "]").into());
                     }
                 }
                 Tokens::Comma => {
@@ -143,49 +233,78 @@ impl Parser {
 
                     generic_type.r#type = Types::UnknownType("".into());
                     generic_type.generics.clear();
+
+                    if self.close_outer_generics > 0 {
+                        self.close_outer_generics -= 1;
+                        base_type.span = base_type.span.to(self.prev_span());
+                        return Ok(());
+                    }
                 }
                 Tokens::Op(Operator::More) => {
                     if generic_type.r#type != Types::UnknownType("".into()) {
                         base_type.generics.push(generic_type.clone());
                     }
+                    base_type.span = base_type.span.to(generic.span);
                     return Ok(());
                 }
-                _ => return Err(self.invalid_token_in_expr("generics", "type")),
+                // `array<array<i32>>` gets lexed with a shift right at the end, it closes these
+                // generics and the generics around them. `Foo<Bar<Baz<i32>>>` ends in a `>>` and
+                // a `>`, every level only closes the generics it is in
+                Tokens::Op(Operator::ShiftRight) => {
+                    if generic_type.r#type != Types::UnknownType("".into()) {
+                        base_type.generics.push(generic_type.clone());
+                    }
+                    base_type.span = base_type.span.to(generic.span);
+                    self.close_outer_generics += 1;
+                    return Ok(());
+                }
+                _ => return Err(self.invalid_token_in_expr("generics", "type").into()),
             }
         }
 
-        Err(self.expected_end_expr("generics", ">"))
+        Err(self.expected_end_expr("generics", ">").into())
     }
 
     /// Expects the next stream of tokens to be a type
     /// Parses up intil the '>' en of generics, or end of single type
+    ///
+    /// A identifier is the name of a struct or enum, `p: Point`
     pub fn parse_type_expr(&mut self) -> ParseResult<Type> {
-        let Some(base_type) = self.next() else { return Err(self.expected_type()) };
-        let Tokens::Kw(_) = base_type.token_type else { return Err(self.expected_type()) };
+        let Some(base_type) = self.next() else { return Err(self.expected_type().into()) };
+        let r#type = match base_type.token_type {
+            Tokens::Kw(_) => match Types::from(base_type.value.as_ref()) {
+                Types::UnknownType(_) => return Err(self.expected_type().into()),
+                r#type => r#type,
+            },
+            Tokens::Identifier if self.modules.contains(base_type.value.as_ref()) && self.next_is(Tokens::DoubleColon) => {
+                Types::Named(self.parse_type_path(Ident { name: base_type.value.to_string() })?.name)
+            }
+            Tokens::Identifier => self.use_type(&base_type.value, base_type.span),
+            _ => return Err(self.expected_type().into()),
+        };
         let mut base_type = Type {
-            r#type: Types::from(base_type.value.as_str()),
+            r#type,
             generics: Vec::new(),
             is_pointer: false,
             is_array: false,
             size: 0,
+            span: base_type.span.to(self.prev_span()),
         };
 
         if self.consume_if_next(Tokens::OpenBracket) {
             base_type.is_array = true;
-            if self.consume_if_next(Tokens::Number) {
-                let value = self.assert_prev_token();
-                base_type.size = value.value.parse().unwrap();
-
-                if !self.consume_if_next(Tokens::CloseBracket) {
-                    return Err(self.expected_end_expr("array type", "]"));
-                }
-            } else {
-                return Err(self.expected_array_size());
+            base_type.size = self.parse_array_size()?;
+            if !self.consume_if_next(Tokens::CloseBracket) {
+                return Err(self.expected_end_expr("array type", "]").into());
             }
         }
 
         if self.consume_if_next(Tokens::Op(Operator::Less)) {
             self.parse_generics_expr(&mut base_type)?;
+            if self.close_outer_generics > 0 {
+                self.close_outer_generics = 0;
+                return Err(self.invalid_token_in_expr("generics", "type").into());
+            }
             return Ok(base_type);
         }
 
@@ -193,6 +312,7 @@ impl Parser {
             base_type.is_pointer = true;
         }
 
+        base_type.span = base_type.span.to(self.prev_span());
         return Ok(base_type);
     }
 
@@ -208,30 +328,29 @@ impl Parser {
     pub fn parse_array(&mut self, base_type: &Type) -> ParseResult<Vec<TypeValues>> {
         let mut array_items: Vec<TypeValues> = Vec::new();
         if !base_type.is_array {
-            return Err(self.expected_array_generic());
+            return Err(self.expected_array_generic().into());
         }
 
         if !self.consume_if_next(Tokens::OpenBracket) {
-            return Err(
-                "Parse array should only get called if the next token is a open bracket."
-                    .to_string(),
-            );
+            return Err(self
+                .error_at_next(ParseErrorKind::ExpectedToken, "Expected a array to start with a open bracket")
+                .expected("'['").into());
         }
 
         let mut curr = TypeValues::None;
         while let Some(array_value) = self.next() {
-            match array_value.token_type {
+            match &array_value.token_type {
                 Tokens::Number | Tokens::FloatNumber | Tokens::String | Tokens::Char => {
                     if curr != TypeValues::None {
-                        return Err(self.expected_value_seprator());
+                        return Err(self.expected_value_seprator().into());
                     }
-                    let value = base_type.r#type.type_value_convert(&array_value.value)?;
+                    let value = base_type.r#type.type_value_convert(&array_value.value).map_err(|error| self.invalid_literal(error))?;
                     curr = value;
                 }
-                Tokens::Identifier => curr = TypeValues::Identifier(array_value.value),
+                Tokens::Identifier => curr = TypeValues::Identifier(array_value.value.to_string()),
                 Tokens::Comma => {
                     if curr == TypeValues::None {
-                        return Err(self.expected_array_value_comma());
+                        return Err(self.expected_array_value_comma().into());
                     }
                     array_items.push(curr.clone());
                     curr = TypeValues::None
@@ -243,37 +362,77 @@ impl Parser {
                     return Ok(array_items);
                 }
                 Tokens::OpenBracket => {
-                    return Err(self.not_supported_array_in_array());
+                    return Err(self.not_supported_array_in_array().into());
                 }
                 invalid_token => {
                     return Err(
-                        self.invalid_expected_type("array value", &format!("{invalid_token:#?}"))
+                        self.invalid_expected_type("array value", &format!("{invalid_token:#?}")).into()
                     )
                 }
             }
         }
 
-        Err(self.expected_end_expr("array", "]"))
+        Err(self.expected_end_expr("array", "]").into())
     }
 
     /// Expects the next token to be value
     /// Parses until a end to the value is found depending on the first token;
     /// For example a FunctionCall value will get parsed until the end of the function call ')'
+    ///
+    /// When the value is followed by a math operator the whole math expression gets parsed,
+    /// `return n * factorial(n - 1)`
     pub fn parse_value_expr(&mut self, base_type: &Type) -> ParseResult<Node> {
+        let node = self.parse_single_value_expr(base_type)?;
+        if self.next_math_operator().is_none() {
+            return Ok(node);
+        }
+        if let NodeTypes::LogicalStatement(_) | NodeTypes::Match(_) = node.node_type {
+            return Ok(node);
+        }
+
+        let line = node.line;
+        let span = node.span;
+        let operand = match node.node_type {
+            NodeTypes::Value(value) => value,
+            NodeTypes::FunctionCall(call) => {
+                let Some(NodeTypes::Arguments(args)) = node.left.map(|args| args.node_type) else {
+                    unreachable!("ERROR: EXPECTED ARGUMENTS FROM PARSE FN CALL")
+                };
+                Value::new(TypeValues::FunctionCall(call, args), false, span)
+            }
+            _ => return Err(self.invalid_token_in_expr("value", "value").into()),
+        };
+
+        let math = self.parse_math_rhs(Math::Value(Box::new(operand)), 0)?;
+        let span = span.to(self.prev_span());
+        Ok(Node::new(
+            NodeTypes::Value(Value::new(TypeValues::Math(math), false, span)),
+            line,
+            span,
+        ))
+    }
+
+    /// Parses a single value, see [`Parser::parse_value_expr`]
+    fn parse_single_value_expr(&mut self, base_type: &Type) -> ParseResult<Node> {
         let mut value = Value {
             value: TypeValues::None,
             is_ptr: false,
+            span: Span::default(),
         };
 
         let expected_type = base_type.r#type.clone();
 
         if self.consume_if_next(Tokens::Pointer) {
             value.is_ptr = true;
+            value.span = self.prev_span();
         }
 
         let Some(value_expr) = self.next() else {
-            return Err(self.invalid_expected_type("value", "none"));
+            return Err(self.invalid_expected_type("value", "none").into());
         };
+        if !value.is_ptr {
+            value.span = value_expr.span;
+        }
 
         match value_expr.token_type {
             Tokens::Number
@@ -281,42 +440,56 @@ impl Parser {
             | Tokens::Char
             | Tokens::FloatNumber
             | Tokens::String => {
-                value.value = expected_type.type_value_convert(&value_expr.value)?;
-                return Ok(Node::new(NodeTypes::Value(value), value_expr.line));
+                value.value = expected_type.type_value_convert(&value_expr.value).map_err(|error| self.invalid_literal(error))?;
+                value.span = value.span.to(value_expr.span);
+                let span = value.span;
+                return Ok(Node::new(NodeTypes::Value(value), value_expr.line, span));
             }
             Tokens::OpenBracket => {
                 self.walk_back(1);
                 let arr = self.parse_array(base_type)?;
                 value.value = TypeValues::Array(arr);
-                return Ok(Node::new(NodeTypes::Value(value), value_expr.line));
+                value.span = value.span.to(self.prev_span());
+                let span = value.span;
+                return Ok(Node::new(NodeTypes::Value(value), value_expr.line, span));
             }
             Tokens::Identifier => {
-                if self.consume_if_next(Tokens::OpenBrace) {
-                    self.walk_back(2);
+                if self.next_is(Tokens::OpenBrace) || self.is_module_path(&value_expr.value) {
+                    self.walk_back(1);
                     let (function_call, arguments) = self.parse_fn_call_expr()?;
-                    let function_call = Node::fn_call(function_call, arguments, value_expr.line);
+                    let span = value.span.to(self.prev_span());
+                    let function_call =
+                        Node::fn_call(function_call, arguments, value_expr.line, span);
                     return Ok(function_call);
                 }
-                value.value = TypeValues::Identifier(value_expr.value);
-                return Ok(Node::new(NodeTypes::Value(value), value_expr.line));
+                let ident = Ident { name: value_expr.value.to_string() };
+                value.value = self.parse_ident_value(ident)?;
+                value.span = value.span.to(self.prev_span());
+                let span = value.span;
+                return Ok(Node::new(NodeTypes::Value(value), value_expr.line, span));
             }
             Tokens::Kw(Keywords::Void) => {
                 return Ok(Node::new(
                     NodeTypes::Value(Value {
                         value: TypeValues::None,
                         is_ptr: false,
+                        span: value_expr.span,
                     }),
                     value_expr.line,
+                    value_expr.span,
                 ));
             }
             Tokens::OpenBrace => {
                 let math_statement = self.parse_math_statement()?;
+                let span = value_expr.span.to(self.prev_span());
                 return Ok(Node::new(
                     NodeTypes::Value(Value {
                         value: TypeValues::Math(math_statement),
                         is_ptr: false,
+                        span,
                     }),
                     value_expr.line,
+                    span,
                 ));
             }
             Tokens::BoolTrue => {
@@ -324,8 +497,10 @@ impl Parser {
                     NodeTypes::Value(Value {
                         value: TypeValues::True,
                         is_ptr: false,
+                        span: value_expr.span,
                     }),
                     value_expr.line,
+                    value_expr.span,
                 ));
             }
             Tokens::BoolFalse => {
@@ -333,11 +508,37 @@ impl Parser {
                     NodeTypes::Value(Value {
                         value: TypeValues::False,
                         is_ptr: false,
+                        span: value_expr.span,
                     }),
                     value_expr.line,
+                    value_expr.span,
                 ));
             }
-            _ => return Err(self.invalid_token_in_expr("value", "value")),
+            Tokens::Op(Operator::Min) | Tokens::Op(Operator::Tilde) if !value.is_ptr => {
+                self.walk_back(1);
+                let value = self.parse_expr_value()?;
+                let span = value.span;
+                return Ok(Node::new(NodeTypes::Value(value), value_expr.line, span));
+            }
+            Tokens::Kw(Keywords::If) if !value.is_ptr => {
+                let lep = self.lep_parse(base_type)?;
+                let span = value_expr.span.to(self.prev_span());
+                return Ok(Node::new(
+                    NodeTypes::LogicalStatement(Box::new(lep)),
+                    value_expr.line,
+                    span,
+                ));
+            }
+            Tokens::Kw(Keywords::Match) if !value.is_ptr => {
+                let match_expr = self.parse_match_expr(base_type)?;
+                let span = value_expr.span.to(self.prev_span());
+                return Ok(Node::new(
+                    NodeTypes::Match(Box::new(match_expr)),
+                    value_expr.line,
+                    span,
+                ));
+            }
+            _ => return Err(self.invalid_token_in_expr("value", "value").into()),
         };
     }
 
@@ -347,13 +548,14 @@ impl Parser {
     /// inference
     pub fn parse_let_expr(&mut self) -> ParseResult<Node> {
         let Some(next_token) = self.next() else {
-            return Err(self.invalid_expected_type("variable", "none"))
+            return Err(self.invalid_expected_type("variable", "none").into())
         };
         assert!(next_token.token_type == Tokens::Kw(Keywords::Let));
 
         let ident = self.parse_next_ident_expr()?;
+        self.expect_not_const(&ident)?;
         if !self.consume_if_next(Tokens::Colon) {
-            return Err(self.expected_type_seperator());
+            return Err(self.expected_type_seperator().into());
         }
 
         let mut var_type = self.parse_type_expr()?;
@@ -366,12 +568,14 @@ impl Parser {
                 },
                 Operator::Eq,
                 next_token.line,
+                next_token.span,
             );
             let variable_value = self.parse_value_expr(&mut var_type)?;
+            node.span = next_token.span.to(variable_value.span);
             node.right = Some(Box::new(variable_value));
             Ok(node)
         } else {
-            Err(self.expected_assign_token())
+            Err(self.expected_assign_token().into())
         }
     }
 
@@ -380,21 +584,39 @@ impl Parser {
     /// # Example of paramaters
     /// `(id_0: string, id1: array<i32>)`
     pub fn parse_params(&mut self) -> ParseResult<Vec<Paramater>> {
+        let (params, _) = self.parse_params_with(false)?;
+        Ok(params)
+    }
+
+    /// Parses the paramaters like [`Parser::parse_params`], when `allow_variadic` is set the last
+    /// paramater can be `...`. Returns if the paramaters ended with `...`
+    pub(super) fn parse_params_with(&mut self, allow_variadic: bool) -> ParseResult<(Vec<Paramater>, bool)> {
         if !self.consume_if_next(Tokens::OpenBrace) {
-            return Err(self.expected_params_openbrace());
+            return Err(self.expected_params_openbrace().into());
         };
 
         let mut params = Vec::new();
         if self.consume_if_next(Tokens::CloseBrace) {
-            return Ok(params);
+            return Ok((params, false));
         }
 
         while let Some(_next_param) = self.next() {
             self.walk_back(1);
 
+            if self.consume_if_next(Tokens::Ellipsis) {
+                if !allow_variadic {
+                    return Err(self.variadic_not_extern().into());
+                }
+                if !self.consume_if_next(Tokens::CloseBrace) {
+                    return Err(self.variadic_not_last().into());
+                }
+                return Ok((params, true));
+            }
+
             let ident = self.parse_next_ident_expr()?;
+            self.expect_not_const(&ident)?;
             if !self.consume_if_next(Tokens::Colon) {
-                return Err(self.expected_type_seperator());
+                return Err(self.expected_type_seperator().into());
             }
 
             let type_param = self.parse_type_expr()?;
@@ -404,92 +626,132 @@ impl Parser {
             });
 
             if self.consume_if_next(Tokens::CloseBrace) {
-                return Ok(params);
+                return Ok((params, false));
             }
 
             if !self.consume_if_next(Tokens::Comma) {
-                return Err(self.expected_type_seperator());
+                return Err(self.expected_type_seperator().into());
             }
         }
 
-        return Err(self.expected_end_expr("paramaters", ")"));
+        return Err(self.expected_end_expr("paramaters", ")").into());
     }
 
     fn parse_not_know_type_value(&mut self) -> ParseResult<Value> {
         let mut value_holder = Value {
             value: TypeValues::None,
             is_ptr: false,
+            span: Span::default(),
         };
         if self.consume_if_next(Tokens::Pointer) {
             value_holder.is_ptr = true;
+            value_holder.span = self.prev_span();
         }
 
         let Some(value) = self.next() else {
             //todo fix tis!
-            return Err(self.expected_value_seprator());
+            return Err(self.expected_value_seprator().into());
+        };
+        value_holder.span = if value_holder.is_ptr {
+            value_holder.span.to(value.span)
+        } else {
+            value.span
         };
 
         // we assume defaults here, consider float to be f32, number to be i32, etc.
         match value.token_type {
             Tokens::String => {
                 let none_type = Types::String;
-                let value = none_type.type_value_convert(&value.value)?;
+                let value = none_type.type_value_convert(&value.value).map_err(|error| self.invalid_literal(error))?;
                 value_holder.value = value;
                 Ok(value_holder)
             }
             Tokens::Char => {
                 let none_type = Types::Char;
-                let value = none_type.type_value_convert(&value.value)?;
+                let value = none_type.type_value_convert(&value.value).map_err(|error| self.invalid_literal(error))?;
                 value_holder.value = value;
                 Ok(value_holder)
             }
             Tokens::Number => {
-                let none_type = Types::I32;
-                let value = none_type.type_value_convert(&value.value)?;
+                let none_type = Types::number_literal_type(&value.value).unwrap_or(Types::I32);
+                let value = none_type.type_value_convert(&value.value).map_err(|error| self.invalid_literal(error))?;
                 value_holder.value = value;
                 Ok(value_holder)
             }
             Tokens::FloatNumber => {
-                let none_type = Types::F32;
-                let value = none_type.type_value_convert(&value.value)?;
+                let none_type = Types::number_literal_type(&value.value).unwrap_or(Types::F32);
+                let value = none_type.type_value_convert(&value.value).map_err(|error| self.invalid_literal(error))?;
                 value_holder.value = value;
                 Ok(value_holder)
             }
             Tokens::NegativeNumber => {
-                let none_type = Types::I32;
-                let value = none_type.type_value_convert(&value.value)?;
+                let none_type = Types::number_literal_type(&value.value).unwrap_or(Types::I32);
+                let value = none_type.type_value_convert(&value.value).map_err(|error| self.invalid_literal(error))?;
                 value_holder.value = value;
                 Ok(value_holder)
             }
             Tokens::Identifier => {
-                if self.consume_if_next(Tokens::OpenBrace) {
-                    self.walk_back(2);
+                if self.next_is(Tokens::OpenBrace) || self.is_module_path(&value.value) {
+                    self.walk_back(1);
                     let (call, args) = self.parse_fn_call_expr()?;
                     let NodeTypes::Arguments(args) = args else { unreachable!("ERROR: EXPECTED ARGUMENTS FROM PARSE FN CALL") };
                     value_holder.value = TypeValues::FunctionCall(call, args);
+                    value_holder.span = value_holder.span.to(self.prev_span());
                     return Ok(value_holder.into());
                 }
-                let none_type = Types::Ident;
-                let value = none_type.type_value_convert(&value.value)?;
-                value_holder.value = value;
+                let ident = Ident { name: value.value.to_string() };
+                value_holder.value = self.parse_ident_value(ident)?;
+                value_holder.span = value_holder.span.to(self.prev_span());
                 Ok(value_holder)
             }
             Tokens::OpenBrace => {
                 let math_value = self.parse_math_statement()?;
-                Ok(TypeValues::Math(math_value).into())
+                Ok(Value::new(
+                    TypeValues::Math(math_value),
+                    false,
+                    value.span.to(self.prev_span()),
+                ))
             }
             Tokens::BoolTrue => Ok(Value {
                 value: TypeValues::True,
                 is_ptr: false,
+                span: value.span,
             }),
             Tokens::BoolFalse => Ok(Value {
                 value: TypeValues::False,
                 is_ptr: false,
+                span: value.span,
             }),
-            _ => Err(self.invalid_token_in_expr("value", "value")),
+            _ => Err(self.invalid_token_in_expr("value", "value").into()),
         }
     }
 
+    /// Parses what comes after a identifier that is not a function call, a struct literal
+    /// `Point { x: 1 }`, a enum variant `Shape::Empty`, a field `p.x` or the identifier on its own.
+    /// A struct literal or enum variant can be of a imported module, `math::Point { x: 1 }`
+    fn parse_ident_value(&mut self, ident: Ident) -> ParseResult<TypeValues> {
+        if self.is_module_type_path(&ident.name) {
+            let ident = self.parse_type_path(ident)?;
+            if self.next_is(Tokens::DoubleColon) {
+                return self.parse_enum_variant_value(ident);
+            }
+            return self.parse_struct_literal(ident);
+        }
+        if self.next_is(Tokens::DoubleColon) {
+            self.use_type(&ident.name, self.prev_span());
+            return self.parse_enum_variant_value(ident);
+        }
+        if self.is_struct_literal_start() {
+            self.use_type(&ident.name, self.prev_span());
+            return self.parse_struct_literal(ident);
+        }
+        let fields = self.parse_field_path()?;
+        if fields.is_empty() {
+            return Ok(TypeValues::Identifier(ident.name));
+        }
+        Ok(TypeValues::FieldAccess(ident, fields))
+    }
+
     pub fn parse_args_expr(&mut self) -> ParseResult<Vec<Value>> {
         // Todo: change this :|
         assert_eq!(self.next().unwrap().token_type, Tokens::OpenBrace);
@@ -502,7 +764,7 @@ impl Parser {
 
         while let Some(_) = self.next() {
             self.walk_back(1);
-            let value = self.parse_not_know_type_value()?;
+            let value = self.parse_expr_value()?;
             values.push(value);
 
             if self.consume_if_next(Tokens::Comma) {
@@ -512,108 +774,196 @@ impl Parser {
                 return Ok(values);
             }
 
-            return Err(self.expected_end_expr("argument", ")"));
+            return Err(self.expected_end_expr("argument", ")").into());
         }
 
-        Err(self.expected_end_expr("argument", ")"))
+        Err(self.expected_end_expr("argument", ")").into())
     }
 
     pub fn parse_reassignment_expr(&mut self) -> ParseResult<Node> {
         let assigns_to = self.parse_next_ident_expr()?;
+        if self.get_const(&assigns_to.name).is_some() {
+            return Err(self.assign_to_const(&assigns_to).into());
+        }
+        let ident_span = self.prev_span();
+        let fields = self.parse_field_path()?;
         if let Some(token) = self.next() {
-            if let Tokens::Op(op) = token.token_type {
-                let value = self.parse_not_know_type_value()?;
-                let assignment = Assignment { assigns_to };
+            if let Tokens::Op(op) = &token.token_type {
+                let value = self.parse_expr_value()?;
+                let value_span = value.span;
+                let assignment = Assignment { assigns_to, fields };
                 let node = Node {
                     node_type: NodeTypes::Assignment(assignment),
-                    right: Some(Box::new(Node::new(NodeTypes::Value(value), token.line))),
-                    left: Some(Box::new(Node::new(NodeTypes::Operator(op), token.line))),
+                    right: Some(Box::new(Node::new(NodeTypes::Value(value), token.line, value_span))),
+                    left: Some(Box::new(Node::new(NodeTypes::Operator(op.clone()), token.line, token.span))),
                     line: token.line,
+                    span: ident_span.to(value_span),
                 };
                 return Ok(node);
             }
         }
-        Err(self.expected_assign_token())
+        Err(self.expected_assign_token().into())
     }
 
     /// Returns the block node and the end line
     pub fn parse_block_expr(&mut self, type_expected: &Type) -> ParseResult<(Vec<Node>, usize)> {
-        let Some(currly_open) = self.next() else {
-            return Err(self.expected_body_openbracket());
-        };
-        assert_eq!(currly_open.token_type, Tokens::OpenCurlyBracket);
+        self.in_const_scope(|parser| parser.parse_block_nodes(type_expected))
+    }
+
+    fn parse_block_nodes(&mut self, type_expected: &Type) -> ParseResult<(Vec<Node>, usize)> {
+        if !self.consume_if_next(Tokens::OpenCurlyBracket) {
+            return Err(self.expected_body_openbracket().into());
+        }
         let mut body = Vec::new();
 
         while let Some(body_token) = self.next() {
-            match body_token.token_type {
-                Tokens::Kw(Keywords::Let) => {
-                    self.walk_back(1);
-                    let parse_let = self.parse_let_expr()?;
-                    body.push(parse_let)
-                }
-                Tokens::Kw(Keywords::Fn) => {
-                    let parse_fn = self.parse_fn_expr()?;
-                    body.push(parse_fn);
-                }
-                Tokens::OpenBracket => {
-                    let (block, line) = self.parse_block_expr(type_expected)?;
-                    body.push(Node::new(NodeTypes::Block(block), line))
-                }
-                Tokens::Identifier => {
-                    // Handle re-assignments
-                    if self.consume_if_next(Tokens::OpenBrace) {
-                        self.walk_back(2);
-                        let (func_call, arguments) = self.parse_fn_call_expr()?;
-                        let func_call_node = Node::fn_call(func_call, arguments, body_token.line);
-                        body.push(func_call_node);
-                        continue;
-                    }
-                    self.walk_back(1);
-                    let reassignment = self.parse_reassignment_expr()?;
-                    body.push(reassignment);
-                }
-                Tokens::CloseCurlyBracket => {
-                    return Ok((body, body_token.line));
+            if body_token.token_type == Tokens::CloseCurlyBracket {
+                return Ok((body, body_token.line));
+            }
+            let start = self.pos - 1;
+            match self.parse_block_statement(body_token, type_expected) {
+                Ok(Some(node)) => body.push(node),
+                Ok(None) => {}
+                Err(error) if self.recovering => {
+                    self.errors.push(*error);
+                    self.recover_statement(start);
                 }
-                Tokens::Kw(Keywords::If) => {
-                    let lep = self.lep_parse(type_expected)?;
-                    body.push(Node::new(
-                        NodeTypes::LogicalStatement(Box::new(lep)),
-                        body_token.line,
-                    ))
+                Err(error) => return Err(error),
+            }
+        }
+
+        Err(self.expected_end_expr("body", "}").into())
+    }
+
+    /// Parses a single statement of a block, starting after its first token. Returns None for
+    /// nodes that aren't part of the body, like comments
+    fn parse_block_statement(&mut self, body_token: &'t Token<'src>, type_expected: &Type) -> ParseResult<Option<Node>> {
+        let node = match body_token.token_type {
+            Tokens::Kw(Keywords::Let) => {
+                self.walk_back(1);
+                self.parse_let_expr()?
+            }
+            Tokens::Kw(Keywords::Fn) => self.parse_fn_expr()?,
+            Tokens::Kw(Keywords::Const) => self.parse_const_expr()?,
+            Tokens::OpenBracket => {
+                let (block, line) = self.parse_block_expr(type_expected)?;
+                let span = body_token.span.to(self.prev_span());
+                Node::new(NodeTypes::Block(block), line, span)
+            }
+            Tokens::Identifier => {
+                // Skip the path to a field, `p.x + 1` is a value and `p.x = 1` is not
+                let mut look = self.pos;
+                while matches!(self.tokens.get(look), Some(token) if token.token_type == Tokens::Dot) {
+                    look += 2;
                 }
-                Tokens::Kw(Keywords::Return) => {
-                    let return_node = self.parse_return_value(type_expected)?;
-                    body.push(return_node);
+                let next_token = self.tokens.get(look).map(|token| &token.token_type);
+                let is_value = matches!(
+                    next_token,
+                    Some(Tokens::OpenBrace | Tokens::CloseCurlyBracket | Tokens::DoubleColon)
+                ) || self.math_operator_at(look).is_some()
+                    || self.is_struct_literal_start();
+                self.walk_back(1);
+                // Handle re-assignments
+                if !is_value {
+                    return Ok(Some(self.parse_reassignment_expr()?));
                 }
-                Tokens::Comment => continue,
-                _ => {
-                    return Err(format!(
-                        "Found a token that is invalid in the body of a token: {:#?} on line {}",
-                        body_token.value, body_token.line
-                    ))
+                // A function call is a statement, `x` and `call() + 1` are the value of the block
+                let value = self.parse_value_expr(type_expected)?;
+                if !matches!(value.node_type, NodeTypes::FunctionCall(_)) {
+                    self.expect_block_value_end()?;
                 }
+                value
             }
-        }
+            Tokens::Number
+            | Tokens::NegativeNumber
+            | Tokens::FloatNumber
+            | Tokens::Char
+            | Tokens::String
+            | Tokens::BoolTrue
+            | Tokens::BoolFalse
+            | Tokens::OpenBrace
+            | Tokens::Op(Operator::Min)
+            | Tokens::Op(Operator::Tilde) => {
+                self.walk_back(1);
+                let value = self.parse_value_expr(type_expected)?;
+                self.expect_block_value_end()?;
+                value
+            }
+            Tokens::Kw(Keywords::If) => {
+                let lep = self.lep_parse(type_expected)?;
+                Node::new(
+                    NodeTypes::LogicalStatement(Box::new(lep)),
+                    body_token.line,
+                    body_token.span.to(self.prev_span()),
+                )
+            }
+            Tokens::Kw(Keywords::While) => {
+                let while_loop = self.parse_while_expr(type_expected)?;
+                Node::new(
+                    NodeTypes::While(Box::new(while_loop)),
+                    body_token.line,
+                    body_token.span.to(self.prev_span()),
+                )
+            }
+            Tokens::Kw(Keywords::Match) => {
+                let match_expr = self.parse_match_expr(type_expected)?;
+                Node::new(
+                    NodeTypes::Match(Box::new(match_expr)),
+                    body_token.line,
+                    body_token.span.to(self.prev_span()),
+                )
+            }
+            Tokens::Kw(Keywords::For) => {
+                let for_loop = self.parse_for_expr(type_expected)?;
+                Node::new(
+                    NodeTypes::For(Box::new(for_loop)),
+                    body_token.line,
+                    body_token.span.to(self.prev_span()),
+                )
+            }
+            Tokens::Kw(Keywords::Break) => Node::new(NodeTypes::Break, body_token.line, body_token.span),
+            Tokens::Kw(Keywords::Continue) => Node::new(NodeTypes::Continue, body_token.line, body_token.span),
+            Tokens::Kw(Keywords::Return) => {
+                let mut return_node = self.parse_return_value(type_expected)?;
+                return_node.span = body_token.span.to(return_node.span);
+                return_node
+            }
+            Tokens::Comment => return Ok(None),
+            _ => {
+                return Err(self
+                    .error_at_prev(ParseErrorKind::UnexpectedToken, "Found a token that can't be in the body of a block")
+                    .expected("a statement").into())
+            }
+        };
+        Ok(Some(node))
+    }
 
-        Err(self.expected_end_expr("body", "}"))
+    /// The value of a block is the last node of the block, `{ let a: i32 = 1; a + 1 }`
+    fn expect_block_value_end(&mut self) -> ParseResult<()> {
+        match self.tokens.get(self.pos) {
+            Some(token) if token.token_type == Tokens::CloseCurlyBracket => Ok(()),
+            _ => Err(self.expected_block_value_end().into()),
+        }
     }
 
     pub fn parse_return_value(&mut self, type_expected: &Type) -> ParseResult<Node> {
         let value = self.parse_value_expr(&type_expected)?;
-        let mut node = Node::new(NodeTypes::Return, value.line);
+        let mut node = Node::new(NodeTypes::Return, value.line, value.span);
         node.right = Some(Box::new(value));
         Ok(node)
     }
 
-    /// Returns the function call it self, and it's arguments
+    /// Returns the function call it self, and it's arguments. The function can be in a imported
+    /// module, `math::sqrt(2)`
     pub fn parse_fn_call_expr(&mut self) -> ParseResult<(FunctionCall, NodeTypes)> {
         let ident = self.parse_next_ident_expr()?;
+        let function_call = if self.is_module_path(&ident.name) {
+            self.parse_module_call(ident)?
+        } else {
+            FunctionCall { calls_to: ident, module: None }
+        };
         let arguments = self.parse_args_expr()?;
-        Ok((
-            FunctionCall { calls_to: ident },
-            NodeTypes::Arguments(arguments),
-        ))
+        Ok((function_call, NodeTypes::Arguments(arguments)))
     }
 
     /// Parses any valid function statement, starting from the identifier up until the ending close
@@ -625,6 +975,8 @@ impl Parser {
     ///     body
     /// }
     pub fn parse_fn_expr(&mut self) -> ParseResult<Node> {
+        // The fn keyword was already consumed by the caller
+        let start = self.prev_span();
         let ident = self.parse_next_ident_expr()?;
         let paramaters = self.parse_params()?;
         let mut returns = self.parse_type_expr()?;
@@ -634,60 +986,138 @@ impl Parser {
             ident,
             body,
             paramaters,
+            is_pub: false,
         };
-        Ok(Node::new(NodeTypes::Function(function), line))
+        Ok(Node::new(
+            NodeTypes::Function(function),
+            line,
+            start.to(self.prev_span()),
+        ))
     }
 
+    /// Parses a math expression in between braces, expects the '(' to already be consumed and
+    /// consumes the closing ')'
     pub fn parse_math_statement(&mut self) -> ParseResult<Math> {
-        let mut math = Math(Vec::new());
+        let math = self.parse_math_expr(0)?;
+        if !self.consume_if_next(Tokens::CloseBrace) {
+            return Err(self.expected_end_expr("mathematical statement", ")").into());
+        }
+        Ok(math)
+    }
 
-        while let Some(token) = self.next() {
-            match &token.token_type {
-                Tokens::Identifier => {
-                    self.walk_back(1);
-                    let value = self.parse_not_know_type_value()?;
-                    math.0.push(value);
-                }
-                Tokens::NegativeNumber => math
-                    .0
-                    .push(TypeValues::I32Neg(token.value.parse::<i32>().unwrap()).into()),
-                Tokens::Number => math
-                    .0
-                    .push(TypeValues::I32(token.value.parse::<i32>().unwrap()).into()),
-                Tokens::Op(op) => match op {
-                    Operator::Plus => math.0.push(TypeValues::Operator(op.clone()).into()),
-                    Operator::Times => math.0.push(TypeValues::Operator(op.clone()).into()),
-                    Operator::Slash => math.0.push(TypeValues::Operator(op.clone()).into()),
-                    Operator::Min => math.0.push(TypeValues::Operator(op.clone()).into()),
-                    _ => {
-                        return Err(self.invalid_token_in_expr(
-                            "operator statement",
-                            "mathematical operator expression",
-                        ))
-                    }
-                },
-                Tokens::OpenBrace => {
-                    let math_expr = self.parse_math_statement()?;
-                    math.0.push(TypeValues::Math(math_expr).into());
-                }
-                Tokens::CloseBrace => return Ok(math),
-                _ => {
-                    return Err(
-                        self.invalid_token_in_expr("math statement", "mathematical expression")
-                    )
+    /// Parses a math expression up until the first token that isn't a binary math operator, only
+    /// operators with a precedence of at least `min_precedence` get parsed.
+    ///
+    /// See [`Operator::precedence`] for the precedence table, all binary operators are left
+    /// associative.
+    pub fn parse_math_expr(&mut self, min_precedence: u8) -> ParseResult<Math> {
+        let lhs = self.parse_math_operand()?;
+        self.parse_math_rhs(lhs, min_precedence)
+    }
+
+    /// Continues a math expression of which the left hand side is already parsed
+    fn parse_math_rhs(&mut self, mut lhs: Math, min_precedence: u8) -> ParseResult<Math> {
+        while let Some(op) = self.next_math_operator() {
+            let Some(precedence) = op.precedence() else {
+                unreachable!("next_math_operator only returns binary operators")
+            };
+            if precedence < min_precedence {
+                break;
+            }
+
+            let Some(token) = self.next() else {
+                unreachable!("next_math_operator only returns a operator if there is a next token")
+            };
+            let rhs = match token.token_type {
+                // The minus is part of the number, `a -1 * 2`, the number is the start of the
+                // right hand side
+                Tokens::NegativeNumber | Tokens::FloatNumber => {
+                    let literal = &token.value[1..];
+                    let literal_type = match token.token_type {
+                        Tokens::FloatNumber => {
+                            Types::number_literal_type(literal).unwrap_or(Types::F32)
+                        }
+                        _ => Types::number_literal_type(literal).unwrap_or(Types::I32),
+                    };
+                    let value = literal_type.type_value_convert(literal).map_err(|error| self.invalid_literal(error))?;
+                    let number = Math::Value(Box::new(Value::new(value, false, token.span)));
+                    self.parse_math_rhs(number, precedence + 1)?
                 }
+                _ => self.parse_math_expr(precedence + 1)?,
+            };
+            lhs = Math::Binary(Box::new(lhs), op, Box::new(rhs));
+        }
+
+        Ok(lhs)
+    }
+
+    /// Returns the binary math operator the next token stands for, without consuming it
+    fn next_math_operator(&self) -> Option<Operator> {
+        self.math_operator_at(self.pos)
+    }
+
+    /// Returns the binary math operator the token at `pos` stands for. The operator has to be on
+    /// the line of the value before it, a `-1` or `^` on the next line isn't part of the expression
+    fn math_operator_at(&self, pos: usize) -> Option<Operator> {
+        let token = self.tokens.get(pos)?;
+        if pos.checked_sub(1).and_then(|prev| self.tokens.get(prev)).is_some_and(|prev| prev.line != token.line) {
+            return None;
+        }
+        match &token.token_type {
+            Tokens::Op(op) if op.precedence().is_some() => Some(op.clone()),
+            // The pointer sigil is only used in types, in between two values '^' is a xor
+            Tokens::Pointer => Some(Operator::Xor),
+            // `a -1` gets lexed as `a` `-1`, after a value the '-' is a minus operator
+            Tokens::NegativeNumber => Some(Operator::Min),
+            Tokens::FloatNumber if token.value.starts_with('-') => Some(Operator::Min),
+            _ => None,
+        }
+    }
+
+    /// Parses a single operand of a math expression, a value, a math expression in between braces
+    /// or a operand with a unary '-' or '~' in front of it
+    fn parse_math_operand(&mut self) -> ParseResult<Math> {
+        let Some(token) = self.next() else {
+            return Err(self.invalid_expected_type("value", "none").into());
+        };
+        match &token.token_type {
+            Tokens::Op(op @ (Operator::Min | Operator::Tilde)) => {
+                let operand = self.parse_math_operand()?;
+                Ok(Math::Unary(op.clone(), Box::new(operand)))
+            }
+            Tokens::OpenBrace => self.parse_math_statement(),
+            _ => {
+                self.walk_back(1);
+                let value = self.parse_not_know_type_value()?;
+                Ok(Math::Value(Box::new(value)))
             }
         }
+    }
 
-        Err(self.expected_end_expr("mathematical statement", ")"))
+    /// Parses a value that can be a math expression, `n * factorial(n - 1)`. A single value is
+    /// returned as is, a math expression as a [`TypeValues::Math`]
+    fn parse_expr_value(&mut self) -> ParseResult<Value> {
+        let start = self
+            .tokens
+            .get(self.pos)
+            .map(|token| token.span)
+            .unwrap_or_default();
+        match self.parse_math_expr(0)? {
+            Math::Value(value) => Ok(*value),
+            math => Ok(Value::new(
+                TypeValues::Math(math),
+                false,
+                start.to(self.prev_span()),
+            )),
+        }
     }
 }
 
-impl Iterator for Parser {
-    type Item = Token;
-    fn next(&mut self) -> Option<Token> {
-        let pos = self.tokens.get(self.pos)?;
+impl<'t, 'src> Iterator for Parser<'t, 'src> {
+    type Item = &'t Token<'src>;
+    fn next(&mut self) -> Option<Self::Item> {
+        let token = self.tokens.get(self.pos)?;
         self.pos += 1;
-        return Some(pos.clone());
+        Some(token)
     }
 }
@@ -0,0 +1,41 @@
+//! Skips past a syntax error so the parser can go on and report the errors after it
+use super::Parser;
+use crate::zon_parser::lexer::{Keywords, Tokens};
+
+impl Parser<'_, '_> {
+    /// Skips to the next `fn`, `pub`, `extern`, `import`, `let`, `const`, `struct` or `enum` that isn't inside of braces, `start` is
+    /// the position of the token the failed item started at. The braces are counted from the
+    /// start of the item, so a error in the middle of a block doesn't end the item early.
+    pub(super) fn recover_top_level(&mut self, start: usize) {
+        self.pos = start + 1;
+        let mut depth = 0usize;
+        while let Some(token) = self.tokens.get(self.pos) {
+            match token.token_type {
+                Tokens::OpenCurlyBracket => depth += 1,
+                Tokens::CloseCurlyBracket => depth = depth.saturating_sub(1),
+                // The fn of a `extern fn` or `pub fn` is part of the item that starts at the keyword before it
+                Tokens::Kw(Keywords::Fn) if matches!(self.tokens[self.pos - 1].token_type, Tokens::Kw(Keywords::Extern | Keywords::Pub)) => {}
+                Tokens::Kw(Keywords::Fn | Keywords::Pub | Keywords::Extern | Keywords::Import | Keywords::Let | Keywords::Const | Keywords::Struct | Keywords::Enum) if depth == 0 => return,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+
+    /// Skips to the next `let`, `const` or `fn` in the same block, or to the `}` that closes the block
+    /// without consuming it, `start` is the position of the token the failed statement started at
+    pub(super) fn recover_statement(&mut self, start: usize) {
+        self.pos = start + 1;
+        let mut depth = 0usize;
+        while let Some(token) = self.tokens.get(self.pos) {
+            match token.token_type {
+                Tokens::OpenCurlyBracket => depth += 1,
+                Tokens::CloseCurlyBracket if depth == 0 => return,
+                Tokens::CloseCurlyBracket => depth -= 1,
+                Tokens::Kw(Keywords::Fn | Keywords::Let | Keywords::Const) if depth == 0 => return,
+                _ => {}
+            }
+            self.pos += 1;
+        }
+    }
+}
@@ -0,0 +1,109 @@
+use crate::parser_v2::ast::{Ident, Paramater, Struct, TypeValues, Value};
+use crate::parser_v2::parser::errors::{ParseError, ParseErrorKind};
+use crate::parser_v2::parser::ParseResult;
+use crate::parser_v2::parser::Parser;
+use crate::zon_parser::lexer::Tokens;
+
+impl Parser<'_, '_> {
+    /// Parses a struct declaration, starting after the struct keyword, the fields can be separated
+    /// by commas
+    ///
+    /// # Example
+    ///
+    /// struct `->starts here` Point {
+    ///     x: i32,
+    ///     y: i32,
+    /// }
+    pub fn parse_struct_expr(&mut self) -> ParseResult<Struct> {
+        let ident = self.parse_next_ident_expr()?;
+        if !self.consume_if_next(Tokens::OpenCurlyBracket) {
+            return Err(self.struct_expected_body(&ident).into());
+        }
+
+        let mut fields: Vec<Paramater> = Vec::new();
+        loop {
+            while self.consume_if_next(Tokens::Comment) {}
+            if self.consume_if_next(Tokens::CloseCurlyBracket) {
+                return Ok(Struct { ident, fields, is_pub: false });
+            }
+            if self.tokens.get(self.pos).is_none() {
+                return Err(self.expected_end_expr("struct", "}").into());
+            }
+
+            let field = self.parse_next_ident_expr()?;
+            if fields.iter().any(|declared| declared.ident == field) {
+                return Err(self.struct_duplicate_field(&ident, &field).into());
+            }
+            if !self.consume_if_next(Tokens::Colon) {
+                return Err(self.expected_type_seperator().into());
+            }
+            let r#type = self.parse_type_expr()?;
+            fields.push(Paramater { r#type, ident: field });
+            self.consume_if_next(Tokens::Comma);
+        }
+    }
+
+    /// Returns true if the next tokens are the start of the fields of a struct literal, `{ x:`
+    pub fn is_struct_literal_start(&self) -> bool {
+        let token_at = |offset: usize| self.tokens.get(self.pos + offset).map(|token| &token.token_type);
+        token_at(0) == Some(&Tokens::OpenCurlyBracket)
+            && token_at(1) == Some(&Tokens::Identifier)
+            && token_at(2) == Some(&Tokens::Colon)
+    }
+
+    /// Parses the fields of a struct literal, the name of the struct was already consumed and the
+    /// next token is the '{'; Consumes the closing '}'
+    ///
+    /// # Example
+    ///
+    /// Point `->starts here` { x: 1, y: 2 }
+    pub fn parse_struct_literal(&mut self, ident: Ident) -> ParseResult<TypeValues> {
+        assert!(self.consume_if_next(Tokens::OpenCurlyBracket));
+
+        let mut fields: Vec<(Ident, Value)> = Vec::new();
+        loop {
+            if self.consume_if_next(Tokens::CloseCurlyBracket) {
+                return Ok(TypeValues::Struct(ident, fields));
+            }
+
+            let field = self.parse_next_ident_expr()?;
+            if fields.iter().any(|(assigned, _)| *assigned == field) {
+                return Err(self.struct_duplicate_field(&ident, &field).into());
+            }
+            if !self.consume_if_next(Tokens::Colon) {
+                return Err(self.struct_expected_field_value(&field).into());
+            }
+            let value = self.parse_expr_value()?;
+            fields.push((field, value));
+
+            if !self.consume_if_next(Tokens::Comma) && !self.next_is(Tokens::CloseCurlyBracket) {
+                return Err(self.expected_end_expr("struct literal", "}").into());
+            }
+        }
+    }
+
+    /// Parses the path to a field after the name of a variable, `.start.x`, returns a empty path if
+    /// the next token is not a '.'
+    pub fn parse_field_path(&mut self) -> ParseResult<Vec<Ident>> {
+        let mut fields = Vec::new();
+        while self.consume_if_next(Tokens::Dot) {
+            fields.push(self.parse_next_ident_expr()?);
+        }
+        Ok(fields)
+    }
+
+    fn struct_expected_body(&self, ident: &Ident) -> ParseError {
+        self.error_at_next(ParseErrorKind::ExpectedBody, format!("Expected the fields of struct {} in between '{{' and '}}'", ident.name))
+            .expected("'{'")
+    }
+
+    fn struct_duplicate_field(&self, ident: &Ident, field: &Ident) -> ParseError {
+        self.error_at_prev(ParseErrorKind::DuplicateName, format!("The field {} of struct {} is given more than once", field.name, ident.name))
+    }
+
+    fn struct_expected_field_value(&self, field: &Ident) -> ParseError {
+        self.error_at_next(ParseErrorKind::ExpectedToken, format!("Expected a ':' in between the field {} and its value", field.name))
+            .expected("':'")
+            .help(format!("a field is given as `{}: value`", field.name))
+    }
+}
@@ -1,6 +1,14 @@
 #![allow(dead_code)]
 
 use core::fmt::Display;
+use std::borrow::Cow;
+
+use unicode_xid::UnicodeXID;
+
+use super::{
+    span::Span,
+    trivia::{Trivia, TriviaKind, TriviaToken, TriviaTokens},
+};
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Tokens {
@@ -8,7 +16,7 @@ pub enum Tokens {
     Op(Operator),
     /// Contains all valid keywords
     Kw(Keywords),
-    ///  -> // comment I am
+    ///  -> // comment I am, or /* comment I am */
     Comment,
     /// Could be anything that identifies another thing
     /// let **id** = "identifier";
@@ -29,6 +37,10 @@ pub enum Tokens {
     BoolFalse,
     /// :
     Colon,
+    /// ::, separates the parts of a path, `Shape::Circle`
+    DoubleColon,
+    /// =>, in between the pattern and the body of a match arm
+    FatArrow,
     /// ;
     SemiColon,
     /// ,
@@ -37,6 +49,8 @@ pub enum Tokens {
     Bang,
     /// .
     Dot,
+    /// ..., the variadic arguments of a extern function, `extern fn printf(fmt: i8^, ...) i32`
+    Ellipsis,
     /// A tab
     Tab,
     /// (
@@ -96,12 +110,74 @@ pub enum Operator {
     MinusIs,
     /// /
     Slash,
+    /// /=
+    SlashIs,
+    /// %
+    Percent,
+    /// %=
+    PercentIs,
+    /// <<
+    ShiftLeft,
+    /// >>
+    ShiftRight,
+    /// ^ in between two values, the lexer always returns '^' as a [`Tokens::Pointer`] so the
+    /// parser decides if it is a pointer or a xor
+    Xor,
+    /// ~
+    Tilde,
+    /// .. the end is exclusive
+    Range,
+    /// ..= the end is inclusive
+    RangeEq,
+}
+
+impl Operator {
+    /// The precedence of the binary math operators, a higher precedence binds tighter.
+    /// Returns None for operators that aren't binary math operators.
+    ///
+    /// | precedence | operators |
+    /// |------------|-----------|
+    /// | 6          | * / %     |
+    /// | 5          | + -       |
+    /// | 4          | << >>     |
+    /// | 3          | &         |
+    /// | 2          | ^         |
+    /// | 1          | \|        |
+    pub fn precedence(&self) -> Option<u8> {
+        match self {
+            Operator::Times | Operator::Slash | Operator::Percent => Some(6),
+            Operator::Plus | Operator::Min => Some(5),
+            Operator::ShiftLeft | Operator::ShiftRight => Some(4),
+            Operator::And => Some(3),
+            Operator::Xor => Some(2),
+            Operator::Or => Some(1),
+            _ => None,
+        }
+    }
+
+    /// Returns the math operator of a compound assignment, `+=` -> `+`
+    pub fn compound_operator(&self) -> Option<Operator> {
+        match self {
+            Operator::PlusIs => Some(Operator::Plus),
+            Operator::MinusIs => Some(Operator::Min),
+            Operator::TimesIs => Some(Operator::Times),
+            Operator::SlashIs => Some(Operator::Slash),
+            Operator::PercentIs => Some(Operator::Percent),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Debug, PartialEq, Clone)]
 pub enum Keywords {
     /// let
     Let,
+    /// const
+    Const,
+    /// extern
+    Extern,
+    /// import
+    Import,
     /// if
     /// return
     Return,
@@ -112,6 +188,14 @@ pub enum Keywords {
     For,
     /// while
     While,
+    /// break
+    Break,
+    /// continue
+    Continue,
+    /// in
+    In,
+    /// match
+    Match,
     /// enum
     Enum,
     /// struct
@@ -145,6 +229,10 @@ pub enum TokenErrorMessages {
     /// This would be a char with no direct '\'' after it: -> [`'c`] <- a char token should always have a closing '\''
     CharNoEnd,
     InvalidChar,
+    /// A block comment that is never closed -> [`/* some comment`] <- missing [`*/`] at the end
+    BlockCommentNoEnd,
+    /// A escape sequence that doesn't exist or is malformed, contains the escape as written -> [`"\q"`]
+    InvalidEscape(String),
     /// This is for error messages with a token that doesn't exist.
     TokenInvalid(String),
 }
@@ -185,120 +273,476 @@ impl std::error::Error for TokenErrorMessages {
                 "Found a char with no end, consider adding a ' to the end of the char"
             }
             TokenErrorMessages::InvalidChar => "Found a invalid char",
+            TokenErrorMessages::BlockCommentNoEnd => "Found a unterminated block comment",
+            TokenErrorMessages::InvalidEscape(escape) => escape,
             TokenErrorMessages::TokenInvalid(str) => str,
         }
     }
 }
 
+/// [`LexDiagnostic`]
+/// A error found while lexing, it points at the source code that could not be lexed.
+///
+/// **kind** the kind of error that was found
+/// **value** the source text or value of the invalid token
+/// **span** the location of the invalid token
+#[derive(Debug, Clone, PartialEq)]
+pub struct LexDiagnostic {
+    pub kind: TokenErrorMessages,
+    pub value: String,
+    pub span: Span,
+}
+
+impl Display for LexDiagnostic {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let message = match &self.kind {
+            TokenErrorMessages::StringNoEnd => {
+                "Found a string with no end, consider adding a \" to the end of the string"
+                    .to_string()
+            }
+            TokenErrorMessages::CharNoEnd => {
+                "Found a char with no end, consider adding a ' to the end of the char".to_string()
+            }
+            TokenErrorMessages::InvalidChar => {
+                format!("Found a invalid char literal '{}'", self.value)
+            }
+            TokenErrorMessages::BlockCommentNoEnd => {
+                "Found a unterminated block comment, consider adding a */ to close it".to_string()
+            }
+            TokenErrorMessages::InvalidEscape(escape) => {
+                format!("Found a invalid escape sequence '{escape}'")
+            }
+            TokenErrorMessages::TokenInvalid(token) => format!("Found a invalid token '{token}'"),
+        };
+        write!(
+            f,
+            "[Lex Error] {message} on line {}, column {}",
+            self.span.line, self.span.column
+        )
+    }
+}
+
 #[derive(Debug, Clone, PartialEq)]
-pub struct Token {
+pub struct Token<'src> {
     pub line: usize,
     pub token_type: Tokens,
-    pub value: String,
+    /// The value of the token, this borrows the source unless the value had to be decoded, for
+    /// example a string with escape sequences or a number with underscores
+    pub value: Cow<'src, str>,
+    /// The exact location of the token in the source code
+    pub span: Span,
 }
 
-pub struct Tokenizer {
-    chars: Vec<char>,
+pub struct Tokenizer<'src> {
+    source: &'src str,
+    /// The byte offset of the next char
     current_position: usize,
     prev_char: Option<char>,
+    /// The byte offset the token that is being lexed starts at
+    token_start: usize,
+    /// The byte offset every line starts at
+    line_starts: Vec<usize>,
+    file_id: usize,
 }
 
-impl Iterator for Tokenizer {
+impl<'src> Iterator for Tokenizer<'src> {
     type Item = char;
     fn next(&mut self) -> Option<Self::Item> {
-        let current_char = self.chars.get(self.current_position)?;
-        self.prev_char = Some(*current_char);
-        self.current_position += 1;
-        Some(current_char.to_owned())
+        let current_char = self.source[self.current_position..].chars().next()?;
+        self.prev_char = Some(current_char);
+        self.current_position += current_char.len_utf8();
+        Some(current_char)
     }
 }
 
-impl Token {
+impl<'src> Token<'src> {
     /// Creates a new [`Token`].
     ///
     /// It expects:
     ///
-    /// The line on which the token was found,    
+    /// The line on which the token was found,
     ///
     /// The type of token being of Tokens::*
     ///
     /// The value of the token being either a direct token or for example a Tokens::String -> value is the string
-    fn new(line: usize, token_type: Tokens, value: &str) -> Token {
+    fn new(line: usize, token_type: Tokens, value: impl Into<Cow<'src, str>>) -> Token<'src> {
         Token {
             line,
             token_type,
-            value: value.to_string(),
+            value: value.into(),
+            span: Span::default(),
         }
     }
 }
 
-impl Tokenizer {
-    fn until_char(&self, char: char) -> Vec<char> {
-        self.chars
-            .iter()
-            .take_while(|x| **x != char)
-            .map(|x| x.to_owned())
-            .collect()
-    }
-    fn until_char_mut(&mut self, char: char) -> Vec<char> {
-        let mut up_until = Vec::new();
-        for token in self.by_ref() {
-            if token == char {
+impl<'src> Tokenizer<'src> {
+    /// walks back the iterator to pos - n
+    fn advance_back(&mut self, n: usize) {
+        for _ in 0..n {
+            let Some(char) = self.source[..self.current_position].chars().next_back() else {
                 break;
+            };
+            self.current_position -= char.len_utf8();
+        }
+        self.prev_char = self.peek(0);
+    }
+
+    /// Returns the char n positions after the previous char without consuming it
+    fn peek(&self, n: usize) -> Option<char> {
+        self.source[self.current_position..].chars().nth(n)
+    }
+
+    /// Returns the source text of the token that is being lexed, up to the current position
+    fn token_text(&self) -> &'src str {
+        &self.source[self.token_start..self.current_position]
+    }
+
+    /// Reads the rest of a number literal where first is the already consumed first digit.
+    ///
+    /// Supports the 0x, 0b and 0o prefixes, underscores for readability, floats with an optional
+    /// exponent and a type suffix, `0xFF_u8`, `1.5e-3f32`, `12i8`.
+    fn number_literal(&mut self, first: char) -> Tokens {
+        let mut token_type = Tokens::Number;
+
+        let radix = match (first, self.peek(0)) {
+            ('0', Some(prefix @ ('x' | 'b' | 'o'))) => {
+                self.next();
+                match prefix {
+                    'x' => 16,
+                    'b' => 2,
+                    _ => 8,
+                }
             }
-            up_until.push(token);
+            _ => 10,
+        };
+
+        while let Some(char) = self.next() {
+            match char {
+                'a'..='f' | 'A'..='F' if radix == 16 => continue,
+                '0'..='9' => continue,
+                // A second dot means this is a range like 0..10 and not a float
+                '.' if radix == 10 && self.peek(0) == Some('.') => {
+                    self.advance_back(1);
+                    break;
+                }
+                // if we find a dot anywhere inbetween the numbers we consider it to be a
+                // floating point number
+                '.' if radix == 10 => token_type = Tokens::FloatNumber,
+                // 1e10, 1.5E-3, the exponent needs at least one digit to not be an identifier
+                'e' | 'E' if radix == 10 && self.exponent_follows() => {
+                    token_type = Tokens::FloatNumber;
+                    if let Some('+' | '-') = self.peek(0) {
+                        self.next();
+                    }
+                }
+                // We allow users to have underscores in their numbers for readability
+                '_' => continue,
+                _ => {
+                    // We consider this to be the end of the number, we also make sure to advance
+                    // back so that the char doesn't get consumed and can be used by the lexer
+                    self.advance_back(1);
+                    break;
+                }
+            }
+        }
+
+        if self.number_suffix(radix) == Some("f32") {
+            token_type = Tokens::FloatNumber;
         }
-        up_until
+        token_type
     }
-    /// walks back the iterator to pos - n
-    fn advance_back(&mut self, n: usize) {
-        self.current_position -= n;
-        self.prev_char = self.chars.get(self.current_position).copied();
+
+    /// The value of the number that is being lexed, the underscores are left out
+    fn number_value(&self) -> Cow<'src, str> {
+        let text = self.token_text();
+        if text.contains('_') {
+            Cow::Owned(text.replace('_', ""))
+        } else {
+            Cow::Borrowed(text)
+        }
+    }
+
+    /// Checks if the chars after a 'e' form an exponent, meaning an optional sign and a digit
+    fn exponent_follows(&self) -> bool {
+        match self.peek(0) {
+            Some('+' | '-') => matches!(self.peek(1), Some('0'..='9')),
+            Some('0'..='9') => true,
+            _ => false,
+        }
+    }
+
+    /// Consumes a type suffix like `u8` or `f32` if the number is followed by one.
+    ///
+    /// The suffix has to make up the whole identifier, `10u8x` is not a suffixed number.
+    fn number_suffix(&mut self, radix: u32) -> Option<&'src str> {
+        let rest = &self.source[self.current_position..];
+        let length = rest
+            .find(|char: char| !(char.is_ascii_alphanumeric() || char == '_'))
+            .unwrap_or(rest.len());
+        let suffix = match &rest[..length] {
+            suffix @ ("u8" | "i8" | "i32") => suffix,
+            "f32" if radix == 10 => "f32",
+            _ => return None,
+        };
+        self.current_position += suffix.len();
+        self.prev_char = suffix.chars().last();
+        Some(suffix)
+    }
+
+    /// Reads a block comment, expects the '/*' to be consumed already.
+    ///
+    /// Block comments nest, so `/* a /* b */ c */` is one comment, this makes it possible to
+    /// comment out code that already contains block comments.
+    fn block_comment(&mut self, line: usize) -> Token<'src> {
+        let mut depth = 1;
+        while let Some(char) = self.next() {
+            match (char, self.peek(0)) {
+                ('/', Some('*')) => {
+                    self.next();
+                    depth += 1;
+                }
+                ('*', Some('/')) => {
+                    self.next();
+                    depth -= 1;
+                    if depth == 0 {
+                        let text = self.token_text();
+                        return Token::new(line, Tokens::Comment, &text[2..text.len() - 2]);
+                    }
+                }
+                _ => continue,
+            }
+        }
+
+        Token::new(
+            line,
+            Tokens::InvalidToken(TokenErrorMessages::BlockCommentNoEnd),
+            "The block comment doesn't have a end",
+        )
+    }
+
+    /// Consumes the closing quote of a char if it's there, so it doesn't get seen as the start of
+    /// another char
+    fn consume_char_end(&mut self) {
+        if self.peek(0) == Some('\'') {
+            self.next();
+        }
+    }
+
+    /// Decodes the escape sequence after a '\', expects the '\' to be the previous character.
+    ///
+    /// Supports `\n \t \r \" \' \\ \0`, `\xNN` up to `\x7F` and `\u{...}` with 1 to 6 hex
+    /// digits. Returns the escape as written if it isn't valid, only valid chars are consumed so a
+    /// closing quote never becomes part of a invalid escape.
+    fn escape_sequence(&mut self) -> Result<char, String> {
+        let Some(escape) = self.next() else {
+            return Err("\\".to_string());
+        };
+        let mut written = format!("\\{escape}");
+        match escape {
+            'n' => Ok('\n'),
+            't' => Ok('\t'),
+            'r' => Ok('\r'),
+            '"' => Ok('"'),
+            '\'' => Ok('\''),
+            '\\' => Ok('\\'),
+            '0' => Ok('\0'),
+            'x' => {
+                let digits = self.hex_digits(2);
+                written.push_str(digits);
+                match u8::from_str_radix(digits, 16) {
+                    Ok(byte) if digits.len() == 2 && byte.is_ascii() => Ok(byte as char),
+                    _ => Err(written),
+                }
+            }
+            'u' => {
+                if self.peek(0) != Some('{') {
+                    return Err(written);
+                }
+                self.next();
+                written.push('{');
+                let digits = self.hex_digits(6);
+                written.push_str(digits);
+                if self.peek(0) != Some('}') {
+                    return Err(written);
+                }
+                self.next();
+                written.push('}');
+                u32::from_str_radix(digits, 16)
+                    .ok()
+                    .and_then(char::from_u32)
+                    .ok_or(written)
+            }
+            _ => Err(written),
+        }
+    }
+
+    /// Consumes up to max hex digits
+    fn hex_digits(&mut self, max: usize) -> &'src str {
+        let start = self.current_position;
+        while self.current_position - start < max
+            && matches!(self.peek(0), Some(char) if char.is_ascii_hexdigit())
+        {
+            self.next();
+        }
+        &self.source[start..self.current_position]
+    }
+
+    /// Splits the source in between the byte offsets start and end into trivia, the lexer only
+    /// skips whitespace, newlines and semicolons so those are the only chars expected in between
+    /// tokens.
+    fn trivia(&self, start: usize, end: usize) -> Vec<Trivia<'src>> {
+        let mut trivia: Vec<Trivia> = Vec::new();
+        let bytes = self.source.as_bytes();
+        let mut position = start;
+        while position < end {
+            let trivia_start = position;
+            let kind = match (bytes[position], bytes.get(position + 1)) {
+                (b'\n', _) => {
+                    position += 1;
+                    TriviaKind::Newline
+                }
+                (b'\r', Some(b'\n')) => {
+                    position += 2;
+                    TriviaKind::Newline
+                }
+                (b';', _) => {
+                    position += 1;
+                    TriviaKind::Semicolon
+                }
+                _ => {
+                    while position < end && !matches!(bytes[position], b'\n' | b';') {
+                        // A "\r\n" is a newline, so the whitespace ends before it
+                        if bytes[position] == b'\r' && bytes.get(position + 1) == Some(&b'\n') {
+                            break;
+                        }
+                        position += 1;
+                    }
+                    TriviaKind::Whitespace
+                }
+            };
+            trivia.push(Trivia {
+                kind,
+                text: &self.source[trivia_start..position],
+                span: self.span(trivia_start, position),
+            });
+        }
+        trivia
+    }
+
+    /// Sets the id of the file that is being tokenized, this id ends up in the span of every token.
+    pub fn with_file_id(mut self, file_id: usize) -> Self {
+        self.file_id = file_id;
+        self
+    }
+
+    /// Returns the span of the source in between the byte offsets start and end.
+    ///
+    /// The line and column start at 1, the column is counted in chars and not in bytes.
+    pub fn span(&self, start: usize, end: usize) -> Span {
+        let line = self
+            .line_starts
+            .partition_point(|line_start| *line_start <= start);
+        self.span_on_line(start, end, line - 1)
+    }
+
+    /// Same as [`Tokenizer::span`] for when the line the span starts on is already known, line
+    /// starting at 0 like [`Token::line`]. This skips the search for the line.
+    fn span_on_line(&self, start: usize, end: usize, line: usize) -> Span {
+        let line_start = self.line_starts[line];
+        // Counts the chars by skipping the utf-8 continuation bytes, which is a lot faster than
+        // decoding the chars
+        let column = self.source.as_bytes()[line_start..start]
+            .iter()
+            .filter(|byte| (**byte as i8) >= -0x40)
+            .count()
+            + 1;
+        Span::new(self.file_id, start, end, line + 1, column)
     }
 }
 
-pub trait Tokenize {
+pub trait Tokenize<'src> {
     /// returns a [`Tokens::String`] token
     /// Expects '"' to be the previous character
-    fn token_str(&mut self, line: usize) -> Token;
-    fn token_comment(&mut self, line: usize) -> Token;
+    fn token_str(&mut self, line: usize) -> Token<'src>;
+    fn token_comment(&mut self, line: usize) -> Token<'src>;
     /// returns a [`Tokens::Char`] token
     /// Expects ''' to be the previous character
-    fn token_char(&mut self, line: usize) -> Token;
+    fn token_char(&mut self, line: usize) -> Token<'src>;
     /// returns a [`Tokens::Op(Operator::Or || Operator::OrOr)`] token
     /// Expects ''' to be the previous character
-    fn token_or(&mut self, line: usize) -> Token;
+    fn token_or(&mut self, line: usize) -> Token<'src>;
     /// returns a [`Tokens::Identifier`] || [`Tokens::Kw`]  token
-    /// Expects the previous character to be of any letter type
-    fn token_identifier(&mut self, line: usize) -> Token;
+    /// Expects the previous character to be a '_' or a unicode XID_Start character
+    fn token_identifier(&mut self, line: usize) -> Token<'src>;
     /// returns a [`Tokens::Number`] || [`Tokens::FloatNumber`]  token
     /// Expects the previous character to be numeric
-    fn token_num(&mut self, line: usize) -> Token;
-    /// returns either a [`Tokens::Op(Operator::Eq)`] token or a [`Tokens::Op(Operator::EqEq)`] token
+    fn token_num(&mut self, line: usize) -> Token<'src>;
+    /// returns a [`Tokens::Op(Operator::Eq)`], [`Tokens::Op(Operator::EqEq)`] or [`Tokens::FatArrow`] token
     /// Expects a '=' character to be the previous character
-    fn token_eq(&mut self, line: usize) -> Token;
-    /// returns either a [`Tokens::Op(Operator::Less)`] token or a [`Tokens::Op(Operator::LessEq)`] token
+    fn token_eq(&mut self, line: usize) -> Token<'src>;
+    /// returns a [`Tokens::Op(Operator::Less)`], [`Tokens::Op(Operator::LessEq)`] or [`Tokens::Op(Operator::ShiftLeft)`] token
     /// Expects a '<' character to be the previous character
-    fn token_less(&mut self, line: usize) -> Token;
-    /// returns either a [`Tokens::Op(Operator::More)`] token or a [`Tokens::Op(Operator::MoreEq)`] token
+    fn token_less(&mut self, line: usize) -> Token<'src>;
+    /// returns a [`Tokens::Op(Operator::More)`], [`Tokens::Op(Operator::MoreEq)`] or [`Tokens::Op(Operator::ShiftRight)`] token
     /// Expects a '<' character to be the previous character
-    fn token_more(&mut self, line: usize) -> Token;
+    fn token_more(&mut self, line: usize) -> Token<'src>;
     /// returns either a [`Tokens::Bang`] token or a [`Tokens::Op(Operator::Nq)`] token
     /// Expects a '!' character to be the previous character
-    fn token_bang(&mut self, line: usize) -> Token;
+    fn token_bang(&mut self, line: usize) -> Token<'src>;
     /// returns either a [`Tokens::Op(Operator::And)`] token or a [`Tokens::Op(Operator::AndAnd)`] token
     /// Expects a '&' character to be the previous character
-    fn token_and(&mut self, line: usize) -> Token;
-    fn tokens_plus(&mut self, line: usize) -> Token;
-    fn tokens_times(&mut self, line: usize) -> Token;
-    fn tokens_minus(&mut self, line: usize) -> Token;
+    fn token_and(&mut self, line: usize) -> Token<'src>;
+    fn tokens_plus(&mut self, line: usize) -> Token<'src>;
+    fn tokens_times(&mut self, line: usize) -> Token<'src>;
+    fn tokens_minus(&mut self, line: usize) -> Token<'src>;
+    /// returns either a [`Tokens::Op(Operator::Percent)`] token or a [`Tokens::Op(Operator::PercentIs)`] token
+    /// Expects a '%' character to be the previous character
+    fn tokens_percent(&mut self, line: usize) -> Token<'src>;
+    /// returns a [`Tokens::Dot`] token, a [`Tokens::Op(Operator::Range)`] token, a
+    /// [`Tokens::Op(Operator::RangeEq)`] token or a [`Tokens::Ellipsis`] token
+    /// Expects a '.' character to be the previous character
+    fn tokens_dot(&mut self, line: usize) -> Token<'src>;
+    /// returns either a [`Tokens::Colon`] token or a [`Tokens::DoubleColon`] token
+    /// Expects a ':' character to be the previous character
+    fn tokens_colon(&mut self, line: usize) -> Token<'src>;
 }
 
-impl Tokenize for Tokenizer {
-    fn token_str(&mut self, line: usize) -> Token {
+impl<'src> Tokenize<'src> for Tokenizer<'src> {
+    fn token_str(&mut self, line: usize) -> Token<'src> {
         assert_eq!(self.prev_char, Some('"'));
-        let tokens_until = self.until_char_mut('"');
-        if tokens_until.is_empty() {
+        let content_start = self.current_position;
+        // Only strings with escape sequences need to be decoded, all others borrow the source
+        let mut decoded: Option<String> = None;
+        let mut has_end = false;
+        let mut invalid_escape = None;
+        while let Some(char) = self.next() {
+            match char {
+                '"' => {
+                    has_end = true;
+                    break;
+                }
+                '\\' => {
+                    let decoded = decoded.get_or_insert_with(|| {
+                        self.source[content_start..self.current_position - 1].to_string()
+                    });
+                    match self.escape_sequence() {
+                        Ok(char) => decoded.push(char),
+                        // We keep going until the end of the string so the rest of it doesn't get
+                        // lexed as tokens, only the first invalid escape gets reported
+                        Err(escape) => {
+                            invalid_escape.get_or_insert(escape);
+                        }
+                    }
+                }
+                char => {
+                    if let Some(decoded) = decoded.as_mut() {
+                        decoded.push(char);
+                    }
+                }
+            }
+        }
+
+        if !has_end {
             return Token::new(
                 line,
                 Tokens::InvalidToken(TokenErrorMessages::StringNoEnd),
@@ -306,20 +750,42 @@ impl Tokenize for Tokenizer {
             );
         };
 
-        let str: String = tokens_until.into_iter().take_while(|x| *x != '"').collect();
-        Token::new(line, Tokens::String, &str)
+        if let Some(escape) = invalid_escape {
+            return Token::new(
+                line,
+                Tokens::InvalidToken(TokenErrorMessages::InvalidEscape(escape.clone())),
+                escape,
+            );
+        }
+
+        match decoded {
+            Some(decoded) => Token::new(line, Tokens::String, decoded),
+            None => Token::new(
+                line,
+                Tokens::String,
+                &self.source[content_start..self.current_position - 1],
+            ),
+        }
     }
 
-    fn token_comment(&mut self, line: usize) -> Token {
+    fn token_comment(&mut self, line: usize) -> Token<'src> {
         assert_eq!(self.prev_char, Some('/'));
         let Some(token_slash) = self.next() else {
            return Token::new(line, '/'.into(), "/");
         };
         match token_slash {
             '/' => {
-                let tokens_until: String = self.until_char_mut('\n').iter().collect();
-                return Token::new(line, Tokens::Comment, &tokens_until);
+                // The newline isn't part of the comment, the lexer still has to count it
+                while let Some(char) = self.peek(0) {
+                    if char == '\n' || (char == '\r' && self.peek(1) == Some('\n')) {
+                        break;
+                    }
+                    self.next();
+                }
+                return Token::new(line, Tokens::Comment, &self.token_text()[2..]);
             }
+            '*' => return self.block_comment(line),
+            '=' => return Token::new(line, "/=".into(), "/="),
             _ => {
                 self.advance_back(1);
                 return Token::new(line, '/'.into(), "/");
@@ -327,111 +793,90 @@ impl Tokenize for Tokenizer {
         }
     }
 
-    fn token_char(&mut self, line: usize) -> Token {
+    fn token_char(&mut self, line: usize) -> Token<'src> {
         assert_eq!(self.prev_char, Some('\''));
-        let Some(tokens_until) = self.next() else {
+        let Some(char) = self.next() else {
             return Token::new(
                 line,
-                Tokens::InvalidToken(TokenErrorMessages::StringNoEnd),
-                "The string doesn't have a end",
+                Tokens::InvalidToken(TokenErrorMessages::CharNoEnd),
+                "The char doesn't have a end",
             );
         };
 
-        match tokens_until {
-            'a'..='z' | 'A'..='Z' | '\\' => {
-                let char_close = self.next();
-
-                match char_close.unwrap() {
-                    'n' => {
-                        let Some('\'') =  self.next() else {
-                            return Token::new(line, Tokens::InvalidToken(TokenErrorMessages::CharNoEnd), &tokens_until.to_string(),);
-                        };
-                        return Token::new(line, Tokens::Char, "\n");
-                    }
-                    't' => {
-                        let Some('\'') =  self.next() else {
-                            return Token::new(line, Tokens::InvalidToken(TokenErrorMessages::CharNoEnd), &tokens_until.to_string(),);
-                        };
-                        return Token::new(line, Tokens::Char, "\t");
-                    }
-                    '\\' => {
-                        let Some('\'') =  self.next() else {
-                            return Token::new(line, Tokens::InvalidToken(TokenErrorMessages::CharNoEnd), &tokens_until.to_string(),);
-                        };
-                        return Token::new(line, Tokens::Char, "\\");
-                    }
-                    '0' => {
-                        let Some('\'') =  self.next() else {
-                            return Token::new(line, Tokens::InvalidToken(TokenErrorMessages::CharNoEnd), &tokens_until.to_string(),);
-                        };
-                        return Token::new(line, Tokens::Char, "\0");
-                    }
-                    _ => {}
-                };
-
-                if char_close == Some('\'') {
-                    Token::new(line, Tokens::Char, &tokens_until.to_string())
-                } else {
-                    Token::new(
+        let value: Cow<'src, str> = match char {
+            // A empty char, `''`
+            '\'' => {
+                return Token::new(line, Tokens::InvalidToken(TokenErrorMessages::InvalidChar), "");
+            }
+            '\n' => {
+                return Token::new(line, Tokens::InvalidToken(TokenErrorMessages::CharNoEnd), "");
+            }
+            '\\' => match self.escape_sequence() {
+                // A char is a single byte, so only ascii chars fit
+                Ok(char) if char.is_ascii() => Cow::Owned(char.to_string()),
+                Ok(char) => {
+                    self.consume_char_end();
+                    return Token::new(
+                        line,
+                        Tokens::InvalidToken(TokenErrorMessages::InvalidChar),
+                        char.to_string(),
+                    );
+                }
+                Err(escape) => {
+                    self.consume_char_end();
+                    return Token::new(
                         line,
-                        Tokens::InvalidToken(TokenErrorMessages::CharNoEnd),
-                        &tokens_until.to_string(),
-                    )
+                        Tokens::InvalidToken(TokenErrorMessages::InvalidEscape(escape.clone())),
+                        escape,
+                    );
                 }
+            },
+            // A char is a single byte, so only ascii chars fit
+            char if char.is_ascii() => Cow::Borrowed(&self.token_text()[1..]),
+            _ => {
+                self.consume_char_end();
+                return Token::new(
+                    line,
+                    Tokens::InvalidToken(TokenErrorMessages::InvalidChar),
+                    char.to_string(),
+                );
             }
-            _ => Token::new(
+        };
+
+        if self.peek(0) == Some('\'') {
+            self.next();
+            Token::new(line, Tokens::Char, value)
+        } else {
+            Token::new(
                 line,
-                Tokens::InvalidToken(TokenErrorMessages::InvalidChar),
-                &tokens_until.to_string(),
-            ),
+                Tokens::InvalidToken(TokenErrorMessages::CharNoEnd),
+                value,
+            )
         }
     }
 
-    fn token_identifier(&mut self, line: usize) -> Token {
+    fn token_identifier(&mut self, line: usize) -> Token<'src> {
         assert!(self.prev_char.is_some());
-        assert!(self.prev_char.unwrap().is_alphabetic());
+        assert!(is_identifier_start(self.prev_char.unwrap()));
 
-        let mut str = String::from(self.prev_char.unwrap());
         while let Some(char) = self.next() {
             match char {
-                'a'..='z' | 'A'..='Z' | '_' | '0'..='9' => str.push(char),
+                char if char.is_xid_continue() => continue,
                 _ => {
                     self.advance_back(1);
                     break;
                 }
             }
         }
-        Token::new(line, str.as_str().into(), &str)
+        let str = self.token_text();
+        Token::new(line, str.into(), str)
     }
 
-    fn token_num(&mut self, line: usize) -> Token {
+    fn token_num(&mut self, line: usize) -> Token<'src> {
         if let Some(prev) = self.prev_char {
             assert!(prev.is_numeric());
-            #[allow(unused_variables)]
-            let mut token_type = Tokens::Number;
-            let mut nums = String::from(prev);
-            while let Some(char) = self.next() {
-                match char {
-                    '0'..='9' => nums.push(char),
-                    // if we find a dot anywhere inbetween the numbers we consider it to be a
-                    // floating point number
-                    #[allow(unused_assignments)]
-                    '.' => {
-                        token_type = Tokens::FloatNumber;
-                        nums.push(char);
-                    }
-                    // We allow users to have underscores in their numbers for readability
-                    '_' => continue,
-                    _ => {
-                        // We consider this to be the end and return the number token, we also make
-                        // sure to advance back so that the char doesn't get consumed and can be
-                        // used by the lexer
-                        self.advance_back(1);
-                        return Token::new(line, token_type, &nums);
-                    }
-                }
-            }
-            return Token::new(line, token_type, &nums);
+            let token_type = self.number_literal(prev);
+            return Token::new(line, token_type, self.number_value());
         };
 
         Token::new(
@@ -443,13 +888,14 @@ impl Tokenize for Tokenizer {
         )
     }
 
-    fn token_eq(&mut self, line: usize) -> Token {
+    fn token_eq(&mut self, line: usize) -> Token<'src> {
         if let Some(prev) = self.prev_char {
             assert_eq!(prev, '=');
             match self.next() {
                 Some(char) => {
                     match char {
                         '=' => return Token::new(line, "==".into(), "=="),
+                        '>' => return Token::new(line, "=>".into(), "=>"),
                         _ => {
                             // we advance the iterator back with 1 since the previous token is needed for the tokenizer
                             self.advance_back(1);
@@ -470,13 +916,14 @@ impl Tokenize for Tokenizer {
         )
     }
 
-    fn token_less(&mut self, line: usize) -> Token {
+    fn token_less(&mut self, line: usize) -> Token<'src> {
         if let Some(prev) = self.prev_char {
             assert_eq!(prev, '<');
             match self.next() {
                 Some(char) => {
                     match char {
                         '=' => return Token::new(line, "<=".into(), "<="),
+                        '<' => return Token::new(line, "<<".into(), "<<"),
                         _ => {
                             // we advance the iterator back with 1 since the previous token is needed for the tokenizer
                             self.advance_back(1);
@@ -497,13 +944,14 @@ impl Tokenize for Tokenizer {
         )
     }
 
-    fn token_more(&mut self, line: usize) -> Token {
+    fn token_more(&mut self, line: usize) -> Token<'src> {
         if let Some(prev) = self.prev_char {
             assert_eq!(prev, '>');
             match self.next() {
                 Some(char) => {
                     match char {
                         '=' => return Token::new(line, ">=".into(), ">="),
+                        '>' => return Token::new(line, ">>".into(), ">>"),
                         _ => {
                             // we advance the iterator back with 1 since the previous token is needed for the tokenizer
                             self.advance_back(1);
@@ -524,7 +972,7 @@ impl Tokenize for Tokenizer {
         )
     }
 
-    fn token_bang(&mut self, line: usize) -> Token {
+    fn token_bang(&mut self, line: usize) -> Token<'src> {
         if let Some(prev) = self.prev_char {
             assert_eq!(prev, '!');
             match self.next() {
@@ -551,7 +999,7 @@ impl Tokenize for Tokenizer {
         )
     }
 
-    fn token_and(&mut self, line: usize) -> Token {
+    fn token_and(&mut self, line: usize) -> Token<'src> {
         if let Some(prev) = self.prev_char {
             assert_eq!(prev, '&');
             match self.next() {
@@ -578,9 +1026,8 @@ impl Tokenize for Tokenizer {
         )
     }
 
-    fn token_or(&mut self, line: usize) -> Token {
+    fn token_or(&mut self, line: usize) -> Token<'src> {
         let Some(next) = self.next() else {
-            self.advance_back(1);
             return Token::new(line, "|".into(), "|");
         };
         match next {
@@ -592,7 +1039,7 @@ impl Tokenize for Tokenizer {
         }
     }
 
-    fn tokens_plus(&mut self, line: usize) -> Token {
+    fn tokens_plus(&mut self, line: usize) -> Token<'src> {
         if let Some(prev) = self.prev_char {
             assert_eq!(prev, '+');
             match self.next() {
@@ -619,7 +1066,7 @@ impl Tokenize for Tokenizer {
         )
     }
 
-    fn tokens_times(&mut self, line: usize) -> Token {
+    fn tokens_times(&mut self, line: usize) -> Token<'src> {
         if let Some(prev) = self.prev_char {
             assert_eq!(prev, '*');
             match self.next() {
@@ -647,7 +1094,7 @@ impl Tokenize for Tokenizer {
         )
     }
 
-    fn tokens_minus(&mut self, line: usize) -> Token {
+    fn tokens_minus(&mut self, line: usize) -> Token<'src> {
         if let Some(prev) = self.prev_char {
             assert_eq!(prev, '-');
             match self.next() {
@@ -655,20 +1102,12 @@ impl Tokenize for Tokenizer {
                     '=' => {
                         return Token::new(line, "-=".into(), "-=");
                     }
-                    '0'..='9'  => {
-                        let mut str_nums = String::from("-");
-                        str_nums.push(char);
-
-                        while let Some(char) = self.next() {
-                            match char {
-                                '0'..='9' => str_nums.push(char),
-                                _ => {
-                                    self.advance_back(1);
-                                    return Token::new(line, Tokens::NegativeNumber, &str_nums);
-                                }
-                            }
-                        }
-                        return Token::new(line, Tokens::NegativeNumber, &str_nums);
+                    '0'..='9' => {
+                        let token_type = match self.number_literal(char) {
+                            Tokens::Number => Tokens::NegativeNumber,
+                            float => float,
+                        };
+                        return Token::new(line, token_type, self.number_value());
                     }
                     _ => {
                         self.advance_back(1);
@@ -689,51 +1128,194 @@ impl Tokenize for Tokenizer {
             "no prev token",
         )
     }
+
+    fn tokens_percent(&mut self, line: usize) -> Token<'src> {
+        assert_eq!(self.prev_char, Some('%'));
+        let Some(next) = self.next() else {
+            return Token::new(line, '%'.into(), "%");
+        };
+        match next {
+            '=' => Token::new(line, "%=".into(), "%="),
+            _ => {
+                self.advance_back(1);
+                Token::new(line, '%'.into(), "%")
+            }
+        }
+    }
+
+    fn tokens_dot(&mut self, line: usize) -> Token<'src> {
+        assert_eq!(self.prev_char, Some('.'));
+        if self.peek(0) != Some('.') {
+            return Token::new(line, '.'.into(), ".");
+        }
+        self.next();
+        match self.peek(0) {
+            Some('=') => {
+                self.next();
+                Token::new(line, "..=".into(), "..=")
+            }
+            Some('.') => {
+                self.next();
+                Token::new(line, "...".into(), "...")
+            }
+            _ => Token::new(line, "..".into(), ".."),
+        }
+    }
+
+    fn tokens_colon(&mut self, line: usize) -> Token<'src> {
+        assert_eq!(self.prev_char, Some(':'));
+        if self.peek(0) != Some(':') {
+            return Token::new(line, ':'.into(), ":");
+        }
+        self.next();
+        Token::new(line, "::".into(), "::")
+    }
 }
 
-pub trait Lexer {
-    fn new(chars: &str) -> Self;
+pub trait Lexer<'src> {
+    fn new(source: &'src str) -> Self;
+
+    /// Lexes the source into tokens, tokens that are not part of the language get dropped.
+    ///
+    /// Use [`Lexer::lex_with_diagnostics`] to get errors for the invalid tokens instead.
+    fn lex(tokenizer: &mut Tokenizer<'src>) -> Vec<Token<'src>> {
+        let mut tokens = Self::lex_all(tokenizer);
+        tokens.retain(|token| {
+            !matches!(
+                token.token_type,
+                Tokens::InvalidToken(TokenErrorMessages::TokenInvalid(_))
+            )
+        });
+        tokens
+    }
+
+    /// Lexes the source into tokens, every invalid token is returned as a [`LexDiagnostic`]
+    /// instead of being part of the tokens.
+    fn lex_with_diagnostics(
+        tokenizer: &mut Tokenizer<'src>,
+    ) -> (Vec<Token<'src>>, Vec<LexDiagnostic>) {
+        let mut tokens = Vec::new();
+        let mut diagnostics = Vec::new();
+        for token in Self::lex_all(tokenizer) {
+            match token.token_type {
+                Tokens::InvalidToken(kind) => {
+                    let mut span = token.span;
+                    // The rest of the source is part of the comment, so we point at the opening '/*'
+                    if kind == TokenErrorMessages::BlockCommentNoEnd {
+                        span.end = span.start + 2;
+                    }
+                    diagnostics.push(LexDiagnostic {
+                        kind,
+                        value: token.value.into_owned(),
+                        span,
+                    })
+                }
+                _ => tokens.push(token),
+            }
+        }
+        (tokens, diagnostics)
+    }
+
+    /// Lexes the source into a lossless token stream, the whitespace, newlines, semicolons and
+    /// comments are kept as trivia in front of the tokens.
+    ///
+    /// Concatenating the trivia and token texts gives back the exact source, see
+    /// [`TriviaTokens::to_source`].
+    fn lex_with_trivia(tokenizer: &mut Tokenizer<'src>) -> TriviaTokens<'src> {
+        let tokens = Self::lex_all(tokenizer);
+        let mut lossless = TriviaTokens::default();
+        let mut leading_trivia = Vec::new();
+        let mut position = 0;
+        for token in tokens {
+            leading_trivia.extend(tokenizer.trivia(position, token.span.start));
+            position = token.span.end;
 
-    fn lex(tokenizer: &mut Tokenizer) -> Vec<Token> {
+            let text = &tokenizer.source[token.span.start..token.span.end];
+            if token.token_type == Tokens::Comment {
+                let kind = if text.starts_with("/*") {
+                    TriviaKind::BlockComment
+                } else {
+                    TriviaKind::LineComment
+                };
+                leading_trivia.push(Trivia {
+                    kind,
+                    text,
+                    span: token.span,
+                });
+                continue;
+            }
+            lossless.tokens.push(TriviaToken {
+                token,
+                text,
+                leading_trivia: std::mem::take(&mut leading_trivia),
+            });
+        }
+        leading_trivia.extend(tokenizer.trivia(position, tokenizer.source.len()));
+        lossless.trailing_trivia = leading_trivia;
+        lossless
+    }
+
+    /// Lexes the source into tokens, including all the [`Tokens::InvalidToken`] tokens
+    fn lex_all(tokenizer: &mut Tokenizer<'src>) -> Vec<Token<'src>> {
         let mut tokens: Vec<Token> = Vec::new();
         let mut line: usize = 0;
         while let Some(char) = tokenizer.next() {
-            match char {
-                '\n' => line += 1,
-                ' ' => continue,
-                '"' => tokens.push(tokenizer.token_str(line)),
-                '=' => tokens.push(tokenizer.token_eq(line)),
-                '!' => tokens.push(tokenizer.token_bang(line)),
-                '<' => tokens.push(tokenizer.token_less(line)),
-                '>' => tokens.push(tokenizer.token_more(line)),
-                '\'' => tokens.push(tokenizer.token_char(line)),
-                '|' => tokens.push(tokenizer.token_or(line)),
-                '&' => tokens.push(tokenizer.token_and(line)),
-                '0'..='9' => tokens.push(tokenizer.token_num(line)),
-                'a'..='z' | 'A'..='Z' => tokens.push(tokenizer.token_identifier(line)),
-                '/' => tokens.push(tokenizer.token_comment(line)),
-                '+' => tokens.push(tokenizer.tokens_plus(line)),
-                '*' => tokens.push(tokenizer.tokens_times(line)),
-                '-' => tokens.push(tokenizer.tokens_minus(line)),
-                token => {
-                    let token = Token::new(line, token.into(), &token.to_string());
-                    if let Tokens::InvalidToken(_) = token.token_type {
-                        continue;
-                    }
-                    tokens.push(token)
+            // The position of the char we just consumed, every token starts here
+            let start = tokenizer.current_position - char.len_utf8();
+            tokenizer.token_start = start;
+            let mut token = match char {
+                '\n' => {
+                    line += 1;
+                    continue;
                 }
+                ' ' | '\t' | '\r' => continue,
+                // Semicolons are allowed after statements but they don't carry any meaning
+                ';' => continue,
+                '"' => tokenizer.token_str(line),
+                '=' => tokenizer.token_eq(line),
+                '!' => tokenizer.token_bang(line),
+                '<' => tokenizer.token_less(line),
+                '>' => tokenizer.token_more(line),
+                '\'' => tokenizer.token_char(line),
+                '|' => tokenizer.token_or(line),
+                '&' => tokenizer.token_and(line),
+                '0'..='9' => tokenizer.token_num(line),
+                char if is_identifier_start(char) => tokenizer.token_identifier(line),
+                '/' => tokenizer.token_comment(line),
+                '+' => tokenizer.tokens_plus(line),
+                '*' => tokenizer.tokens_times(line),
+                '-' => tokenizer.tokens_minus(line),
+                '%' => tokenizer.tokens_percent(line),
+                '.' => tokenizer.tokens_dot(line),
+                ':' => tokenizer.tokens_colon(line),
+                token => Token::new(line, token.into(), tokenizer.token_text()),
             };
+            token.span = tokenizer.span_on_line(start, tokenizer.current_position, line);
+            // Strings and block comments can span multiple lines
+            line += tokenizer.token_text().matches('\n').count();
+            tokens.push(token);
         }
         tokens
     }
 }
 
-impl Lexer for Tokenizer {
-    fn new(char: &str) -> Self {
+/// Identifiers start with a '_' or any unicode XID_Start character, `_unused`, `größe`
+fn is_identifier_start(char: char) -> bool {
+    char == '_' || char.is_xid_start()
+}
+
+impl<'src> Lexer<'src> for Tokenizer<'src> {
+    fn new(source: &'src str) -> Self {
+        let mut line_starts = vec![0];
+        line_starts.extend(source.match_indices('\n').map(|(offset, _)| offset + 1));
+
         Tokenizer {
-            chars: char.chars().collect(),
+            source,
             current_position: 0,
             prev_char: None,
+            token_start: 0,
+            line_starts,
+            file_id: 0,
         }
     }
 }
@@ -15,6 +15,8 @@ impl From<&str> for Tokens {
             "]" => Tokens::CloseBracket,
             "^" => Tokens::Pointer,
             ":" => Tokens::Colon,
+            "::" => Tokens::DoubleColon,
+            "=>" => Tokens::FatArrow,
             ";" => Tokens::SemiColon,
             "+" => Tokens::Op(Operator::Plus),
             "-" => Tokens::Op(Operator::Min),
@@ -26,6 +28,7 @@ impl From<&str> for Tokens {
             // All operator values
             "=" => Tokens::Op(Operator::Eq),
             "&" => Tokens::Op(Operator::And),
+            "|" => Tokens::Op(Operator::Or),
             ">" => Tokens::Op(Operator::More),
             "<" => Tokens::Op(Operator::Less),
             "==" => Tokens::Op(Operator::EqEq),
@@ -37,13 +40,30 @@ impl From<&str> for Tokens {
             "+=" => Tokens::Op(Operator::PlusIs),
             "*=" => Tokens::Op(Operator::TimesIs),
             "-=" => Tokens::Op(Operator::MinusIs),
+            "/=" => Tokens::Op(Operator::SlashIs),
+            "%" => Tokens::Op(Operator::Percent),
+            "%=" => Tokens::Op(Operator::PercentIs),
+            "<<" => Tokens::Op(Operator::ShiftLeft),
+            ">>" => Tokens::Op(Operator::ShiftRight),
+            "~" => Tokens::Op(Operator::Tilde),
+            ".." => Tokens::Op(Operator::Range),
+            "..=" => Tokens::Op(Operator::RangeEq),
+            "..." => Tokens::Ellipsis,
             // All Keywords
             "return" => Tokens::Kw(Keywords::Return),
             "let" => Tokens::Kw(Keywords::Let),
             "fn" => Tokens::Kw(Keywords::Fn),
             "for" => Tokens::Kw(Keywords::For),
+            "while" => Tokens::Kw(Keywords::While),
+            "break" => Tokens::Kw(Keywords::Break),
+            "continue" => Tokens::Kw(Keywords::Continue),
+            "in" => Tokens::Kw(Keywords::In),
+            "match" => Tokens::Kw(Keywords::Match),
             "if" => Tokens::Kw(Keywords::If),
             "else" => Tokens::Kw(Keywords::Else),
+            "const" => Tokens::Kw(Keywords::Const),
+            "extern" => Tokens::Kw(Keywords::Extern),
+            "import" => Tokens::Kw(Keywords::Import),
             "struct" => Tokens::Kw(Keywords::Struct),
             "enum" => Tokens::Kw(Keywords::Enum),
             "pub" => Tokens::Kw(Keywords::Pub),
@@ -82,8 +102,12 @@ impl From<char> for Tokens {
             // All operator values
             '=' => Tokens::Op(Operator::Eq),
             '&' => Tokens::Op(Operator::And),
+            '|' => Tokens::Op(Operator::Or),
             '>' => Tokens::Op(Operator::More),
             '<' => Tokens::Op(Operator::Less),
+            '%' => Tokens::Op(Operator::Percent),
+            '.' => Tokens::Dot,
+            '~' => Tokens::Op(Operator::Tilde),
             // If none of the above tokens match it will a InvalidToken;
             value => Tokens::InvalidToken(TokenErrorMessages::TokenInvalid(value.to_string())),
         }
@@ -106,9 +130,16 @@ impl ToString for Keywords {
             Keywords::Array => "array".into(),
             Keywords::For => "for".into(),
             Keywords::While => "while".into(),
+            Keywords::Break => "break".into(),
+            Keywords::Continue => "continue".into(),
+            Keywords::In => "in".into(),
+            Keywords::Match => "match".into(),
             Keywords::Pub => "pub".into(),
             Keywords::Enum => "enum".into(),
             Keywords::Struct => "struct".into(),
+            Keywords::Const => "const".into(),
+            Keywords::Extern => "extern".into(),
+            Keywords::Import => "import".into(),
             Keywords::Void => "void".into(),
             Keywords::Return => "return".into(),
         }
@@ -1,3 +1,5 @@
 pub mod lexer;
 pub mod lexer_from;
 pub mod parser;
+pub mod span;
+pub mod trivia;
@@ -26,7 +26,7 @@ pub trait IfElseParser {
     fn parse_case(&mut self, line: usize) -> Result<Case, String>;
 }
 
-impl IfElseParser for Parser {
+impl IfElseParser for Parser<'_> {
     fn parse_if(&mut self, line: usize) -> Result<Expr, String> {
         let Some(if_statement) = self.next() else {
             return Err(ParseErrors::ExpectedNext(line).to_string());
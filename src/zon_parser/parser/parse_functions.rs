@@ -51,7 +51,7 @@ pub trait FunctionCalls {
     ) -> Result<FunctionCall, String>;
 }
 
-impl FunctionParser for Parser {
+impl FunctionParser for Parser<'_> {
     fn parse_params(&mut self) -> Result<Vec<Paramater>, String> {
         let mut paramaters = Vec::new();
         let Some(prev_token) = self.get_prev_token() else {
@@ -155,7 +155,7 @@ impl FunctionParser for Parser {
         let params = self.parse_params()?;
         let return_type = self.parse_function_return_type(function_name.line)?;
         return Ok(Function {
-            name: function_name.value,
+            name: function_name.value.into_owned(),
             block: self.parse_block(return_type.clone())?,
             return_type,
             params,
@@ -164,7 +164,7 @@ impl FunctionParser for Parser {
     }
 }
 
-impl FunctionCalls for Parser {
+impl FunctionCalls for Parser<'_> {
     fn parse_arguments(&mut self) -> Result<Vec<VarTypes>, String> {
         let mut arguments = Vec::new();
         let mut line: usize = 0;
@@ -191,7 +191,7 @@ impl FunctionCalls for Parser {
                         if func_call.token_type == Tokens::OpenBrace {
                             let function_call = FunctionCall {
                                 args: self.parse_arguments()?,
-                                call_to: token.value,
+                                call_to: token.value.into_owned(),
                                 line,
                             };
                             current_value =
@@ -201,7 +201,7 @@ impl FunctionCalls for Parser {
                             self.advance_back(1);
                         }
                     }
-                    current_value = VarTypes::Identifier(token.value, MarkerTypes::None);
+                    current_value = VarTypes::Identifier(token.value.into_owned(), MarkerTypes::None);
                 }
                 Tokens::OpenBracket => {
                     if current_value != VarTypes::None {
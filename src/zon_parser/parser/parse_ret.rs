@@ -2,7 +2,7 @@ use crate::ast::{r#return::Return, types::MarkerTypes};
 
 use super::parser::Parser;
 
-impl Parser {
+impl<'src> Parser<'src> {
     pub fn parse_ret(&mut self, marker_type: MarkerTypes) -> Result<Return, String> {
         let Some(return_value) = self.next() else {
             return Err(format!("Expected a return value but there wasn't one"));
@@ -22,13 +22,13 @@ use crate::zon_parser::lexer::{Keywords, Token, Tokens};
 use super::logical_parser::IfElseParser;
 
 #[derive(Debug)]
-pub struct Parser {
-    tokens: Vec<Token>,
+pub struct Parser<'src> {
+    tokens: Vec<Token<'src>>,
     current_position: usize,
 }
 
-impl Iterator for Parser {
-    type Item = Token;
+impl<'src> Iterator for Parser<'src> {
+    type Item = Token<'src>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let curr = self.tokens.get(self.current_position)?;
@@ -37,9 +37,9 @@ impl Iterator for Parser {
     }
 }
 
-impl Parser {
+impl<'src> Parser<'src> {
     /// Returns the previous from the current position
-    pub fn get_prev_token(&self) -> Option<&Token> {
+    pub fn get_prev_token(&self) -> Option<&Token<'src>> {
         if self.current_position == 0 {
             return None;
         }
@@ -59,7 +59,7 @@ pub trait ParseTokens {
     fn parse_block(&mut self, return_type: MarkerTypes) -> Result<Block, String>;
 }
 
-impl ParseTokens for Parser {
+impl ParseTokens for Parser<'_> {
     fn parse_var_assignment(&mut self) -> Result<Variable, String> {
         let Some(prev_token) = self.get_prev_token() else {
             return Err(ParseErrors::NoPrevToken.to_string());
@@ -73,7 +73,7 @@ impl ParseTokens for Parser {
         };
         Parser::parse_expect(var_name.token_type, Tokens::Identifier)?;
 
-        let _ = variable.set_name(var_name.value, Some(var_name.line));
+        let _ = variable.set_name(var_name.value.into_owned(), Some(var_name.line));
         variable.var_line = prev_token.line;
 
         let Some(variable_assignment) = self.next() else {
@@ -260,7 +260,7 @@ impl ParseTokens for Parser {
                     block.insert_node(if_statement);
                 }
                 Tokens::Identifier => {
-                    let function_call = self.parse_function_call(token.value, token.line)?;
+                    let function_call = self.parse_function_call(token.value.into_owned(), token.line)?;
                     block.insert_node(Expr::FunctionCall(function_call))
                 }
                 Tokens::Kw(Keywords::Fn) => {
@@ -286,9 +286,9 @@ impl ParseTokens for Parser {
     }
 }
 
-impl Parser {
+impl<'src> Parser<'src> {
     /// Create a new instance of a parser
-    pub fn new(tokens: Vec<Token>) -> Self {
+    pub fn new(tokens: Vec<Token<'src>>) -> Self {
         Parser {
             tokens,
             current_position: 0,
@@ -371,13 +371,13 @@ impl Parser {
                 // Consider function call after assignment;
                 if let Tokens::OpenBrace = is_call.token_type {
                     return Ok(VarTypes::FunctionCall(
-                        self.parse_function_call(token.value, line)?,
+                        self.parse_function_call(token.value.into_owned(), line)?,
                         marker_type,
                     ));
                 }
 
                 self.advance_back(1);
-                return Ok(VarTypes::Identifier(token.value, MarkerTypes::None));
+                return Ok(VarTypes::Identifier(token.value.into_owned(), MarkerTypes::None));
             }
             Tokens::String | Tokens::Number | Tokens::FloatNumber | Tokens::Char => {
                 let Ok(var_type) = VarTypes::from_str(&token.value, &token.token_type.to_string(), token.line) else {
@@ -0,0 +1,59 @@
+/// [`Span`]
+/// The location of a piece of source code.
+///
+/// **file_id** the id of the file the span points into, 0 for the main file
+/// **start** the byte offset of the first byte of the span
+/// **end** the byte offset right after the last byte of the span
+/// **line** the line the span starts on, starting at 1
+/// **column** the column the span starts on, starting at 1
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Span {
+    pub file_id: usize,
+    pub start: usize,
+    pub end: usize,
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Span {
+    pub fn new(file_id: usize, start: usize, end: usize, line: usize, column: usize) -> Self {
+        Self {
+            file_id,
+            start,
+            end,
+            line,
+            column,
+        }
+    }
+
+    /// Returns a span that covers both spans, the line and column are taken from whichever span
+    /// starts first.
+    pub fn to(self, other: Span) -> Span {
+        let (first, _) = if self.start <= other.start {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        Span {
+            file_id: self.file_id,
+            start: first.start,
+            end: self.end.max(other.end),
+            line: first.line,
+            column: first.column,
+        }
+    }
+
+    /// The amount of bytes the span covers
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns the source text the span points at, None if the span is out of bounds of source.
+    pub fn source_text<'a>(&self, source: &'a str) -> Option<&'a str> {
+        source.get(self.start..self.end)
+    }
+}
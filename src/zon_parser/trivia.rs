@@ -0,0 +1,77 @@
+use super::{lexer::Token, span::Span};
+
+/// The kinds of source text that have no meaning to the parser
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TriviaKind {
+    /// A run of spaces, tabs and carriage returns
+    Whitespace,
+    /// '\n' or "\r\n"
+    Newline,
+    /// ;
+    Semicolon,
+    /// // comment I am
+    LineComment,
+    /// /* comment I am */
+    BlockComment,
+}
+
+/// [`Trivia`]
+/// A piece of source text that the parser skips, kept so tools can reproduce the source.
+///
+/// **kind** what kind of trivia this is
+/// **text** the exact source text of the trivia
+/// **span** the location of the trivia
+#[derive(Debug, Clone, PartialEq)]
+pub struct Trivia<'src> {
+    pub kind: TriviaKind,
+    pub text: &'src str,
+    pub span: Span,
+}
+
+/// [`TriviaToken`]
+/// A token together with its exact source text and the trivia in front of it.
+///
+/// **token** the token as the parser sees it
+/// **text** the source text of the token, "\"a\\n\"" for the string token with the value "a\n"
+/// **leading_trivia** all the trivia in between the previous token and this token
+#[derive(Debug, Clone, PartialEq)]
+pub struct TriviaToken<'src> {
+    pub token: Token<'src>,
+    pub text: &'src str,
+    pub leading_trivia: Vec<Trivia<'src>>,
+}
+
+/// [`TriviaTokens`]
+/// A lossless token stream, see [`crate::zon_parser::lexer::Lexer::lex_with_trivia`].
+///
+/// **tokens** every token in the source, including invalid tokens
+/// **trailing_trivia** the trivia after the last token
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct TriviaTokens<'src> {
+    pub tokens: Vec<TriviaToken<'src>>,
+    pub trailing_trivia: Vec<Trivia<'src>>,
+}
+
+impl TriviaToken<'_> {
+    /// Returns the leading trivia followed by the text of the token
+    pub fn to_source(&self) -> String {
+        let mut source: String = self
+            .leading_trivia
+            .iter()
+            .map(|trivia| trivia.text)
+            .collect();
+        source.push_str(self.text);
+        source
+    }
+}
+
+impl TriviaTokens<'_> {
+    /// Concatenates all the tokens and trivia, this is exactly the source that was lexed.
+    pub fn to_source(&self) -> String {
+        let mut source: String = self.tokens.iter().map(TriviaToken::to_source).collect();
+        for trivia in &self.trailing_trivia {
+            source.push_str(trivia.text);
+        }
+        source
+    }
+}